@@ -0,0 +1,128 @@
+//! The [axis-variations table][1].
+//!
+//! [1]: https://www.microsoft.com/typography/otspec/avar.htm
+
+use crate::variation::item::Variations;
+use crate::variation::mapping::Mapping;
+
+table! {
+    @position
+    #[doc = "An axis-variations table."]
+    pub AxisVariations {
+        major_version (u16), // majorVersion
+        minor_version (u16), // minorVersion
+        reserved      (u16), // reserved
+        axis_count    (u16), // axisCount
+
+        segment_maps (Vec<SegmentMap>) |this, tape, _| { // axisSegmentMaps
+            match this.major_version {
+                1 | 2 => {}
+                _ => raise!("found an unknown version of the axis-variations table"),
+            }
+            tape.take_given(this.axis_count as usize)
+        },
+
+        mapping_offset (u32) |this, tape, _| { // axisIndexMapOffset
+            if this.major_version == 2 {
+                tape.take()
+            } else {
+                Ok(0)
+            }
+        },
+
+        variations_offset (u32) |this, tape, _| { // varStoreOffset
+            if this.major_version == 2 {
+                tape.take()
+            } else {
+                Ok(0)
+            }
+        },
+
+        mapping (Option<Mapping>) |this, tape, position| {
+            jump_take_maybe!(tape, position, this.mapping_offset)
+        },
+
+        variations (Option<Variations>) |this, tape, position| {
+            jump_take_maybe!(tape, position, this.variations_offset)
+        },
+    }
+}
+
+table! {
+    #[doc = "A segment map of an axis."]
+    pub SegmentMap { // SegmentMaps
+        count (u16), // positionMapCount
+
+        records (Vec<ValueMap>) |this, tape| { // axisValueMaps
+            tape.take_given(this.count as usize)
+        },
+    }
+}
+
+table! {
+    #[doc = "A mapping of a normalized coordinate."]
+    #[derive(Copy)]
+    pub ValueMap { // AxisValueMap
+        from (i16), // fromCoordinate
+        to   (i16), // toCoordinate
+    }
+}
+
+impl AxisVariations {
+    /// Map normalized coordinates given in format F2DOT14.
+    ///
+    /// The segment maps are applied first, and then, in version 2, the deltas
+    /// of the item variation store computed at the resulting coordinates are
+    /// added.
+    pub fn map(&self, coordinates: &[i16]) -> Vec<i16> {
+        let mut values = coordinates
+            .iter()
+            .enumerate()
+            .map(|(i, &value)| match self.segment_maps.get(i) {
+                Some(map) => map.map(value),
+                _ => value,
+            })
+            .collect::<Vec<_>>();
+        if let Some(ref variations) = self.variations {
+            let coordinates = values.clone();
+            for (i, value) in values.iter_mut().enumerate() {
+                let (outer_index, inner_index) = match self.mapping {
+                    Some(ref mapping) => match mapping.get(i) {
+                        Some(indices) => indices,
+                        _ => continue,
+                    },
+                    _ => (0, i as u16),
+                };
+                if let Some(delta) = variations.delta(outer_index, inner_index, &coordinates) {
+                    *value = (*value as f32 + delta).round().clamp(-16384.0, 16384.0) as i16;
+                }
+            }
+        }
+        values
+    }
+}
+
+impl SegmentMap {
+    /// Map a normalized coordinate given in format F2DOT14.
+    pub fn map(&self, value: i16) -> i16 {
+        let records = &self.records;
+        let (first, last) = match (records.first(), records.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return value,
+        };
+        if value <= first.from {
+            return (value as i32 + first.to as i32 - first.from as i32) as i16;
+        }
+        if value >= last.from {
+            return (value as i32 + last.to as i32 - last.from as i32) as i16;
+        }
+        let i = records.partition_point(|record| record.from < value);
+        let (start, end) = (&records[i - 1], &records[i]);
+        if end.from == value {
+            return end.to;
+        }
+        let ratio = (value as i32 - start.from as i32) as f32
+            / (end.from as i32 - start.from as i32) as f32;
+        (start.to as f32 + ratio * (end.to as i32 - start.to as i32) as f32).round() as i16
+    }
+}
@@ -0,0 +1,77 @@
+//! The [font collection][1].
+//!
+//! [1]: https://www.microsoft.com/typography/otspec/otff.htm#collections
+
+use truetype::{Result, Tag, Tape, Value};
+
+/// The header of a font collection.
+#[derive(Clone, Debug)]
+pub enum Header {
+    /// Version 1.0.
+    Version1(Header1),
+    /// Version 2.0.
+    Version2(Header2),
+}
+
+table! {
+    #[doc = "The header of a font collection of version 1.0."]
+    pub Header1 { // TTCHeader
+        tag           (Tag) = { Tag(*b"ttcf") }, // ttcTag
+        major_version (u16) = { 1 }, // majorVersion
+        minor_version (u16) = { 0 }, // minorVersion
+        count         (u32), // numFonts
+
+        offsets (Vec<u32>) |this, tape| { // tableDirectoryOffsets
+            tape.take_given(this.count as usize)
+        },
+    }
+}
+
+table! {
+    #[doc = "The header of a font collection of version 2.0."]
+    pub Header2 { // TTCHeader
+        tag           (Tag) = { Tag(*b"ttcf") }, // ttcTag
+        major_version (u16) = { 2 }, // majorVersion
+        minor_version (u16) = { 0 }, // minorVersion
+        count         (u32), // numFonts
+
+        offsets (Vec<u32>) |this, tape| { // tableDirectoryOffsets
+            tape.take_given(this.count as usize)
+        },
+
+        signature_tag    (Tag), // dsigTag
+        signature_length (u32), // dsigLength
+        signature_offset (u32), // dsigOffset
+    }
+}
+
+impl Header {
+    /// Return the offsets of the offset tables of the fonts.
+    pub fn offsets(&self) -> &[u32] {
+        match *self {
+            Header::Version1(ref header) => &header.offsets,
+            Header::Version2(ref header) => &header.offsets,
+        }
+    }
+}
+
+impl Default for Header {
+    #[inline]
+    fn default() -> Self {
+        Header::Version1(Header1::default())
+    }
+}
+
+impl Value for Header {
+    fn read<T: Tape>(tape: &mut T) -> Result<Self> {
+        let major_version = tape.stay(|tape| {
+            tape.take::<Tag>()?;
+            tape.take::<u16>()
+        })?;
+        Ok(match major_version {
+            1 => Header::Version1(tape.take()?),
+            2 => Header::Version2(tape.take()?),
+            _ => raise!("found an unknown version of the font collection"),
+        })
+    }
+}
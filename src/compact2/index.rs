@@ -0,0 +1,78 @@
+//! The indices.
+
+use std::io::Read;
+use truetype::{Result, Tape, Value};
+
+table! {
+    @define
+    #[doc = "An index."]
+    pub Index {
+        count       (u32         ), // count
+        offset_size (u8          ), // offSize
+        offsets     (Vec<u32>    ), // offset
+        data        (Vec<Vec<u8>>), // data
+    }
+}
+
+impl Index {
+    /// Return the data of an element.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&[u8]> {
+        self.data.get(index).map(|data| &data[..])
+    }
+
+    /// Return the number of elements.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Check if there are no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+}
+
+impl Value for Index {
+    fn read<T: Tape>(tape: &mut T) -> Result<Self> {
+        let count = tape.take::<u32>()?;
+        if count == 0 {
+            return Ok(Index::default());
+        }
+        let offset_size = tape.take::<u8>()?;
+        let mut offsets = vec![];
+        for i in 0..(count as usize + 1) {
+            let offset = match offset_size {
+                1 => tape.take::<u8>()? as u32,
+                2 => tape.take::<u16>()? as u32,
+                3 => {
+                    let value = tape.take::<[u8; 3]>()?;
+                    u32::from_be_bytes([0, value[0], value[1], value[2]])
+                }
+                4 => tape.take::<u32>()?,
+                _ => raise!("found a malformed offset size"),
+            };
+            if i == 0 && offset != 1 || i > 0 && offset < offsets[i - 1] {
+                raise!("found a malformed index");
+            }
+            offsets.push(offset);
+        }
+        let size = offsets[count as usize] as usize - 1;
+        let mut buffer = vec![];
+        Read::take(&mut *tape, size as u64).read_to_end(&mut buffer)?;
+        if buffer.len() != size {
+            raise!("found a malformed index");
+        }
+        let data = offsets
+            .windows(2)
+            .map(|pair| buffer[(pair[0] as usize - 1)..(pair[1] as usize - 1)].to_vec())
+            .collect();
+        Ok(Index {
+            count: count,
+            offset_size: offset_size,
+            offsets: offsets,
+            data: data,
+        })
+    }
+}
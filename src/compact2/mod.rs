@@ -1,3 +1,163 @@
 //! The [compact file format][1] of version 2.0.
 //!
 //! [1]: https://www.microsoft.com/typography/otspec/cff2.htm
+
+use std::io::Cursor;
+use truetype::{GlyphID, Result, Tape, Value};
+
+use crate::variation::item::Variations;
+
+mod index;
+mod operation;
+mod outline;
+mod selector;
+
+pub use index::Index;
+pub use operation::{Operand, Operations, Operator};
+pub use outline::{Point, Segment};
+pub use selector::{Range3, Range4, Selector, Selector0, Selector3, Selector4};
+
+/// A font set.
+#[derive(Clone, Debug)]
+pub struct FontSet {
+    /// The header.
+    pub header: Header,
+    /// The top dictionary.
+    pub top_dictionary: Operations,
+    /// The global subroutines.
+    pub global_subroutines: Index,
+    /// The charstrings of the glyphs.
+    pub char_strings: Index,
+    /// The item variations referred to by the charstrings if present.
+    pub variations: Option<Variations>,
+    /// The font dictionaries.
+    pub font_dictionaries: Vec<Operations>,
+    /// The selector of the font dictionaries if present.
+    pub font_selector: Option<Selector>,
+    /// The private dictionaries, one per font dictionary.
+    pub private_dictionaries: Vec<Operations>,
+    /// The local subroutines, one index per private dictionary.
+    pub local_subroutines: Vec<Index>,
+}
+
+table! {
+    #[doc = "A header."]
+    #[derive(Copy)]
+    pub Header {
+        major               (u8 ), // majorVersion
+        minor               (u8 ), // minorVersion
+        header_size         (u8 ), // headerSize
+        top_dictionary_size (u16), // topDictLength
+    }
+}
+
+macro_rules! get(
+    (@single $operations:expr, $operator:ident) => (
+        match $operations.get_single(Operator::$operator) {
+            Some(value) if value >= 0.0 && value as u32 as Operand == value => Some(value as u32),
+            Some(_) => raise!(concat!("found a malformed operation with operator ", stringify!($operator))),
+            _ => None,
+        }
+    );
+    (@double $operations:expr, $operator:ident) => (
+        match $operations.get_double(Operator::$operator) {
+            Some((value0, value1))
+                if value0 >= 0.0
+                    && value1 >= 0.0
+                    && value0 as u32 as Operand == value0
+                    && value1 as u32 as Operand == value1 =>
+            {
+                Some((value0 as u32, value1 as u32))
+            }
+            Some(_) => raise!(concat!("found a malformed operation with operator ", stringify!($operator))),
+            _ => None,
+        }
+    );
+);
+
+impl FontSet {
+    /// Return the index of the font dictionary of a glyph.
+    pub fn font_index(&self, glyph_id: GlyphID) -> Option<usize> {
+        let index = match self.font_selector {
+            Some(ref selector) => selector.get(glyph_id)?,
+            _ => 0,
+        };
+        if index < self.font_dictionaries.len() {
+            Some(index)
+        } else {
+            None
+        }
+    }
+}
+
+impl Value for FontSet {
+    fn read<T: Tape>(tape: &mut T) -> Result<Self> {
+        let position = tape.position()?;
+        let header = tape.take::<Header>()?;
+        if header.major != 2 {
+            raise!("found an unknown version of the compact font format");
+        }
+        tape.jump(position + header.header_size as u64)?;
+        let data = tape.take_bytes(header.top_dictionary_size as usize)?;
+        let top_dictionary = Cursor::new(data).take_given::<Operations>(None)?;
+        let global_subroutines = tape.take::<Index>()?;
+        let char_strings: Index = match get!(@single top_dictionary, CharStrings) {
+            Some(offset) => jump_take!(@unwrap tape, position, offset),
+            _ => raise!("failed to find an operation with operator CharStrings"),
+        };
+        let variations = match get!(@single top_dictionary, VariationStore) {
+            Some(offset) => Some(jump_take!(@unwrap tape, position, offset + 2)),
+            _ => None,
+        };
+        let font_dictionaries: Vec<Operations> = match get!(@single top_dictionary, FDArray) {
+            Some(offset) => {
+                let index: Index = jump_take!(@unwrap tape, position, offset);
+                let mut values = Vec::with_capacity(index.len());
+                for data in &index.data {
+                    values.push(Cursor::new(data).take_given(None)?);
+                }
+                values
+            }
+            _ => raise!("failed to find an operation with operator FDArray"),
+        };
+        let font_selector = match get!(@single top_dictionary, FDSelect) {
+            Some(offset) => {
+                let count = char_strings.len();
+                Some(jump_take_given!(@unwrap tape, position, offset, count))
+            }
+            _ if font_dictionaries.len() > 1 => {
+                raise!("failed to find an operation with operator FDSelect")
+            }
+            _ => None,
+        };
+        let mut private_dictionaries = Vec::with_capacity(font_dictionaries.len());
+        let mut local_subroutines = Vec::with_capacity(font_dictionaries.len());
+        for dictionary in &font_dictionaries {
+            let (size, offset) = match get!(@double dictionary, Private) {
+                Some(value) => value,
+                _ => raise!("failed to find an operation with operator Private"),
+            };
+            tape.jump(position + offset as u64)?;
+            let data = tape.take_bytes(size as usize)?;
+            let private = Cursor::new(data).take_given::<Operations>(variations.as_ref())?;
+            local_subroutines.push(match get!(@single private, Subrs) {
+                Some(subroutines_offset) => {
+                    jump_take!(@unwrap tape, position, offset + subroutines_offset)
+                }
+                _ => Index::default(),
+            });
+            private_dictionaries.push(private);
+        }
+        Ok(FontSet {
+            header: header,
+            top_dictionary: top_dictionary,
+            global_subroutines: global_subroutines,
+            char_strings: char_strings,
+            variations: variations,
+            font_dictionaries: font_dictionaries,
+            font_selector: font_selector,
+            private_dictionaries: private_dictionaries,
+            local_subroutines: local_subroutines,
+        })
+    }
+}
@@ -0,0 +1,248 @@
+//! The operations.
+
+use std::collections::HashMap;
+use truetype::{Result, Tape, Walue};
+
+use crate::variation::item::Variations;
+
+/// An operand.
+pub type Operand = f32;
+
+/// A collection of operations of a dictionary.
+#[derive(Clone, Debug, Default)]
+pub struct Operations {
+    /// The operands at the default location.
+    pub values: HashMap<Operator, Vec<Operand>>,
+    /// The deltas of blended operands for the regions of the variation store.
+    ///
+    /// There is one vector per operand, and it is empty if the operand is not
+    /// blended.
+    pub deltas: HashMap<Operator, Vec<Vec<Operand>>>,
+}
+
+macro_rules! operator {
+    ($(#[$attribute:meta])* pub $name:ident {
+        $($code:pat => $variant:ident [$($operand:expr),*],)+
+    }) => (
+        $(#[$attribute])*
+        #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+        pub enum $name { $($variant,)+ }
+
+        impl $name {
+            /// Return the operator given its code if known.
+            pub fn from(code: u16) -> Option<Self> {
+                Some(match code {
+                    $($code => $name::$variant,)+
+                    _ => return None,
+                })
+            }
+
+            /// Return the default operands if any.
+            pub fn default(&self) -> Option<&'static [Operand]> {
+                match *self {
+                    $($name::$variant => operator!(@default [$($operand),*]),)+
+                }
+            }
+        }
+    );
+    (@default []) => (None);
+    (@default [$($operand:expr),+]) => ({
+        const OPERANDS: &[Operand] = &[$($operand as Operand),+];
+        Some(OPERANDS)
+    });
+}
+
+operator! {
+    #[doc = "An operator."]
+    pub Operator {
+        0x06 => BlueValues [],
+        0x07 => OtherBlues [],
+        0x08 => FamilyBlues [],
+        0x09 => FamilyOtherBlues [],
+        0x0a => StdHW [],
+        0x0b => StdVW [],
+        0x11 => CharStrings [],
+        0x12 => Private [],
+        0x13 => Subrs [],
+        0x16 => VSIndex [0],
+        0x18 => VariationStore [],
+        0x0c07 => FontMatrix [0.001, 0.0, 0.0, 0.001, 0.0, 0.0],
+        0x0c09 => BlueScale [0.039625],
+        0x0c0a => BlueShift [7],
+        0x0c0b => BlueFuzz [1],
+        0x0c0c => StemSnapH [],
+        0x0c0d => StemSnapV [],
+        0x0c11 => LanguageGroup [0],
+        0x0c12 => ExpansionFactor [0.06],
+        0x0c24 => FDArray [],
+        0x0c25 => FDSelect [],
+    }
+}
+
+const BLEND: u16 = 0x17;
+
+impl Operations {
+    /// Return the operands of an operation at the default location.
+    #[inline]
+    pub fn get(&self, operator: Operator) -> Option<&[Operand]> {
+        match self.values.get(&operator) {
+            Some(operands) => Some(operands),
+            _ => operator.default(),
+        }
+    }
+
+    /// Return the operands of an operation given the scalars of the regions
+    /// of the variation store.
+    ///
+    /// The scalars are to be computed for the regions referred to by the
+    /// record of the variation store given by `VSIndex`.
+    pub fn get_blended(&self, operator: Operator, scalars: &[f32]) -> Option<Vec<Operand>> {
+        let mut operands = self.get(operator)?.to_vec();
+        if let Some(deltas) = self.deltas.get(&operator) {
+            for (operand, deltas) in operands.iter_mut().zip(deltas) {
+                for (delta, scalar) in deltas.iter().zip(scalars) {
+                    *operand += delta * scalar;
+                }
+            }
+        }
+        Some(operands)
+    }
+
+    #[doc(hidden)]
+    pub fn get_single(&self, operator: Operator) -> Option<Operand> {
+        self.get(operator)
+            .and_then(|operands| operands.first().cloned())
+    }
+
+    #[doc(hidden)]
+    pub fn get_double(&self, operator: Operator) -> Option<(Operand, Operand)> {
+        match self.get(operator) {
+            Some(operands) if operands.len() > 1 => Some((operands[0], operands[1])),
+            _ => None,
+        }
+    }
+}
+
+impl<'l> Walue<'l> for Operations {
+    /// The variation store needed for resolving blended operands.
+    type Parameter = Option<&'l Variations>;
+
+    fn read<T: Tape>(tape: &mut T, variations: Option<&'l Variations>) -> Result<Self> {
+        use std::io::ErrorKind;
+
+        let mut values = HashMap::new();
+        let mut deltas = HashMap::new();
+        let mut stack: Vec<(Operand, Vec<Operand>)> = vec![];
+        let mut index = 0;
+        loop {
+            let code = match tape.peek::<u8>() {
+                Ok(code) => code,
+                Err(error) if error.kind() == ErrorKind::UnexpectedEof => break,
+                Err(error) => return Err(error),
+            };
+            match code {
+                0x1c | 0x1d | 0x1e | 0x20..=0xfe => {
+                    stack.push((number(tape)?, vec![]));
+                    continue;
+                }
+                _ => {}
+            }
+            let code = if code == 0x0c {
+                tape.take::<u16>()?
+            } else {
+                tape.take::<u8>()? as u16
+            };
+            if code == BLEND {
+                let count = match stack.pop() {
+                    Some((count, _)) if count >= 0.0 => count as usize,
+                    _ => raise!("found a malformed blend operation"),
+                };
+                let region_count =
+                    match variations.and_then(|variations| variations.records.get(index)) {
+                        Some(record) => record.region_indices.len(),
+                        _ => raise!("found a malformed blend operation"),
+                    };
+                let total = count * (region_count + 1);
+                if stack.len() < total {
+                    raise!("found a malformed blend operation");
+                }
+                let operands = stack.split_off(stack.len() - total);
+                let (defaults, rest) = operands.split_at(count);
+                for (i, &(value, _)) in defaults.iter().enumerate() {
+                    let deltas = rest[(i * region_count)..((i + 1) * region_count)]
+                        .iter()
+                        .map(|&(delta, _)| delta)
+                        .collect();
+                    stack.push((value, deltas));
+                }
+                continue;
+            }
+            let operands = std::mem::take(&mut stack);
+            let operator = match Operator::from(code) {
+                Some(operator) => operator,
+                _ => continue,
+            };
+            if operator == Operator::VSIndex {
+                match operands.first() {
+                    Some(&(value, _)) if value >= 0.0 => index = value as usize,
+                    _ => raise!("found a malformed operation with operator VSIndex"),
+                }
+            }
+            if operands.iter().any(|(_, deltas)| !deltas.is_empty()) {
+                deltas.insert(
+                    operator,
+                    operands.iter().map(|(_, deltas)| deltas.clone()).collect(),
+                );
+            }
+            values.insert(
+                operator,
+                operands.into_iter().map(|(value, _)| value).collect(),
+            );
+        }
+        Ok(Operations {
+            values: values,
+            deltas: deltas,
+        })
+    }
+}
+
+fn number<T: Tape>(tape: &mut T) -> Result<Operand> {
+    let first = tape.take::<u8>()?;
+    Ok(match first {
+        0x20..=0xf6 => (first as i32 - 139) as Operand,
+        0xf7..=0xfa => ((first as i32 - 247) * 256 + tape.take::<u8>()? as i32 + 108) as Operand,
+        0xfb..=0xfe => (-(first as i32 - 251) * 256 - tape.take::<u8>()? as i32 - 108) as Operand,
+        0x1c => tape.take::<i16>()? as Operand,
+        0x1d => tape.take::<u32>()? as i32 as Operand,
+        0x1e => real(tape)?,
+        _ => raise!("found a malformed number"),
+    })
+}
+
+fn real<T: Tape>(tape: &mut T) -> Result<Operand> {
+    let mut buffer = String::new();
+    let mut byte = 0;
+    let mut high = true;
+    loop {
+        let nibble = if high {
+            byte = tape.take::<u8>()?;
+            byte >> 4
+        } else {
+            byte & 0x0f
+        };
+        high = !high;
+        match nibble {
+            0..=9 => buffer.push((b'0' + nibble) as char),
+            0x0a => buffer.push('.'),
+            0x0b => buffer.push('e'),
+            0x0c => buffer.push_str("e-"),
+            0x0e => buffer.push('-'),
+            0x0f => break,
+            _ => raise!("found a malformed number"),
+        }
+    }
+    match buffer.parse() {
+        Ok(value) => Ok(value),
+        _ => raise!("found a malformed number"),
+    }
+}
@@ -0,0 +1,408 @@
+//! The outlines.
+
+use truetype::{GlyphID, Result};
+
+use crate::compact2::{FontSet, Index, Operator};
+
+const MAXIMUM_DEPTH: usize = 10;
+const MAXIMUM_STACK: usize = 513;
+
+/// A point.
+pub type Point = (f32, f32);
+
+/// A segment of an outline.
+///
+/// Each contour starts with a move, and contours are implicitly closed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Segment {
+    /// A move to a point.
+    Move(Point),
+    /// A line to a point.
+    Line(Point),
+    /// A cubic Bézier curve given by two control points and an end point.
+    Curve(Point, Point, Point),
+}
+
+impl FontSet {
+    /// Return the outline of a glyph at normalized coordinates given in format
+    /// F2DOT14.
+    pub fn outline(&self, glyph_id: GlyphID, coordinates: &[i16]) -> Result<Vec<Segment>> {
+        let char_string = match self.char_strings.get(glyph_id as usize) {
+            Some(char_string) => char_string,
+            _ => raise!("found no char string for a glyph"),
+        };
+        let font_index = match self.font_index(glyph_id) {
+            Some(font_index) => font_index,
+            _ => raise!("found no font dictionary for a glyph"),
+        };
+        let vsindex = match self.private_dictionaries[font_index].get_single(Operator::VSIndex) {
+            Some(value) if value >= 0.0 => value as usize,
+            _ => raise!("found a malformed operation with operator VSIndex"),
+        };
+        let mut interpreter = Interpreter {
+            font_set: self,
+            local_subroutines: &self.local_subroutines[font_index],
+            coordinates: coordinates,
+            scalars: None,
+            vsindex: vsindex,
+            stack: vec![],
+            stem_count: 0,
+            position: (0.0, 0.0),
+            segments: vec![],
+        };
+        interpreter.run(char_string, 0)?;
+        Ok(interpreter.segments)
+    }
+}
+
+struct Interpreter<'l> {
+    font_set: &'l FontSet,
+    local_subroutines: &'l Index,
+    coordinates: &'l [i16],
+    scalars: Option<Vec<f32>>,
+    vsindex: usize,
+    stack: Vec<f32>,
+    stem_count: usize,
+    position: Point,
+    segments: Vec<Segment>,
+}
+
+impl<'l> Interpreter<'l> {
+    /// Run a program.
+    fn run(&mut self, data: &[u8], depth: usize) -> Result<()> {
+        if depth > MAXIMUM_DEPTH {
+            raise!("found too deeply nested subroutines");
+        }
+        let mut i = 0;
+        while i < data.len() {
+            let code = data[i];
+            i += 1;
+            let value = match code {
+                32..=246 => Some(code as i32 - 139),
+                247..=250 => Some((code as i32 - 247) * 256 + byte(data, &mut i)? as i32 + 108),
+                251..=254 => Some(-(code as i32 - 251) * 256 - byte(data, &mut i)? as i32 - 108),
+                28 => {
+                    let high = byte(data, &mut i)?;
+                    Some(i16::from_be_bytes([high, byte(data, &mut i)?]) as i32)
+                }
+                255 => {
+                    let mut value = [0; 4];
+                    for byte_ in value.iter_mut() {
+                        *byte_ = byte(data, &mut i)?;
+                    }
+                    self.push(i32::from_be_bytes(value) as f32 / 65536.0)?;
+                    continue;
+                }
+                _ => None,
+            };
+            if let Some(value) = value {
+                self.push(value as f32)?;
+                continue;
+            }
+            match code {
+                // hstem, vstem, hstemhm, vstemhm
+                1 | 3 | 18 | 23 => {
+                    self.stem_count += self.stack.len() / 2;
+                }
+                // vmoveto
+                4 => {
+                    let dy = self.last(1)?[0];
+                    self.move_by(0.0, dy);
+                }
+                // rlineto
+                5 => {
+                    for pair in self.stack.clone().chunks_exact(2) {
+                        self.line_by(pair[0], pair[1]);
+                    }
+                }
+                // hlineto, vlineto
+                6 | 7 => {
+                    let mut horizontal = code == 6;
+                    for &delta in &self.stack.clone() {
+                        if horizontal {
+                            self.line_by(delta, 0.0);
+                        } else {
+                            self.line_by(0.0, delta);
+                        }
+                        horizontal = !horizontal;
+                    }
+                }
+                // rrcurveto
+                8 => {
+                    for values in self.stack.clone().chunks_exact(6) {
+                        self.curve_by(values);
+                    }
+                }
+                // callsubr
+                10 => {
+                    let subroutines = self.local_subroutines;
+                    self.call(subroutines, depth)?;
+                    continue;
+                }
+                // vsindex
+                15 => {
+                    match self.stack.pop() {
+                        Some(value) if value >= 0.0 => self.vsindex = value as usize,
+                        _ => raise!("found a malformed vsindex operation"),
+                    }
+                    self.scalars = None;
+                }
+                // blend
+                16 => {
+                    self.blend()?;
+                    continue;
+                }
+                // hintmask, cntrmask
+                19 | 20 => {
+                    self.stem_count += self.stack.len() / 2;
+                    i += (self.stem_count + 7) / 8;
+                    if i > data.len() {
+                        raise!("found a malformed hint mask");
+                    }
+                }
+                // rmoveto
+                21 => {
+                    let values = self.last(2)?;
+                    self.move_by(values[0], values[1]);
+                }
+                // hmoveto
+                22 => {
+                    let dx = self.last(1)?[0];
+                    self.move_by(dx, 0.0);
+                }
+                // rcurveline
+                24 => {
+                    let stack = self.stack.clone();
+                    if stack.len() < 8 || (stack.len() - 2) % 6 != 0 {
+                        raise!("found a malformed rcurveline operation");
+                    }
+                    let (curves, line) = stack.split_at(stack.len() - 2);
+                    for values in curves.chunks_exact(6) {
+                        self.curve_by(values);
+                    }
+                    self.line_by(line[0], line[1]);
+                }
+                // rlinecurve
+                25 => {
+                    let stack = self.stack.clone();
+                    if stack.len() < 8 || (stack.len() - 6) % 2 != 0 {
+                        raise!("found a malformed rlinecurve operation");
+                    }
+                    let (lines, curve) = stack.split_at(stack.len() - 6);
+                    for pair in lines.chunks_exact(2) {
+                        self.line_by(pair[0], pair[1]);
+                    }
+                    self.curve_by(curve);
+                }
+                // vvcurveto
+                26 => {
+                    let stack = self.stack.clone();
+                    let (mut dx1, values) = match stack.len() % 4 {
+                        1 => (stack[0], &stack[1..]),
+                        _ => (0.0, &stack[..]),
+                    };
+                    for values in values.chunks_exact(4) {
+                        self.curve_by(&[dx1, values[0], values[1], values[2], 0.0, values[3]]);
+                        dx1 = 0.0;
+                    }
+                }
+                // hhcurveto
+                27 => {
+                    let stack = self.stack.clone();
+                    let (mut dy1, values) = match stack.len() % 4 {
+                        1 => (stack[0], &stack[1..]),
+                        _ => (0.0, &stack[..]),
+                    };
+                    for values in values.chunks_exact(4) {
+                        self.curve_by(&[values[0], dy1, values[1], values[2], values[3], 0.0]);
+                        dy1 = 0.0;
+                    }
+                }
+                // callgsubr
+                29 => {
+                    let subroutines = &self.font_set.global_subroutines;
+                    self.call(subroutines, depth)?;
+                    continue;
+                }
+                // vhcurveto, hvcurveto
+                30 | 31 => {
+                    let stack = self.stack.clone();
+                    let mut horizontal = code == 31;
+                    let count = stack.len() / 4;
+                    for (j, values) in stack.chunks_exact(4).enumerate() {
+                        let last = if j + 1 == count && stack.len() % 4 == 1 {
+                            stack[stack.len() - 1]
+                        } else {
+                            0.0
+                        };
+                        if horizontal {
+                            self.curve_by(&[values[0], 0.0, values[1], values[2], last, values[3]]);
+                        } else {
+                            self.curve_by(&[0.0, values[0], values[1], values[2], values[3], last]);
+                        }
+                        horizontal = !horizontal;
+                    }
+                }
+                12 => {
+                    let code = byte(data, &mut i)?;
+                    self.flex(code)?;
+                }
+                _ => raise!("found an unknown char-string operator"),
+            }
+            self.stack.clear();
+        }
+        Ok(())
+    }
+
+    fn call(&mut self, subroutines: &Index, depth: usize) -> Result<()> {
+        let count = subroutines.len();
+        let bias = if count < 1240 {
+            107
+        } else if count < 33900 {
+            1131
+        } else {
+            32768
+        };
+        let index = match self.stack.pop() {
+            Some(value) => value as i32 + bias,
+            _ => raise!("found a malformed subroutine call"),
+        };
+        match subroutines.get(index as usize) {
+            Some(data) if index >= 0 => self.run(data, depth + 1),
+            _ => raise!("found a malformed subroutine call"),
+        }
+    }
+
+    fn blend(&mut self) -> Result<()> {
+        let count = match self.stack.pop() {
+            Some(value) if value >= 0.0 => value as usize,
+            _ => raise!("found a malformed blend operation"),
+        };
+        let scalars = self.scalars()?;
+        let region_count = scalars.len();
+        let total = count * (region_count + 1);
+        if self.stack.len() < total {
+            raise!("found a malformed blend operation");
+        }
+        let start = self.stack.len() - total;
+        for i in 0..count {
+            let deltas = &self.stack[(start + count + i * region_count)..][..region_count];
+            let delta = deltas
+                .iter()
+                .zip(&scalars)
+                .map(|(delta, scalar)| delta * scalar)
+                .sum::<f32>();
+            self.stack[start + i] += delta;
+        }
+        self.stack.truncate(start + count);
+        Ok(())
+    }
+
+    fn flex(&mut self, code: u8) -> Result<()> {
+        let stack = self.stack.clone();
+        match (code, stack.len()) {
+            // hflex
+            (34, 7) => {
+                let (dx1, dx2, dy2, dx3, dx4, dx5, dx6) = (
+                    stack[0], stack[1], stack[2], stack[3], stack[4], stack[5], stack[6],
+                );
+                self.curve_by(&[dx1, 0.0, dx2, dy2, dx3, 0.0]);
+                self.curve_by(&[dx4, 0.0, dx5, -dy2, dx6, 0.0]);
+            }
+            // flex
+            (35, 13) => {
+                self.curve_by(&stack[0..6]);
+                self.curve_by(&stack[6..12]);
+            }
+            // hflex1
+            (36, 9) => {
+                let dy6 = -(stack[1] + stack[3] + stack[7]);
+                self.curve_by(&[stack[0], stack[1], stack[2], stack[3], stack[4], 0.0]);
+                self.curve_by(&[stack[5], 0.0, stack[6], stack[7], stack[8], dy6]);
+            }
+            // flex1
+            (37, 11) => {
+                let dx = stack[0] + stack[2] + stack[4] + stack[6] + stack[8];
+                let dy = stack[1] + stack[3] + stack[5] + stack[7] + stack[9];
+                let (dx6, dy6) = if dx.abs() > dy.abs() {
+                    (stack[10], -dy)
+                } else {
+                    (-dx, stack[10])
+                };
+                self.curve_by(&stack[0..6]);
+                self.curve_by(&[stack[6], stack[7], stack[8], stack[9], dx6, dy6]);
+            }
+            (34..=37, _) => raise!("found a malformed flex operation"),
+            _ => raise!("found an unknown char-string operator"),
+        }
+        Ok(())
+    }
+
+    fn scalars(&mut self) -> Result<Vec<f32>> {
+        if let Some(ref scalars) = self.scalars {
+            return Ok(scalars.clone());
+        }
+        let variations = match self.font_set.variations {
+            Some(ref variations) => variations,
+            _ => raise!("found a malformed blend operation"),
+        };
+        let record = match variations.records.get(self.vsindex) {
+            Some(record) => record,
+            _ => raise!("found a malformed vsindex operation"),
+        };
+        let mut scalars = Vec::with_capacity(record.region_indices.len());
+        for &index in &record.region_indices {
+            match variations.regions.records.get(index as usize) {
+                Some(region) => scalars.push(region.scalar(self.coordinates)),
+                _ => raise!("found a malformed item variation store"),
+            }
+        }
+        self.scalars = Some(scalars.clone());
+        Ok(scalars)
+    }
+
+    fn push(&mut self, value: f32) -> Result<()> {
+        if self.stack.len() >= MAXIMUM_STACK {
+            raise!("found a char-string stack overflow");
+        }
+        self.stack.push(value);
+        Ok(())
+    }
+
+    fn last(&self, count: usize) -> Result<&[f32]> {
+        match self.stack.len().checked_sub(count) {
+            Some(start) => Ok(&self.stack[start..]),
+            _ => raise!("found a char-string stack underflow"),
+        }
+    }
+
+    fn move_by(&mut self, dx: f32, dy: f32) {
+        self.position = (self.position.0 + dx, self.position.1 + dy);
+        self.segments.push(Segment::Move(self.position));
+    }
+
+    fn line_by(&mut self, dx: f32, dy: f32) {
+        self.position = (self.position.0 + dx, self.position.1 + dy);
+        self.segments.push(Segment::Line(self.position));
+    }
+
+    fn curve_by(&mut self, values: &[f32]) {
+        let (x, y) = self.position;
+        let a = (x + values[0], y + values[1]);
+        let b = (a.0 + values[2], a.1 + values[3]);
+        let c = (b.0 + values[4], b.1 + values[5]);
+        self.position = c;
+        self.segments.push(Segment::Curve(a, b, c));
+    }
+}
+
+#[inline]
+fn byte(data: &[u8], i: &mut usize) -> Result<u8> {
+    match data.get(*i) {
+        Some(&value) => {
+            *i += 1;
+            Ok(value)
+        }
+        _ => raise!("found a malformed char string"),
+    }
+}
@@ -0,0 +1,123 @@
+//! The font selectors.
+
+use truetype::{GlyphID, Result, Tape, Walue};
+
+/// A font selector.
+#[derive(Clone, Debug)]
+pub enum Selector {
+    /// Format 0.
+    Format0(Selector0),
+    /// Format 3.
+    Format3(Selector3),
+    /// Format 4.
+    Format4(Selector4),
+}
+
+table! {
+    @define
+    #[doc = "A font selector in format 0."]
+    pub Selector0 {
+        format     (u8     ), // format
+        fd_indices (Vec<u8>), // fds
+    }
+}
+
+table! {
+    #[doc = "A font selector in format 3."]
+    pub Selector3 {
+        format      (u8) = { 3 }, // format
+        range_count (u16), // nRanges
+
+        ranges (Vec<Range3>) |this, tape| { // Range3
+            tape.take_given(this.range_count as usize)
+        },
+
+        sentinel (u16), // sentinel
+    }
+}
+
+table! {
+    #[doc = "A range of a font selector in format 3."]
+    #[derive(Copy)]
+    pub Range3 {
+        first_glyph_id (u16), // first
+        fd_index       (u8 ), // fd
+    }
+}
+
+table! {
+    #[doc = "A font selector in format 4."]
+    pub Selector4 {
+        format      (u8) = { 4 }, // format
+        range_count (u32), // nRanges
+
+        ranges (Vec<Range4>) |this, tape| { // Range4
+            tape.take_given(this.range_count as usize)
+        },
+
+        sentinel (u32), // sentinel
+    }
+}
+
+table! {
+    #[doc = "A range of a font selector in format 4."]
+    #[derive(Copy)]
+    pub Range4 {
+        first_glyph_id (u32), // first
+        fd_index       (u16), // fd
+    }
+}
+
+impl Selector {
+    /// Return the index of the font dictionary of a glyph.
+    pub fn get(&self, glyph_id: GlyphID) -> Option<usize> {
+        macro_rules! find(
+            ($selector:ident) => ({
+                let glyph_id = glyph_id as u32;
+                if glyph_id >= $selector.sentinel as u32 {
+                    return None;
+                }
+                let i = $selector
+                    .ranges
+                    .partition_point(|range| range.first_glyph_id as u32 <= glyph_id);
+                Some($selector.ranges[i.checked_sub(1)?].fd_index as usize)
+            });
+        );
+        match *self {
+            Selector::Format0(ref selector) => selector
+                .fd_indices
+                .get(glyph_id as usize)
+                .map(|&index| index as usize),
+            Selector::Format3(ref selector) => find!(selector),
+            Selector::Format4(ref selector) => find!(selector),
+        }
+    }
+}
+
+impl Walue<'static> for Selector {
+    type Parameter = usize;
+
+    fn read<T: Tape>(tape: &mut T, glyph_count: usize) -> Result<Self> {
+        Ok(match tape.peek::<u8>()? {
+            0 => Selector::Format0(tape.take_given(glyph_count)?),
+            3 => Selector::Format3(tape.take()?),
+            4 => Selector::Format4(tape.take()?),
+            _ => raise!("found an unknown format of the font selector"),
+        })
+    }
+}
+
+impl Walue<'static> for Selector0 {
+    type Parameter = usize;
+
+    fn read<T: Tape>(tape: &mut T, glyph_count: usize) -> Result<Self> {
+        let format = tape.take()?;
+        if format != 0 {
+            raise!("found a malformed font selector");
+        }
+        Ok(Selector0 {
+            format: format,
+            fd_indices: tape.take_given(glyph_count)?,
+        })
+    }
+}
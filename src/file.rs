@@ -2,22 +2,54 @@ use std::io::{Read, Seek};
 use std::ops::Deref;
 use truetype::{Result, Tag, Tape};
 
-use crate::Font;
+use crate::collection::Header;
+use crate::{woff, woff2, Font, Options};
 
 /// A file.
 pub struct File {
+    /// The header of the font collection if the file is a collection.
+    pub collection: Option<Header>,
     /// The fonts.
     pub fonts: Vec<Font>,
 }
 
 impl File {
     /// Read a file.
+    ///
+    /// Font collections and fonts in the Web Open Font Format of versions 1.0
+    /// and 2.0 are recognized by their signatures.
+    #[inline]
     pub fn read<T: Read + Seek>(tape: &mut T) -> Result<File> {
-        if Tag::from(Tape::peek::<u32>(tape)?) == Tag(*b"ttcf") {
-            raise!("TrueType collections are not supported yet");
-        }
+        File::read_with(tape, Default::default())
+    }
+
+    /// Read a file given options for reading the tables of its fonts.
+    pub fn read_with<T: Read + Seek>(tape: &mut T, options: Options) -> Result<File> {
+        let data = match &*Tag::from(Tape::peek::<u32>(tape)?) {
+            b"wOFF" => Tape::take::<woff::Directory>(tape)?.decompress(tape)?,
+            b"wOF2" => Tape::take::<woff2::Directory>(tape)?.decompress(tape)?,
+            b"ttcf" => {
+                let collection = Tape::take::<Header>(tape)?;
+                let mut fonts = Vec::with_capacity(collection.offsets().len());
+                for &offset in collection.offsets() {
+                    Tape::jump(tape, offset as u64)?;
+                    fonts.push(Font::read_with(tape, options)?);
+                }
+                return Ok(File {
+                    collection: Some(collection),
+                    fonts: fonts,
+                });
+            }
+            _ => {
+                return Ok(File {
+                    collection: None,
+                    fonts: vec![Font::read_with(tape, options)?],
+                })
+            }
+        };
         Ok(File {
-            fonts: vec![Font::read(tape)?],
+            collection: None,
+            fonts: vec![Font::read_data(data, options)?],
         })
     }
 }
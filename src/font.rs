@@ -1,24 +1,65 @@
-use std::io::{Read, Seek};
+use std::io::{Cursor, Read, Seek};
 use truetype::offset_table::OffsetTable;
 use truetype::{Result, Tag, Tape};
 
+use crate::writer::{self, Write};
 use crate::Table;
 
 /// A font.
 pub struct Font {
     /// The offset table.
     pub offset_table: OffsetTable,
+    /// The data of the font if it had to be decompressed.
+    pub data: Option<Vec<u8>>,
+    /// The options for reading tables.
+    pub options: Options,
+}
+
+/// Options for reading tables.
+#[derive(Clone, Copy, Debug)]
+pub struct Options {
+    /// Whether to verify the checksums of tables, which is the default.
+    pub verify_checksums: bool,
+    /// Whether to replace lookup tables that cannot be read with placeholders
+    /// instead of failing, which is not the default.
+    pub lenient: bool,
+}
+
+/// A font builder.
+#[derive(Clone, Debug)]
+pub struct Builder {
+    version: u32,
+    tables: Vec<(Tag, Vec<u8>)>,
 }
 
 impl Font {
     /// Read a font.
     #[inline]
     pub fn read<T>(tape: &mut T) -> Result<Font>
+    where
+        T: Read + Seek,
+    {
+        Font::read_with(tape, Default::default())
+    }
+
+    /// Read a font given options for reading tables.
+    pub fn read_with<T>(tape: &mut T, options: Options) -> Result<Font>
     where
         T: Read + Seek,
     {
         Ok(Font {
             offset_table: Tape::take(tape)?,
+            data: None,
+            options: options,
+        })
+    }
+
+    /// Read a font from decompressed data.
+    pub(crate) fn read_data(data: Vec<u8>, options: Options) -> Result<Font> {
+        Ok(Font {
+            offset_table: Tape::take(&mut Cursor::new(&data))?,
+            data: Some(data),
+            options: options,
         })
     }
 
@@ -38,21 +79,195 @@ impl Font {
         T: Read + Seek,
         U: Table<'l>,
     {
-        let tag = U::tag();
+        match self.data {
+            Some(ref data) => self.find(&mut Cursor::new(data), parameter),
+            _ => self.find(tape, parameter),
+        }
+    }
+
+    /// Find, verify, and read the data of a table given its tag.
+    pub fn take_bytes<T>(&self, tape: &mut T, tag: Tag) -> Result<Option<Vec<u8>>>
+    where
+        T: Read + Seek,
+    {
+        match self.data {
+            Some(ref data) => self.find_bytes(&mut Cursor::new(data), tag),
+            _ => self.find_bytes(tape, tag),
+        }
+    }
+
+    fn find<'l, T, U>(&self, tape: &mut T, parameter: U::Parameter) -> Result<Option<U>>
+    where
+        T: Read + Seek,
+        U: Table<'l>,
+    {
+        match self.locate(tape, U::tag())? {
+            Some(_) if self.options.lenient => Ok(Some(Table::take_lenient(tape, parameter)?)),
+            Some(_) => Ok(Some(Table::take(tape, parameter)?)),
+            _ => Ok(None),
+        }
+    }
+
+    fn find_bytes<T>(&self, tape: &mut T, tag: Tag) -> Result<Option<Vec<u8>>>
+    where
+        T: Read + Seek,
+    {
+        match self.locate(tape, tag)? {
+            Some(length) => Ok(Some(Tape::take_bytes(tape, length)?)),
+            _ => Ok(None),
+        }
+    }
+
+    fn locate<T>(&self, tape: &mut T, tag: Tag) -> Result<Option<usize>>
+    where
+        T: Read + Seek,
+    {
         for record in &self.offset_table.records {
             if record.tag == tag {
-                let check = if tag == Tag(*b"head") {
-                    record.checksum(tape, |i, word| if i == 2 { 0 } else { word })?
-                } else {
-                    record.checksum(tape, |_, word| word)?
-                };
-                if !check {
-                    raise!("found a malformed font table");
+                if self.options.verify_checksums {
+                    let check = if tag == Tag(*b"head") {
+                        record.checksum(tape, |i, word| if i == 2 { 0 } else { word })?
+                    } else {
+                        record.checksum(tape, |_, word| word)?
+                    };
+                    if !check {
+                        raise!("found a malformed font table");
+                    }
                 }
                 Tape::jump(tape, record.offset as u64)?;
-                return Ok(Some(Table::take(tape, parameter)?));
+                return Ok(Some(record.length as usize));
             }
         }
         Ok(None)
     }
 }
+
+impl Default for Options {
+    #[inline]
+    fn default() -> Self {
+        Options {
+            verify_checksums: true,
+            lenient: false,
+        }
+    }
+}
+
+impl Builder {
+    /// Create a builder given the version of the font, which is 0x00010000
+    /// for TrueType outlines and `OTTO` for CFF outlines.
+    #[inline]
+    pub fn new(version: u32) -> Self {
+        Builder {
+            version: version,
+            tables: vec![],
+        }
+    }
+
+    /// Add a table given its tag and data.
+    ///
+    /// A table with the same tag added earlier is replaced.
+    pub fn add(&mut self, tag: Tag, data: Vec<u8>) -> &mut Self {
+        match self.tables.iter().position(|&(other, _)| other == tag) {
+            Some(i) => self.tables[i].1 = data,
+            _ => self.tables.push((tag, data)),
+        }
+        self
+    }
+
+    /// Write and add a table.
+    pub fn add_table<'l, T>(&mut self, table: &T) -> Result<&mut Self>
+    where
+        T: Table<'l> + Write,
+    {
+        Ok(self.add(T::tag(), writer::write(table)?))
+    }
+
+    /// Assemble the font.
+    ///
+    /// The tables are sorted by their tags and aligned to four bytes, and the
+    /// checksums, including the checksum adjustment of the font header, are
+    /// computed.
+    pub fn build(&self) -> Result<Vec<u8>> {
+        let mut tables = self.tables.clone();
+        tables.sort_by_key(|&(tag, _)| tag.0);
+        let head = tables.iter().position(|&(tag, _)| tag == Tag(*b"head"));
+        if let Some(i) = head {
+            if tables[i].1.len() < 12 {
+                raise!("found a malformed font header");
+            }
+            tables[i].1[8..12].copy_from_slice(&[0; 4]);
+        }
+        let tables = tables
+            .into_iter()
+            .map(|(tag, table)| (tag, checksum(tag, &table), table))
+            .collect::<Vec<_>>();
+        let mut data = assemble(self.version, &tables)?;
+        if let Some(i) = head {
+            let position = 12 + 16 * i + 8;
+            let mut offset = [0; 4];
+            offset.copy_from_slice(&data[position..(position + 4)]);
+            let offset = u32::from_be_bytes(offset) as usize + 8;
+            let adjustment = 0xB1B0AFBAu32.wrapping_sub(sum(&data));
+            data[offset..(offset + 4)].copy_from_slice(&adjustment.to_be_bytes());
+        }
+        Ok(data)
+    }
+}
+
+pub(crate) fn assemble(version: u32, tables: &[(Tag, u32, Vec<u8>)]) -> Result<Vec<u8>> {
+    macro_rules! push(
+        ($data:ident, $value:expr) => ($data.extend_from_slice(&$value.to_be_bytes()));
+    );
+    let count = tables.len();
+    let mut selector = 0;
+    while 1 << (selector + 1) <= count {
+        selector += 1;
+    }
+    let range = if count > 0 { 16 << selector } else { 0 };
+    let shift = 16 * count - range;
+    if range > u16::MAX as usize || shift > u16::MAX as usize {
+        raise!("found too many font tables");
+    }
+    let mut data = vec![];
+    push!(data, version);
+    push!(data, count as u16);
+    push!(data, range as u16);
+    push!(data, selector as u16);
+    push!(data, shift as u16);
+    let mut offset = 12 + 16 * count;
+    for &(tag, checksum, ref table) in tables {
+        if offset > u32::MAX as usize || table.len() > u32::MAX as usize {
+            raise!("found a font table overflow");
+        }
+        data.extend_from_slice(&*tag);
+        push!(data, checksum);
+        push!(data, offset as u32);
+        push!(data, table.len() as u32);
+        offset += (table.len() + 3) & !3;
+    }
+    for (_, _, table) in tables {
+        data.extend_from_slice(table);
+        data.resize((data.len() + 3) & !3, 0);
+    }
+    Ok(data)
+}
+
+pub(crate) fn checksum(tag: Tag, data: &[u8]) -> u32 {
+    let value = sum(data);
+    if &*tag == b"head" && data.len() >= 12 {
+        let mut word = [0; 4];
+        word.copy_from_slice(&data[8..12]);
+        return value.wrapping_sub(u32::from_be_bytes(word));
+    }
+    value
+}
+
+fn sum(data: &[u8]) -> u32 {
+    let mut value = 0u32;
+    for chunk in data.chunks(4) {
+        let mut word = [0; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        value = value.wrapping_add(u32::from_be_bytes(word));
+    }
+    value
+}
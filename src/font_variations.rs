@@ -0,0 +1,162 @@
+//! The [font-variations table][1].
+//!
+//! [1]: https://www.microsoft.com/typography/otspec/fvar.htm
+
+use truetype::{q32, Result, Tag, Tape, Walue};
+
+use crate::AxisVariations;
+
+table! {
+    @position
+    #[doc = "A font-variations table."]
+    pub FontVariations {
+        major_version  (u16) = { 1 }, // majorVersion
+        minor_version  (u16) = { 0 }, // minorVersion
+        axes_offset    (u16), // axesArrayOffset
+        reserved       (u16), // reserved
+        axis_count     (u16), // axisCount
+        axis_size      (u16), // axisSize
+        instance_count (u16), // instanceCount
+        instance_size  (u16), // instanceSize
+
+        axes (Vec<Axis>) |this, tape, position| { // axes
+            if this.axis_size < 20 {
+                raise!("found a malformed font-variations table");
+            }
+            let mut values = Vec::with_capacity(this.axis_count as usize);
+            for i in 0..(this.axis_count as usize) {
+                let offset = this.axes_offset as usize + i * this.axis_size as usize;
+                values.push(jump_take!(@unwrap tape, position, offset));
+            }
+            Ok(values)
+        },
+
+        instances (Vec<Instance>) |this, tape, position| { // instances
+            let axis_count = this.axis_count as usize;
+            let has_name = match this.instance_size as usize {
+                size if size == 4 + 4 * axis_count => false,
+                size if size >= 6 + 4 * axis_count => true,
+                _ => raise!("found a malformed font-variations table"),
+            };
+            let start = this.axes_offset as usize + axis_count * this.axis_size as usize;
+            let parameter = (axis_count, has_name);
+            let mut values = Vec::with_capacity(this.instance_count as usize);
+            for i in 0..(this.instance_count as usize) {
+                let offset = start + i * this.instance_size as usize;
+                values.push(jump_take_given!(@unwrap tape, position, offset, parameter));
+            }
+            Ok(values)
+        },
+    }
+}
+
+table! {
+    #[doc = "A variation axis."]
+    #[derive(Copy)]
+    pub Axis { // VariationAxisRecord
+        tag           (Tag      ), // axisTag
+        min_value     (q32      ), // minValue
+        default_value (q32      ), // defaultValue
+        max_value     (q32      ), // maxValue
+        flags         (AxisFlags), // flags
+        name_id       (u16      ), // axisNameID
+    }
+}
+
+table! {
+    @define
+    #[doc = "A named instance."]
+    pub Instance { // InstanceRecord
+        subfamily_name_id  (u16        ), // subfamilyNameID
+        flags              (u16        ), // flags
+        coordinates        (Vec<q32>   ), // coordinates
+        postscript_name_id (Option<u16>), // postScriptNameID
+    }
+}
+
+flags! {
+    #[doc = "Axis flags."]
+    pub AxisFlags(u16) {
+        0b0000_0000_0000_0001 => is_hidden,
+        0b1111_1111_1111_1110 => is_invalid,
+    }
+}
+
+impl FontVariations {
+    /// Normalize user coordinates.
+    ///
+    /// The coordinates are given in the order of the axes, and missing ones are
+    /// considered to be at the default location. The result is in the range
+    /// from −1 to 1 and has one coordinate per axis.
+    pub fn normalize(&self, coordinates: &[f32]) -> Vec<f32> {
+        self.axes
+            .iter()
+            .enumerate()
+            .map(|(i, axis)| match coordinates.get(i) {
+                Some(&value) => axis.normalize(value),
+                _ => 0.0,
+            })
+            .collect()
+    }
+
+    /// Normalize user coordinates and convert them to format F2DOT14 taking
+    /// into account an axis-variations table if present.
+    ///
+    /// See `normalize` and `AxisVariations::map` for the details.
+    pub fn normalize_given(
+        &self,
+        coordinates: &[f32],
+        axis_variations: Option<&AxisVariations>,
+    ) -> Vec<i16> {
+        let coordinates = self
+            .normalize(coordinates)
+            .into_iter()
+            .map(|value| (value * 16384.0).round() as i16)
+            .collect::<Vec<_>>();
+        match axis_variations {
+            Some(axis_variations) => axis_variations.map(&coordinates),
+            _ => coordinates,
+        }
+    }
+}
+
+impl Axis {
+    /// Normalize a user coordinate.
+    ///
+    /// The coordinate is clamped to the range of the axis and mapped to the
+    /// range from −1 to 1 with the default value mapped to 0.
+    pub fn normalize(&self, value: f32) -> f32 {
+        let (min, default, max) = (
+            fixed(self.min_value),
+            fixed(self.default_value),
+            fixed(self.max_value),
+        );
+        let value = value.max(min).min(max);
+        if value < default {
+            -(default - value) / (default - min)
+        } else if value > default {
+            (value - default) / (max - default)
+        } else {
+            0.0
+        }
+    }
+}
+
+impl Walue<'static> for Instance {
+    type Parameter = (usize, bool);
+
+    fn read<T: Tape>(tape: &mut T, (axis_count, has_name): (usize, bool)) -> Result<Self> {
+        Ok(Instance {
+            subfamily_name_id: tape.take()?,
+            flags: tape.take()?,
+            coordinates: tape.take_given(axis_count)?,
+            postscript_name_id: if has_name { Some(tape.take()?) } else { None },
+        })
+    }
+}
+
+/// Convert a number in format Q16.16 taking into account its sign.
+#[inline]
+pub(crate) fn fixed(value: q32) -> f32 {
+    value.0 as i32 as f32 / 65536.0
+}
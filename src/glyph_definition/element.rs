@@ -1,6 +1,7 @@
 use truetype::{Result, Tape, Value};
 
 use crate::layout::{Correction, Coverage};
+use crate::writer::{Write, Writer};
 
 table! {
     #[doc = "A glyph attachment."]
@@ -133,7 +134,7 @@ table! {
 
 impl Value for Caret {
     fn read<T: Tape>(tape: &mut T) -> Result<Self> {
-        Ok(match tape.peek::<u32>()? {
+        Ok(match tape.peek::<u16>()? {
             1 => Caret::Format1(tape.take()?),
             2 => Caret::Format2(tape.take()?),
             3 => Caret::Format3(tape.take()?),
@@ -141,3 +142,73 @@ impl Value for Caret {
         })
     }
 }
+
+impl Write for Attachment {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give_count(self.indices.len())?;
+        writer.give(&self.indices)
+    }
+}
+
+impl Write for Attachments {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.link(&self.coverage)?;
+        writer.give_count(self.records.len())?;
+        for record in &self.records {
+            writer.link(record)?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for Caret {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        match *self {
+            Caret::Format1(ref table) => {
+                writer.give(&1u16)?;
+                writer.give(&table.coordinate)
+            }
+            Caret::Format2(ref table) => {
+                writer.give(&2u16)?;
+                writer.give(&table.index)
+            }
+            Caret::Format3(ref table) => {
+                writer.give(&3u16)?;
+                writer.give(&table.coordinate)?;
+                writer.link(&table.correction)
+            }
+        }
+    }
+}
+
+impl Write for Ligature {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give_count(self.carets.len())?;
+        for record in &self.carets {
+            writer.link(record)?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for Ligatures {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.link(&self.coverage)?;
+        writer.give_count(self.records.len())?;
+        for record in &self.records {
+            writer.link(record)?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for Marks {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give(&1u16)?;
+        writer.give_count(self.coverages.len())?;
+        for record in &self.coverages {
+            writer.link_long(record)?;
+        }
+        Ok(())
+    }
+}
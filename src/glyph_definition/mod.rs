@@ -6,6 +6,7 @@ use truetype::{Result, Tape, Value};
 
 use crate::layout::Class;
 use crate::variation::item::Variations;
+use crate::writer::{Write, Writer};
 
 mod element;
 
@@ -57,7 +58,7 @@ table! {
         },
 
         variations (Option<Variations>) |this, tape, position| {
-            jump_take_maybe!(tape, position, field!(this.header => marks_offset(0),
+            jump_take_maybe!(tape, position, field!(this.header => variations_offset(0),
                                                     Header::{Version13}))
         },
     }
@@ -133,3 +134,32 @@ impl Value for Header {
         })
     }
 }
+
+impl Write for GlyphDefinition {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        let minor_version = if self.variations.is_some() {
+            3
+        } else if self.marks.is_some() {
+            2
+        } else {
+            match self.header {
+                Header::Version1(_) => 0,
+                Header::Version12(_) => 2,
+                Header::Version13(_) => 3,
+            }
+        };
+        writer.give(&1u16)?;
+        writer.give(&(minor_version as u16))?;
+        writer.link_maybe(self.glyph_class.as_ref())?;
+        writer.link_maybe(self.attachments.as_ref())?;
+        writer.link_maybe(self.ligatures.as_ref())?;
+        writer.link_maybe(self.mark_class.as_ref())?;
+        if minor_version >= 2 {
+            writer.link_maybe(self.marks.as_ref())?;
+        }
+        if minor_version >= 3 {
+            writer.link_long_maybe(self.variations.as_ref())?;
+        }
+        Ok(())
+    }
+}
@@ -1,6 +1,21 @@
 use truetype::{GlyphID, Result, Tape, Value, Walue};
 
 use crate::layout::Correction;
+use crate::variation::item::Variations;
+use crate::writer::{Write, WriteGiven, Writer};
+
+/// An adjustment of a glyph.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Adjustment {
+    /// The horizontal adjustment of the placement.
+    pub x_placement: f32,
+    /// The vertical adjustment of the placement.
+    pub y_placement: f32,
+    /// The horizontal adjustment of the advance.
+    pub x_advance: f32,
+    /// The vertical adjustment of the advance.
+    pub y_advance: f32,
+}
 
 /// An anchor.
 #[derive(Clone, Debug)]
@@ -58,8 +73,8 @@ table! {
     @define
     #[doc = "A base attachment."]
     pub Base { // BaseRecord
-        anchor_offsets (Vec<u16>   ), // BaseAnchor
-        anchors        (Vec<Anchor>),
+        anchor_offsets (Vec<u16>           ), // BaseAnchor
+        anchors        (Vec<Option<Anchor>>),
     }
 }
 
@@ -207,8 +222,8 @@ table! {
     @define
     #[doc = "A component attachment."]
     pub Component { // ComponentRecord
-        anchor_offsets (Vec<u16>   ),
-        anchors        (Vec<Anchor>),
+        anchor_offsets (Vec<u16>           ), // LigatureAnchor
+        anchors        (Vec<Option<Anchor>>),
     }
 }
 
@@ -261,8 +276,8 @@ table! {
     @define
     #[doc = "A mark attachment in format 2."]
     pub Mark2 { // Mark2Record
-        anchor_offsets (Vec<u16>   ), // Mark2Anchor
-        anchors        (Vec<Anchor>),
+        anchor_offsets (Vec<u16>           ), // Mark2Anchor
+        anchors        (Vec<Option<Anchor>>),
     }
 }
 
@@ -315,10 +330,10 @@ table! {
     @define
     #[doc = "An entry-exit record."]
     pub Passage { // EntryExitRecord
-        entry_offset (u16   ), // EntryAnchor
-        exit_offset  (u16   ), // ExitAnchor
-        entry        (Anchor),
-        exit         (Anchor),
+        entry_offset (u16           ), // EntryAnchor
+        exit_offset  (u16           ), // ExitAnchor
+        entry        (Option<Anchor>),
+        exit         (Option<Anchor>),
     }
 }
 
@@ -401,6 +416,47 @@ flags! {
     }
 }
 
+impl Anchor {
+    /// Compute the coordinates at normalized coordinates of a variable font
+    /// given in format F2DOT14.
+    pub fn vary(&self, variations: &Variations, coordinates: &[i16]) -> (f32, f32) {
+        match *self {
+            Anchor::Format1(ref anchor) => (anchor.x as f32, anchor.y as f32),
+            Anchor::Format2(ref anchor) => (anchor.x as f32, anchor.y as f32),
+            Anchor::Format3(ref anchor) => anchor.vary(variations, coordinates),
+        }
+    }
+
+    /// Compute the coordinates in pixels at a size given in pixels per em.
+    pub fn hint(&self, size: u16, units_per_em: u16) -> (f32, f32) {
+        let scale = size as f32 / units_per_em as f32;
+        match *self {
+            Anchor::Format1(ref anchor) => (anchor.x as f32 * scale, anchor.y as f32 * scale),
+            Anchor::Format2(ref anchor) => (anchor.x as f32 * scale, anchor.y as f32 * scale),
+            Anchor::Format3(ref anchor) => anchor.hint(size, units_per_em),
+        }
+    }
+}
+
+impl Anchor3 {
+    /// Compute the coordinates at normalized coordinates of a variable font
+    /// given in format F2DOT14.
+    pub fn vary(&self, variations: &Variations, coordinates: &[i16]) -> (f32, f32) {
+        (
+            vary(self.x, &self.x_correction, variations, coordinates),
+            vary(self.y, &self.y_correction, variations, coordinates),
+        )
+    }
+
+    /// Compute the coordinates in pixels at a size given in pixels per em.
+    pub fn hint(&self, size: u16, units_per_em: u16) -> (f32, f32) {
+        (
+            hint(self.x, &self.x_correction, size, units_per_em),
+            hint(self.y, &self.y_correction, size, units_per_em),
+        )
+    }
+}
+
 impl Default for Anchor {
     #[inline]
     fn default() -> Self {
@@ -424,7 +480,8 @@ impl Walue<'static> for Base {
 
     fn read<T: Tape>(tape: &mut T, (position, class_count): Self::Parameter) -> Result<Self> {
         let anchor_offsets: Vec<u16> = tape.take_given(class_count as usize)?;
-        let anchors = jump_take!(@unwrap tape, position, class_count, anchor_offsets);
+        let anchors =
+            tape.stay(|tape| jump_take_maybe!(tape, position, class_count, anchor_offsets))?;
         Ok(Base {
             anchor_offsets: anchor_offsets,
             anchors: anchors,
@@ -454,7 +511,8 @@ impl Walue<'static> for Component {
 
     fn read<T: Tape>(tape: &mut T, (position, class_count): Self::Parameter) -> Result<Self> {
         let anchor_offsets: Vec<u16> = tape.take_given(class_count as usize)?;
-        let anchors = jump_take!(@unwrap tape, position, class_count, anchor_offsets);
+        let anchors =
+            tape.stay(|tape| jump_take_maybe!(tape, position, class_count, anchor_offsets))?;
         Ok(Component {
             anchor_offsets: anchor_offsets,
             anchors: anchors,
@@ -501,7 +559,7 @@ impl Walue<'static> for Mark1 {
     fn read<T: Tape>(tape: &mut T, position: u64) -> Result<Self> {
         let class_id = tape.take()?;
         let anchor_offset = tape.take()?;
-        let anchor = jump_take!(@unwrap tape, position, anchor_offset);
+        let anchor = tape.stay(|tape| jump_take!(tape, position, anchor_offset))?;
         Ok(Mark1 {
             class_id: class_id,
             anchor_offset: anchor_offset,
@@ -515,7 +573,8 @@ impl Walue<'static> for Mark2 {
 
     fn read<T: Tape>(tape: &mut T, (position, class_count): Self::Parameter) -> Result<Self> {
         let anchor_offsets: Vec<u16> = tape.take_given(class_count as usize)?;
-        let anchors = jump_take!(@unwrap tape, position, class_count, anchor_offsets);
+        let anchors =
+            tape.stay(|tape| jump_take_maybe!(tape, position, class_count, anchor_offsets))?;
         Ok(Mark2 {
             anchor_offsets: anchor_offsets,
             anchors: anchors,
@@ -556,13 +615,14 @@ impl Walue<'static> for Pair1 {
 }
 
 impl Walue<'static> for Pair1s {
-    type Parameter = (u64, SingleFlags, SingleFlags);
+    type Parameter = (SingleFlags, SingleFlags);
 
-    fn read<T: Tape>(tape: &mut T, parameter: Self::Parameter) -> Result<Self> {
+    fn read<T: Tape>(tape: &mut T, (value1_flags, value2_flags): Self::Parameter) -> Result<Self> {
+        let position = tape.position()?;
         let count = tape.take()?;
         let mut records = Vec::with_capacity(count as usize);
         for _ in 0..(count as usize) {
-            records.push(tape.take_given(parameter)?);
+            records.push(tape.take_given((position, value1_flags, value2_flags))?);
         }
         Ok(Pair1s {
             count: count,
@@ -606,8 +666,8 @@ impl Walue<'static> for Passage {
     fn read<T: Tape>(tape: &mut T, position: u64) -> Result<Self> {
         let entry_offset = tape.take()?;
         let exit_offset = tape.take()?;
-        let entry = jump_take!(@unwrap tape, position, entry_offset);
-        let exit = jump_take!(@unwrap tape, position, exit_offset);
+        let entry = tape.stay(|tape| jump_take_maybe!(tape, position, entry_offset))?;
+        let exit = tape.stay(|tape| jump_take_maybe!(tape, position, exit_offset))?;
         Ok(Passage {
             entry_offset: entry_offset,
             exit_offset: exit_offset,
@@ -617,6 +677,62 @@ impl Walue<'static> for Passage {
     }
 }
 
+impl Single {
+    /// Compute the adjustment at normalized coordinates of a variable font
+    /// given in format F2DOT14.
+    pub fn vary(&self, variations: &Variations, coordinates: &[i16]) -> Adjustment {
+        macro_rules! vary(
+            ($value:ident, $correction:ident) => (
+                vary(self.$value.unwrap_or(0), &self.$correction, variations, coordinates)
+            );
+        );
+        Adjustment {
+            x_placement: vary!(x_placement, x_placement_correction),
+            y_placement: vary!(y_placement, y_placement_correction),
+            x_advance: vary!(x_advance, x_advance_correction),
+            y_advance: vary!(y_advance, y_advance_correction),
+        }
+    }
+
+    /// Compute the adjustment in pixels at a size given in pixels per em.
+    pub fn hint(&self, size: u16, units_per_em: u16) -> Adjustment {
+        macro_rules! hint(
+            ($value:ident, $correction:ident) => (
+                hint(self.$value.unwrap_or(0), &self.$correction, size, units_per_em)
+            );
+        );
+        Adjustment {
+            x_placement: hint!(x_placement, x_placement_correction),
+            y_placement: hint!(y_placement, y_placement_correction),
+            x_advance: hint!(x_advance, x_advance_correction),
+            y_advance: hint!(y_advance, y_advance_correction),
+        }
+    }
+}
+
+impl Single {
+    /// Return the flags of the fields that are present.
+    pub fn flags(&self) -> SingleFlags {
+        let presence = [
+            self.x_placement.is_some(),
+            self.y_placement.is_some(),
+            self.x_advance.is_some(),
+            self.y_advance.is_some(),
+            self.x_placement_correction_offset.is_some() || self.x_placement_correction.is_some(),
+            self.y_placement_correction_offset.is_some() || self.y_placement_correction.is_some(),
+            self.x_advance_correction_offset.is_some() || self.x_advance_correction.is_some(),
+            self.y_advance_correction_offset.is_some() || self.y_advance_correction.is_some(),
+        ];
+        let mut flags = 0;
+        for (i, &present) in presence.iter().enumerate() {
+            if present {
+                flags |= 1 << i;
+            }
+        }
+        SingleFlags(flags)
+    }
+}
+
 impl Walue<'static> for Single {
     type Parameter = (u64, SingleFlags);
 
@@ -634,7 +750,9 @@ impl Walue<'static> for Single {
         let y_advance_correction_offset = take!(has_y_advance_correction);
         macro_rules! take(
             ($offset:ident) => (match $offset {
-                Some(offset) => Some(jump_take!(@unwrap tape, position, offset)),
+                Some(offset) if offset > 0 => Some(tape.stay(|tape| {
+                    jump_take!(tape, position, offset)
+                })?),
                 _ => None,
             });
         );
@@ -658,3 +776,310 @@ impl Walue<'static> for Single {
         })
     }
 }
+
+impl Write for Anchor {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        match *self {
+            Anchor::Format1(ref table) => writer.give(table),
+            Anchor::Format2(ref table) => writer.give(table),
+            Anchor::Format3(ref table) => writer.give(table),
+        }
+    }
+}
+
+impl Write for Anchor1 {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give(&1u16)?;
+        writer.give(&self.x)?;
+        writer.give(&self.y)
+    }
+}
+
+impl Write for Anchor2 {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give(&2u16)?;
+        writer.give(&self.x)?;
+        writer.give(&self.y)?;
+        writer.give(&self.index)
+    }
+}
+
+impl Write for Anchor3 {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give(&3u16)?;
+        writer.give(&self.x)?;
+        writer.give(&self.y)?;
+        writer.link_maybe(self.x_correction.as_ref())?;
+        writer.link_maybe(self.y_correction.as_ref())
+    }
+}
+
+impl Write for Base {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        for anchor in &self.anchors {
+            writer.link_maybe(anchor.as_ref())?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for Bases {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give_count(self.records.len())?;
+        writer.give(&self.records)
+    }
+}
+
+impl Write for ChainClassRule {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give_count(self.backward_class_ids.len())?;
+        writer.give(&self.backward_class_ids)?;
+        writer.give_count(self.input_class_ids.len() + 1)?;
+        writer.give(&self.input_class_ids)?;
+        writer.give_count(self.forward_class_ids.len())?;
+        writer.give(&self.forward_class_ids)?;
+        writer.give_count(self.operations.len())?;
+        writer.give(&self.operations)
+    }
+}
+
+impl Write for ChainClassRules {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give_count(self.records.len())?;
+        for record in &self.records {
+            writer.link(record)?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for ChainRule {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give_count(self.backward_glyph_ids.len())?;
+        writer.give(&self.backward_glyph_ids)?;
+        writer.give_count(self.input_glyph_ids.len() + 1)?;
+        writer.give(&self.input_glyph_ids)?;
+        writer.give_count(self.forward_glyph_ids.len())?;
+        writer.give(&self.forward_glyph_ids)?;
+        writer.give_count(self.operations.len())?;
+        writer.give(&self.operations)
+    }
+}
+
+impl Write for ChainRules {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give_count(self.records.len())?;
+        for record in &self.records {
+            writer.link(record)?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for ClassRule {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give_count(self.input_class_ids.len() + 1)?;
+        writer.give_count(self.operations.len())?;
+        writer.give(&self.input_class_ids)?;
+        writer.give(&self.operations)
+    }
+}
+
+impl Write for ClassRules {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give_count(self.records.len())?;
+        for record in &self.records {
+            writer.link(record)?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for Component {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        for anchor in &self.anchors {
+            writer.link_maybe(anchor.as_ref())?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for Ligature {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give_count(self.components.len())?;
+        writer.give(&self.components)
+    }
+}
+
+impl Write for Ligatures {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give_count(self.records.len())?;
+        for record in &self.records {
+            writer.link(record)?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for Mark1 {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give(&self.class_id)?;
+        writer.link(&self.anchor)
+    }
+}
+
+impl Write for Mark1s {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give_count(self.records.len())?;
+        writer.give(&self.records)
+    }
+}
+
+impl Write for Mark2 {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        for anchor in &self.anchors {
+            writer.link_maybe(anchor.as_ref())?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for Mark2s {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give_count(self.records.len())?;
+        writer.give(&self.records)
+    }
+}
+
+impl WriteGiven for Pair1 {
+    type Parameter = (SingleFlags, SingleFlags);
+
+    fn write(
+        &self,
+        writer: &mut Writer,
+        (value1_flags, value2_flags): Self::Parameter,
+    ) -> Result<()> {
+        writer.give(&self.glyph2_id)?;
+        writer.give_given(&self.value1, value1_flags)?;
+        writer.give_given(&self.value2, value2_flags)
+    }
+}
+
+impl WriteGiven for Pair1s {
+    type Parameter = (SingleFlags, SingleFlags);
+
+    fn write(&self, writer: &mut Writer, flags: Self::Parameter) -> Result<()> {
+        writer.give_count(self.records.len())?;
+        for record in &self.records {
+            writer.give_given(record, flags)?;
+        }
+        Ok(())
+    }
+}
+
+impl WriteGiven for Pair2 {
+    type Parameter = (SingleFlags, SingleFlags);
+
+    fn write(
+        &self,
+        writer: &mut Writer,
+        (value1_flags, value2_flags): Self::Parameter,
+    ) -> Result<()> {
+        writer.give_given(&self.value1, value1_flags)?;
+        writer.give_given(&self.value2, value2_flags)
+    }
+}
+
+impl WriteGiven for Pair2s {
+    type Parameter = (SingleFlags, SingleFlags);
+
+    fn write(&self, writer: &mut Writer, flags: Self::Parameter) -> Result<()> {
+        for record in &self.records {
+            writer.give_given(record, flags)?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for Passage {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.link_maybe(self.entry.as_ref())?;
+        writer.link_maybe(self.exit.as_ref())
+    }
+}
+
+impl Write for Positioning {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give(&self.sequence_index)?;
+        writer.give(&self.lookup_index)
+    }
+}
+
+impl Write for Rule {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give_count(self.input_glyph_ids.len() + 1)?;
+        writer.give_count(self.operations.len())?;
+        writer.give(&self.input_glyph_ids)?;
+        writer.give(&self.operations)
+    }
+}
+
+impl Write for Rules {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give_count(self.records.len())?;
+        for record in &self.records {
+            writer.link(record)?;
+        }
+        Ok(())
+    }
+}
+
+impl WriteGiven for Single {
+    type Parameter = SingleFlags;
+
+    fn write(&self, writer: &mut Writer, flags: SingleFlags) -> Result<()> {
+        macro_rules! give(
+            ($flag:ident, $value:ident) => (
+                if flags.$flag() {
+                    writer.give(&self.$value.unwrap_or(0))?;
+                }
+            );
+        );
+        give!(has_x_placement, x_placement);
+        give!(has_y_placement, y_placement);
+        give!(has_x_advance, x_advance);
+        give!(has_y_advance, y_advance);
+        macro_rules! give(
+            ($flag:ident, $correction:ident) => (
+                if flags.$flag() {
+                    writer.link_maybe(self.$correction.as_ref())?;
+                }
+            );
+        );
+        give!(has_x_placement_correction, x_placement_correction);
+        give!(has_y_placement_correction, y_placement_correction);
+        give!(has_x_advance_correction, x_advance_correction);
+        give!(has_y_advance_correction, y_advance_correction);
+        Ok(())
+    }
+}
+
+fn hint(value: i16, correction: &Option<Correction>, size: u16, units_per_em: u16) -> f32 {
+    let value = value as f32 * size as f32 / units_per_em as f32;
+    match *correction {
+        Some(ref correction) => value + correction.hint(size) as f32,
+        _ => value,
+    }
+}
+
+fn vary(
+    value: i16,
+    correction: &Option<Correction>,
+    variations: &Variations,
+    coordinates: &[i16],
+) -> f32 {
+    match *correction {
+        Some(ref correction) => value as f32 + correction.vary(variations, coordinates),
+        _ => value as f32,
+    }
+}
@@ -0,0 +1,491 @@
+use crate::glyph_definition::GlyphDefinition;
+use crate::glyph_positioning::*;
+use crate::layout::lookup::Flags;
+use crate::layout::{Glyph, Matcher};
+use crate::variation::item::Variations;
+
+const MAXIMUM_DEPTH: usize = 64;
+
+/// A position of a glyph.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Position {
+    /// The horizontal advance.
+    pub x_advance: f32,
+    /// The vertical advance.
+    pub y_advance: f32,
+    /// The horizontal offset.
+    pub x_offset: f32,
+    /// The vertical offset.
+    pub y_offset: f32,
+}
+
+#[derive(Clone, Copy)]
+enum Attachment {
+    Cursive(usize),
+    Mark(usize),
+}
+
+impl GlyphPositioning {
+    /// Apply lookups to a glyph buffer.
+    ///
+    /// The positions are expected to be as many as the glyphs and to be
+    /// initialized with the advances of the glyphs, and the glyphs are expected
+    /// to be in the left-to-right order.
+    /// The lookups are given by their indices and applied in the given order;
+    /// see `Directory::lookup_indices`. The glyph-definition table is used to
+    /// skip glyphs according to the flags of the lookups and to vary values
+    /// at normalized coordinates given in format F2DOT14. The offsets of
+    /// attached glyphs are resolved relative to the glyphs they are attached
+    /// to, so that each glyph is to be drawn at its pen position shifted by
+    /// its offset.
+    pub fn apply(
+        &self,
+        glyphs: &[Glyph],
+        positions: &mut [Position],
+        lookup_indices: &[u16],
+        definition: Option<&GlyphDefinition>,
+        coordinates: &[i16],
+    ) -> Result<()> {
+        if glyphs.len() != positions.len() {
+            raise!("found a mismatch between the glyphs and the positions");
+        }
+        let default = Variations::default();
+        let variations = definition
+            .and_then(|definition| definition.variations.as_ref())
+            .unwrap_or(&default);
+        let mut engine = Engine {
+            table: self,
+            definition: definition,
+            variations: variations,
+            coordinates: coordinates,
+            glyphs: glyphs,
+            positions: positions,
+            attachments: vec![None; glyphs.len()],
+        };
+        for &lookup_index in lookup_indices {
+            let record = match self.lookups.records.get(lookup_index as usize) {
+                Some(record) => record,
+                _ => continue,
+            };
+            let matcher = Matcher::new(record, definition);
+            let mut index = 0;
+            while index < glyphs.len() {
+                if !matcher.ignores(glyphs[index].glyph_id) {
+                    if let Some(next) = engine.apply(lookup_index, index, 0) {
+                        index = next.max(index + 1);
+                        continue;
+                    }
+                }
+                index += 1;
+            }
+        }
+        engine.resolve();
+        Ok(())
+    }
+}
+
+struct Engine<'l, 'm> {
+    table: &'l GlyphPositioning,
+    definition: Option<&'l GlyphDefinition>,
+    variations: &'l Variations,
+    coordinates: &'l [i16],
+    glyphs: &'l [Glyph],
+    positions: &'m mut [Position],
+    attachments: Vec<Option<Attachment>>,
+}
+
+impl<'l, 'm> Engine<'l, 'm> {
+    fn apply(&mut self, lookup_index: u16, index: usize, depth: usize) -> Option<usize> {
+        let table = self.table;
+        let record = table.lookups.records.get(lookup_index as usize)?;
+        let matcher = Matcher::new(record, self.definition);
+        for table in record.resolved_tables() {
+            if let Some(next) = self.apply_table(table, record.flags, &matcher, index, depth) {
+                return Some(next);
+            }
+        }
+        None
+    }
+
+    fn apply_table(
+        &mut self,
+        table: &Table,
+        flags: Flags,
+        matcher: &Matcher,
+        index: usize,
+        depth: usize,
+    ) -> Option<usize> {
+        let glyphs = self.glyphs;
+        let glyph_id = glyphs.get(index)?.glyph_id;
+        match *table {
+            Table::SingleAdjustment(SingleAdjustment::Format1(ref table)) => {
+                table.coverage.index(glyph_id)?;
+                self.adjust(index, &table.value);
+                Some(index + 1)
+            }
+            Table::SingleAdjustment(SingleAdjustment::Format2(ref table)) => {
+                let coverage_index = table.coverage.index(glyph_id)?;
+                self.adjust(index, table.values.get(coverage_index as usize)?);
+                Some(index + 1)
+            }
+            Table::PairAdjustment(PairAdjustment::Format1(ref table)) => {
+                let coverage_index = table.coverage.index(glyph_id)?;
+                let next = matcher.next(glyphs, index)?;
+                let set = table.sets.get(coverage_index as usize)?;
+                let pair = set
+                    .records
+                    .iter()
+                    .find(|pair| pair.glyph2_id == glyphs[next].glyph_id)?;
+                self.adjust(index, &pair.value1);
+                self.adjust(next, &pair.value2);
+                Some(if table.value2_flags.0 == 0 {
+                    next
+                } else {
+                    next + 1
+                })
+            }
+            Table::PairAdjustment(PairAdjustment::Format2(ref table)) => {
+                table.coverage.index(glyph_id)?;
+                let next = matcher.next(glyphs, index)?;
+                let class1 = table.class1.get(glyph_id);
+                let class2 = table.class2.get(glyphs[next].glyph_id);
+                let pair = table
+                    .sets
+                    .get(class1 as usize)?
+                    .records
+                    .get(class2 as usize)?;
+                self.adjust(index, &pair.value1);
+                self.adjust(next, &pair.value2);
+                Some(if table.value2_flags.0 == 0 {
+                    next
+                } else {
+                    next + 1
+                })
+            }
+            Table::CursiveAttachment(ref table) => {
+                let coverage_index = table.coverage.index(glyph_id)?;
+                let exit = table.passages.get(coverage_index as usize)?.exit.as_ref()?;
+                let next = matcher.next(glyphs, index)?;
+                let coverage_index = table.coverage.index(glyphs[next].glyph_id)?;
+                let entry = table
+                    .passages
+                    .get(coverage_index as usize)?
+                    .entry
+                    .as_ref()?;
+                let (exit_x, exit_y) = exit.vary(self.variations, self.coordinates);
+                let (entry_x, entry_y) = entry.vary(self.variations, self.coordinates);
+                let positions = &mut *self.positions;
+                positions[index].x_advance = exit_x + positions[index].x_offset;
+                let delta = entry_x + positions[next].x_offset;
+                positions[next].x_advance -= delta;
+                positions[next].x_offset -= delta;
+                let (child, parent, y) = if flags.is_right_to_left() {
+                    (index, next, entry_y - exit_y)
+                } else {
+                    (next, index, exit_y - entry_y)
+                };
+                if let Some(Attachment::Cursive(other)) = self.attachments[parent] {
+                    if other == child {
+                        self.attachments[parent] = None;
+                    }
+                }
+                positions[child].y_offset = y;
+                self.attachments[child] = Some(Attachment::Cursive(parent));
+                Some(next)
+            }
+            Table::MarkToBaseAttachment(ref table) => {
+                let mark_index = table.mark_coverage.index(glyph_id)?;
+                let base = self.previous_base(matcher, index)?;
+                let base_index = table.base_coverage.index(glyphs[base].glyph_id)?;
+                let mark = table.marks.records.get(mark_index as usize)?;
+                let anchor = table
+                    .bases
+                    .records
+                    .get(base_index as usize)?
+                    .anchors
+                    .get(mark.class_id as usize)?
+                    .as_ref()?;
+                self.attach(index, &mark.anchor, base, anchor);
+                Some(index + 1)
+            }
+            Table::MarkToLigatureAttachment(ref table) => {
+                let mark_index = table.mark_coverage.index(glyph_id)?;
+                let base = self.previous_base(matcher, index)?;
+                let ligature_index = table.ligature_coverage.index(glyphs[base].glyph_id)?;
+                let mark = table.marks.records.get(mark_index as usize)?;
+                let ligature = table.ligatures.records.get(ligature_index as usize)?;
+                let count = ligature.components.len();
+                if count == 0 {
+                    return None;
+                }
+                let (glyph, other) = (&glyphs[index], &glyphs[base]);
+                let component = if glyph.ligature_id != 0
+                    && glyph.ligature_id == other.ligature_id
+                    && glyph.component > 0
+                {
+                    (glyph.component as usize).min(count) - 1
+                } else {
+                    count - 1
+                };
+                let anchor = ligature.components[component]
+                    .anchors
+                    .get(mark.class_id as usize)?
+                    .as_ref()?;
+                self.attach(index, &mark.anchor, base, anchor);
+                Some(index + 1)
+            }
+            Table::MarkToMarkAttachment(ref table) => {
+                let mark1_index = table.mark1_coverage.index(glyph_id)?;
+                let base = matcher.previous(glyphs, index)?;
+                let (glyph, other) = (&glyphs[index], &glyphs[base]);
+                let attachable = if glyph.ligature_id == other.ligature_id {
+                    glyph.ligature_id == 0 || glyph.component == other.component
+                } else {
+                    (glyph.ligature_id != 0 && glyph.component == 0)
+                        || (other.ligature_id != 0 && other.component == 0)
+                };
+                if !attachable {
+                    return None;
+                }
+                let mark2_index = table.mark2_coverage.index(other.glyph_id)?;
+                let mark = table.mark1s.records.get(mark1_index as usize)?;
+                let anchor = table
+                    .mark2s
+                    .records
+                    .get(mark2_index as usize)?
+                    .anchors
+                    .get(mark.class_id as usize)?
+                    .as_ref()?;
+                self.attach(index, &mark.anchor, base, anchor);
+                Some(index + 1)
+            }
+            Table::ContextPositioning(ContextPositioning::Format1(ref table)) => {
+                let coverage_index = table.coverage.index(glyph_id)?;
+                for rule in &table.sets.get(coverage_index as usize)?.records {
+                    let glyph_ids = &rule.input_glyph_ids;
+                    let indices = matcher.matches(
+                        glyphs,
+                        index,
+                        (0, |_, _| true),
+                        (glyph_ids.len(), |i, glyph_id| glyph_ids[i] == glyph_id),
+                        (0, |_, _| true),
+                    );
+                    if let Some(indices) = indices {
+                        return Some(self.apply_operations(&indices, &rule.operations, depth));
+                    }
+                }
+                None
+            }
+            Table::ContextPositioning(ContextPositioning::Format2(ref table)) => {
+                table.coverage.index(glyph_id)?;
+                let class = &table.class;
+                let set = table.sets.get(class.get(glyph_id) as usize)?.as_ref()?;
+                for rule in &set.records {
+                    let class_ids = &rule.input_class_ids;
+                    let indices = matcher.matches(
+                        glyphs,
+                        index,
+                        (0, |_, _| true),
+                        (class_ids.len(), |i, glyph_id| {
+                            class_ids[i] == class.get(glyph_id)
+                        }),
+                        (0, |_, _| true),
+                    );
+                    if let Some(indices) = indices {
+                        return Some(self.apply_operations(&indices, &rule.operations, depth));
+                    }
+                }
+                None
+            }
+            Table::ContextPositioning(ContextPositioning::Format3(ref table)) => {
+                let coverages = &table.coverages;
+                if !coverages.first()?.contains(glyph_id) {
+                    return None;
+                }
+                let indices = matcher.matches(
+                    glyphs,
+                    index,
+                    (0, |_, _| true),
+                    (coverages.len() - 1, |i, glyph_id| {
+                        coverages[i + 1].contains(glyph_id)
+                    }),
+                    (0, |_, _| true),
+                )?;
+                Some(self.apply_operations(&indices, &table.operations, depth))
+            }
+            Table::ChainContextPositioning(ChainContextPositioning::Format1(ref table)) => {
+                let coverage_index = table.coverage.index(glyph_id)?;
+                for rule in &table.sets.get(coverage_index as usize)?.records {
+                    let (backward, input, forward) = (
+                        &rule.backward_glyph_ids,
+                        &rule.input_glyph_ids,
+                        &rule.forward_glyph_ids,
+                    );
+                    let indices = matcher.matches(
+                        glyphs,
+                        index,
+                        (backward.len(), |i, glyph_id| backward[i] == glyph_id),
+                        (input.len(), |i, glyph_id| input[i] == glyph_id),
+                        (forward.len(), |i, glyph_id| forward[i] == glyph_id),
+                    );
+                    if let Some(indices) = indices {
+                        return Some(self.apply_operations(&indices, &rule.operations, depth));
+                    }
+                }
+                None
+            }
+            Table::ChainContextPositioning(ChainContextPositioning::Format2(ref table)) => {
+                table.coverage.index(glyph_id)?;
+                let (backward_class, input_class, forward_class) = (
+                    &table.backward_class,
+                    &table.input_class,
+                    &table.forward_class,
+                );
+                let set = table
+                    .sets
+                    .get(input_class.get(glyph_id) as usize)?
+                    .as_ref()?;
+                for rule in &set.records {
+                    let (backward, input, forward) = (
+                        &rule.backward_class_ids,
+                        &rule.input_class_ids,
+                        &rule.forward_class_ids,
+                    );
+                    let indices = matcher.matches(
+                        glyphs,
+                        index,
+                        (backward.len(), |i, glyph_id| {
+                            backward[i] == backward_class.get(glyph_id)
+                        }),
+                        (input.len(), |i, glyph_id| {
+                            input[i] == input_class.get(glyph_id)
+                        }),
+                        (forward.len(), |i, glyph_id| {
+                            forward[i] == forward_class.get(glyph_id)
+                        }),
+                    );
+                    if let Some(indices) = indices {
+                        return Some(self.apply_operations(&indices, &rule.operations, depth));
+                    }
+                }
+                None
+            }
+            Table::ChainContextPositioning(ChainContextPositioning::Format3(ref table)) => {
+                let (backward, input, forward) = (
+                    &table.backward_coverages,
+                    &table.input_coverages,
+                    &table.forward_coverages,
+                );
+                if !input.first()?.contains(glyph_id) {
+                    return None;
+                }
+                let indices = matcher.matches(
+                    glyphs,
+                    index,
+                    (backward.len(), |i, glyph_id| backward[i].contains(glyph_id)),
+                    (input.len() - 1, |i, glyph_id| {
+                        input[i + 1].contains(glyph_id)
+                    }),
+                    (forward.len(), |i, glyph_id| forward[i].contains(glyph_id)),
+                )?;
+                Some(self.apply_operations(&indices, &table.operations, depth))
+            }
+            Table::ExtensionPositioning(ref table) => {
+                self.apply_table(&table.table, flags, matcher, index, depth)
+            }
+            Table::Unknown(_) => None,
+        }
+    }
+
+    fn apply_operations(
+        &mut self,
+        indices: &[usize],
+        operations: &[Positioning],
+        depth: usize,
+    ) -> usize {
+        if depth < MAXIMUM_DEPTH {
+            for operation in operations {
+                if let Some(&index) = indices.get(operation.sequence_index as usize) {
+                    self.apply(operation.lookup_index, index, depth + 1);
+                }
+            }
+        }
+        indices[indices.len() - 1] + 1
+    }
+
+    fn adjust(&mut self, index: usize, value: &Single) {
+        let adjustment = value.vary(self.variations, self.coordinates);
+        let position = &mut self.positions[index];
+        position.x_offset += adjustment.x_placement;
+        position.y_offset += adjustment.y_placement;
+        position.x_advance += adjustment.x_advance;
+        position.y_advance += adjustment.y_advance;
+    }
+
+    fn attach(&mut self, index: usize, anchor: &Anchor, base: usize, base_anchor: &Anchor) {
+        let (x, y) = anchor.vary(self.variations, self.coordinates);
+        let (base_x, base_y) = base_anchor.vary(self.variations, self.coordinates);
+        let position = &mut self.positions[index];
+        position.x_offset = base_x - x;
+        position.y_offset = base_y - y;
+        self.attachments[index] = Some(Attachment::Mark(base));
+    }
+
+    fn previous_base(&self, matcher: &Matcher, index: usize) -> Option<usize> {
+        let mut index = index;
+        loop {
+            index = matcher.previous(self.glyphs, index)?;
+            if matcher.class(self.glyphs[index].glyph_id) != 3 {
+                return Some(index);
+            }
+        }
+    }
+
+    fn resolve(&mut self) {
+        let mut resolved = vec![false; self.positions.len()];
+        let mut chain = vec![];
+        for start in 0..self.positions.len() {
+            let mut index = start;
+            while !resolved[index] {
+                resolved[index] = true;
+                match self.attachments[index] {
+                    Some(Attachment::Cursive(parent)) | Some(Attachment::Mark(parent)) => {
+                        chain.push(index);
+                        index = parent;
+                    }
+                    _ => break,
+                }
+            }
+            while let Some(index) = chain.pop() {
+                self.resolve_one(index);
+            }
+        }
+    }
+
+    fn resolve_one(&mut self, index: usize) {
+        let (parent, mark) = match self.attachments[index] {
+            Some(Attachment::Cursive(parent)) => (parent, false),
+            Some(Attachment::Mark(parent)) => (parent, true),
+            _ => return,
+        };
+        let other = self.positions[parent];
+        let position = &mut self.positions[index];
+        position.y_offset += other.y_offset;
+        if mark {
+            position.x_offset += other.x_offset;
+            if parent < index {
+                let advance: f32 = self.positions[parent..index]
+                    .iter()
+                    .map(|position| position.x_advance)
+                    .sum();
+                self.positions[index].x_offset -= advance;
+            } else {
+                let advance: f32 = self.positions[index..parent]
+                    .iter()
+                    .map(|position| position.x_advance)
+                    .sum();
+                self.positions[index].x_offset += advance;
+            }
+        }
+    }
+}
@@ -2,13 +2,19 @@
 //!
 //! [1]: https://www.microsoft.com/typography/otspec/gpos.htm
 
-use truetype::{Result, Tape, Value, Walue};
+#![allow(unused_parens)]
 
+use truetype::{Error, Result, Tape, Value, Walue};
+
+use crate::layout::lookup::{Extension, Placeholder};
 use crate::layout::{Class, Coverage, Directory};
+use crate::writer::{Write, Writer};
 
 mod element;
+mod engine;
 
 pub use element::*;
+pub use engine::Position;
 
 /// A glyph-positioning table.
 pub type GlyphPositioning = Directory<Table>;
@@ -25,6 +31,8 @@ pub enum Table {
     ContextPositioning(ContextPositioning),
     ChainContextPositioning(ChainContextPositioning),
     ExtensionPositioning(ExtensionPositioning),
+    /// A table that could not be read along with the reason.
+    Unknown(String),
 }
 
 /// A table for adjusting single glyphs.
@@ -106,7 +114,7 @@ table! {
 
         sets (Vec<Pair1s>) |this, tape, position| {
             jump_take_given!(tape, position, this.set_count, this.set_offsets,
-                             (position, this.value1_flags, this.value2_flags))
+                             (this.value1_flags, this.value2_flags))
         },
     }
 }
@@ -306,6 +314,10 @@ table! {
             jump_take!(tape, position, this.coverage_offset)
         },
 
+        class (Class) |this, tape, position| {
+            jump_take!(tape, position, this.class_offset)
+        },
+
         sets (Vec<Option<ClassRules>>) |this, tape, position| {
             jump_take_maybe!(tape, position, this.set_count, this.set_offsets)
         },
@@ -447,12 +459,31 @@ table! {
     }
 }
 
-table! {
-    #[doc = "A table for other types of positioning."]
-    pub ExtensionPositioning { // ExtensionPosFormat1
-        format (u16) = { 1 }, // PosFormat
-        kind   (u16), // ExtensionLookupType
-        offset (u32), // ExtensionOffset
+/// A table for other types of positioning.
+#[derive(Clone, Debug)]
+#[rustfmt::skip]
+pub struct ExtensionPositioning { // ExtensionPosFormat1
+    pub format: (u16       ), // PosFormat
+    pub kind:   (u16       ), // ExtensionLookupType
+    pub offset: (u32       ), // ExtensionOffset
+    pub table:  (Box<Table>),
+}
+
+impl Extension for Table {
+    const KIND: u16 = 9;
+
+    fn extension(&self) -> Option<(u16, &Self)> {
+        match *self {
+            Table::ExtensionPositioning(ref table) => Some((table.kind, &table.table)),
+            _ => None,
+        }
+    }
+}
+
+impl Placeholder for Table {
+    #[inline]
+    fn placeholder(error: Error) -> Self {
+        Table::Unknown(error.to_string())
     }
 }
 
@@ -516,3 +547,315 @@ impl Value for ChainContextPositioning {
         })
     }
 }
+
+impl Value for ExtensionPositioning {
+    fn read<T: Tape>(tape: &mut T) -> Result<Self> {
+        let position = tape.position()?;
+        if tape.take::<u16>()? != 1 {
+            raise!("found an unknown format of the extension-positioning table");
+        }
+        let kind = tape.take()?;
+        if kind == 9 {
+            raise!("found a malformed extension-positioning table");
+        }
+        let offset = tape.take()?;
+        let table = jump_take_given!(@unwrap tape, position, offset, kind);
+        Ok(ExtensionPositioning {
+            format: 1,
+            kind: kind,
+            offset: offset,
+            table: Box::new(table),
+        })
+    }
+}
+
+impl Write for Table {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        match *self {
+            Table::SingleAdjustment(ref table) => writer.give(table),
+            Table::PairAdjustment(ref table) => writer.give(table),
+            Table::CursiveAttachment(ref table) => writer.give(table),
+            Table::MarkToBaseAttachment(ref table) => writer.give(table),
+            Table::MarkToLigatureAttachment(ref table) => writer.give(table),
+            Table::MarkToMarkAttachment(ref table) => writer.give(table),
+            Table::ContextPositioning(ref table) => writer.give(table),
+            Table::ChainContextPositioning(ref table) => writer.give(table),
+            Table::ExtensionPositioning(ref table) => writer.give(table),
+            Table::Unknown(_) => raise!("found an unknown glyph-positioning table"),
+        }
+    }
+}
+
+impl Write for SingleAdjustment {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        match *self {
+            SingleAdjustment::Format1(ref table) => writer.give(table),
+            SingleAdjustment::Format2(ref table) => writer.give(table),
+        }
+    }
+}
+
+impl Write for SingleAdjustment1 {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        let flags = self.value.flags();
+        writer.give(&1u16)?;
+        writer.link(&self.coverage)?;
+        writer.give(&flags.0)?;
+        writer.give_given(&self.value, flags)
+    }
+}
+
+impl Write for SingleAdjustment2 {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        let flags = unite(self.values.iter());
+        writer.give(&2u16)?;
+        writer.link(&self.coverage)?;
+        writer.give(&flags.0)?;
+        writer.give_count(self.values.len())?;
+        for value in &self.values {
+            writer.give_given(value, flags)?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for PairAdjustment {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        match *self {
+            PairAdjustment::Format1(ref table) => writer.give(table),
+            PairAdjustment::Format2(ref table) => writer.give(table),
+        }
+    }
+}
+
+impl Write for PairAdjustment1 {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        let records = || self.sets.iter().flat_map(|set| &set.records);
+        let value1_flags = unite(records().map(|record| &record.value1));
+        let value2_flags = unite(records().map(|record| &record.value2));
+        writer.give(&1u16)?;
+        writer.link(&self.coverage)?;
+        writer.give(&value1_flags.0)?;
+        writer.give(&value2_flags.0)?;
+        writer.give_count(self.sets.len())?;
+        for set in &self.sets {
+            writer.link_given(set, (value1_flags, value2_flags))?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for PairAdjustment2 {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        let class2_count = self.sets.first().map(|set| set.records.len()).unwrap_or(0);
+        if self
+            .sets
+            .iter()
+            .any(|set| set.records.len() != class2_count)
+        {
+            raise!("found a malformed pair-adjustment table");
+        }
+        let records = || self.sets.iter().flat_map(|set| &set.records);
+        let value1_flags = unite(records().map(|record| &record.value1));
+        let value2_flags = unite(records().map(|record| &record.value2));
+        writer.give(&2u16)?;
+        writer.link(&self.coverage)?;
+        writer.give(&value1_flags.0)?;
+        writer.give(&value2_flags.0)?;
+        writer.link(&self.class1)?;
+        writer.link(&self.class2)?;
+        writer.give_count(self.sets.len())?;
+        writer.give_count(class2_count)?;
+        for set in &self.sets {
+            writer.give_given(set, (value1_flags, value2_flags))?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for CursiveAttachment {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give(&1u16)?;
+        writer.link(&self.coverage)?;
+        writer.give_count(self.passages.len())?;
+        writer.give(&self.passages)
+    }
+}
+
+impl Write for MarkToBaseAttachment {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        let class_count = self.class_count as usize;
+        if self
+            .bases
+            .records
+            .iter()
+            .any(|record| record.anchors.len() != class_count)
+        {
+            raise!("found a malformed mark-to-base attachment table");
+        }
+        writer.give(&1u16)?;
+        writer.link(&self.mark_coverage)?;
+        writer.link(&self.base_coverage)?;
+        writer.give(&self.class_count)?;
+        writer.link(&self.marks)?;
+        writer.link(&self.bases)
+    }
+}
+
+impl Write for MarkToLigatureAttachment {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        let class_count = self.class_count as usize;
+        if self
+            .ligatures
+            .records
+            .iter()
+            .flat_map(|record| &record.components)
+            .any(|record| record.anchors.len() != class_count)
+        {
+            raise!("found a malformed mark-to-ligature attachment table");
+        }
+        writer.give(&1u16)?;
+        writer.link(&self.mark_coverage)?;
+        writer.link(&self.ligature_coverage)?;
+        writer.give(&self.class_count)?;
+        writer.link(&self.marks)?;
+        writer.link(&self.ligatures)
+    }
+}
+
+impl Write for MarkToMarkAttachment {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        let class_count = self.class_count as usize;
+        if self
+            .mark2s
+            .records
+            .iter()
+            .any(|record| record.anchors.len() != class_count)
+        {
+            raise!("found a malformed mark-to-mark attachment table");
+        }
+        writer.give(&1u16)?;
+        writer.link(&self.mark1_coverage)?;
+        writer.link(&self.mark2_coverage)?;
+        writer.give(&self.class_count)?;
+        writer.link(&self.mark1s)?;
+        writer.link(&self.mark2s)
+    }
+}
+
+impl Write for ContextPositioning {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        match *self {
+            ContextPositioning::Format1(ref table) => writer.give(table),
+            ContextPositioning::Format2(ref table) => writer.give(table),
+            ContextPositioning::Format3(ref table) => writer.give(table),
+        }
+    }
+}
+
+impl Write for ContextPositioning1 {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give(&1u16)?;
+        writer.link(&self.coverage)?;
+        writer.give_count(self.sets.len())?;
+        for record in &self.sets {
+            writer.link(record)?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for ContextPositioning2 {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give(&2u16)?;
+        writer.link(&self.coverage)?;
+        writer.link(&self.class)?;
+        writer.give_count(self.sets.len())?;
+        for record in &self.sets {
+            writer.link_maybe(record.as_ref())?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for ContextPositioning3 {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give(&3u16)?;
+        writer.give_count(self.coverages.len())?;
+        writer.give_count(self.operations.len())?;
+        for record in &self.coverages {
+            writer.link(record)?;
+        }
+        writer.give(&self.operations)
+    }
+}
+
+impl Write for ChainContextPositioning {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        match *self {
+            ChainContextPositioning::Format1(ref table) => writer.give(table),
+            ChainContextPositioning::Format2(ref table) => writer.give(table),
+            ChainContextPositioning::Format3(ref table) => writer.give(table),
+        }
+    }
+}
+
+impl Write for ChainContextPositioning1 {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give(&1u16)?;
+        writer.link(&self.coverage)?;
+        writer.give_count(self.sets.len())?;
+        for record in &self.sets {
+            writer.link(record)?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for ChainContextPositioning2 {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give(&2u16)?;
+        writer.link(&self.coverage)?;
+        writer.link(&self.backward_class)?;
+        writer.link(&self.input_class)?;
+        writer.link(&self.forward_class)?;
+        writer.give_count(self.sets.len())?;
+        for record in &self.sets {
+            writer.link_maybe(record.as_ref())?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for ChainContextPositioning3 {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give(&3u16)?;
+        for records in &[
+            &self.backward_coverages,
+            &self.input_coverages,
+            &self.forward_coverages,
+        ] {
+            writer.give_count(records.len())?;
+            for record in records.iter() {
+                writer.link(record)?;
+            }
+        }
+        writer.give_count(self.operations.len())?;
+        writer.give(&self.operations)
+    }
+}
+
+impl Write for ExtensionPositioning {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give(&1u16)?;
+        writer.give(&self.kind)?;
+        writer.link_detached(&*self.table)
+    }
+}
+
+fn unite<'l, T>(values: T) -> SingleFlags
+where
+    T: Iterator<Item = &'l Single>,
+{
+    SingleFlags(values.fold(0, |flags, value| flags | value.flags().0))
+}
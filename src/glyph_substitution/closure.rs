@@ -0,0 +1,234 @@
+use std::collections::BTreeSet;
+use truetype::{GlyphID, Tag};
+
+use crate::glyph_substitution::*;
+
+impl GlyphSubstitution {
+    /// Return the glyphs reachable from a set of glyphs via the lookups of a
+    /// set of features given by their tags.
+    ///
+    /// The features are taken from all scripts and languages and from all
+    /// feature variations, and the required features are always included. The
+    /// result includes the given glyphs. See `close` for the details.
+    pub fn closure(&self, glyph_ids: &[GlyphID], features: &[Tag]) -> BTreeSet<GlyphID> {
+        let mut lookup_indices = vec![];
+        let languages = self.scripts.records.iter().flat_map(|record| {
+            record
+                .default_language
+                .iter()
+                .chain(&record.language_records)
+        });
+        for language in languages {
+            if let Some(record) = self
+                .features
+                .records
+                .get(language.required_feature_index as usize)
+            {
+                lookup_indices.extend_from_slice(&record.lookup_indices);
+            }
+        }
+        for (header, record) in self.features.headers.iter().zip(&self.features.records) {
+            if features.contains(&header.tag) {
+                lookup_indices.extend_from_slice(&record.lookup_indices);
+            }
+        }
+        let substitutions = self
+            .variations
+            .iter()
+            .flat_map(|variations| &variations.substitutions)
+            .flatten();
+        for substitutions in substitutions {
+            for (header, record) in substitutions.headers.iter().zip(&substitutions.records) {
+                match self.features.headers.get(header.index as usize) {
+                    Some(other) if features.contains(&other.tag) => {
+                        lookup_indices.extend_from_slice(&record.lookup_indices);
+                    }
+                    _ => {}
+                }
+            }
+        }
+        let mut glyph_ids = glyph_ids.iter().cloned().collect();
+        self.close(&mut glyph_ids, &lookup_indices);
+        glyph_ids
+    }
+
+    /// Extend a set of glyphs with the glyphs reachable via lookups given by
+    /// their indices.
+    ///
+    /// Single, multiple, alternate, ligature, and reverse chaining
+    /// substitutions contribute their output glyphs for the reachable input
+    /// glyphs; a ligature requires all its components to be reachable.
+    /// Context and chaining context substitutions whose rules can match
+    /// reachable glyphs bring in the lookups that the rules refer to, which
+    /// are then applied to all reachable glyphs. The result is therefore a
+    /// superset of what shaping can produce.
+    pub fn close(&self, glyph_ids: &mut BTreeSet<GlyphID>, lookup_indices: &[u16]) {
+        let mut lookup_indices = lookup_indices.iter().cloned().collect::<BTreeSet<_>>();
+        loop {
+            let (glyph_count, lookup_count) = (glyph_ids.len(), lookup_indices.len());
+            let mut found = vec![];
+            let mut nested = vec![];
+            for &lookup_index in &lookup_indices {
+                let record = match self.lookups.records.get(lookup_index as usize) {
+                    Some(record) => record,
+                    _ => continue,
+                };
+                for table in record.resolved_tables() {
+                    close(table, glyph_ids, &mut found, &mut nested);
+                }
+            }
+            glyph_ids.extend(found);
+            lookup_indices.extend(nested);
+            if glyph_ids.len() == glyph_count && lookup_indices.len() == lookup_count {
+                break;
+            }
+        }
+    }
+}
+
+fn close(
+    table: &Table,
+    glyph_ids: &BTreeSet<GlyphID>,
+    found: &mut Vec<GlyphID>,
+    nested: &mut Vec<u16>,
+) {
+    let covered = |coverage: &Coverage| {
+        coverage
+            .iter()
+            .filter(|(glyph_id, _)| glyph_ids.contains(glyph_id))
+            .map(|(glyph_id, index)| (glyph_id, index as usize))
+            .collect::<Vec<_>>()
+    };
+    let contains = |values: &[GlyphID]| values.iter().all(|value| glyph_ids.contains(value));
+    let intersects =
+        |coverage: &Coverage| coverage.iter().any(|(value, _)| glyph_ids.contains(&value));
+    let reaches = |class: &Class, values: &[u16]| {
+        values.iter().all(|&value| {
+            value == 0
+                || glyph_ids
+                    .iter()
+                    .any(|&glyph_id| class.get(glyph_id) == value)
+        })
+    };
+    let lookups = |operations: &[Substitution]| {
+        operations
+            .iter()
+            .map(|operation| operation.lookup_index)
+            .collect::<Vec<_>>()
+    };
+    match *table {
+        Table::SingleSubstitution(SingleSubstitution::Format1(ref table)) => {
+            for (glyph_id, _) in covered(&table.coverage) {
+                found.push((glyph_id as i32 + table.delta_glyph_id as i32) as GlyphID);
+            }
+        }
+        Table::SingleSubstitution(SingleSubstitution::Format2(ref table)) => {
+            for (_, index) in covered(&table.coverage) {
+                found.extend(table.glyph_ids.get(index));
+            }
+        }
+        Table::MultipleSubstitution(ref table) => {
+            for (_, index) in covered(&table.coverage) {
+                if let Some(sequence) = table.sequences.get(index) {
+                    found.extend(&sequence.glyph_ids);
+                }
+            }
+        }
+        Table::AlternateSubstitution(ref table) => {
+            for (_, index) in covered(&table.coverage) {
+                if let Some(set) = table.sets.get(index) {
+                    found.extend(&set.glyph_ids);
+                }
+            }
+        }
+        Table::LigatureSubstitution(ref table) => {
+            for (_, index) in covered(&table.coverage) {
+                for ligature in table.sets.get(index).iter().flat_map(|set| &set.records) {
+                    if contains(&ligature.component_ids) {
+                        found.push(ligature.glyph_id);
+                    }
+                }
+            }
+        }
+        Table::ContextSubstitution(ContextSubstitution::Format1(ref table)) => {
+            for (_, index) in covered(&table.coverage) {
+                for rule in table.sets.get(index).iter().flat_map(|set| &set.records) {
+                    if contains(&rule.input_glyph_ids) {
+                        nested.extend(lookups(&rule.operations));
+                    }
+                }
+            }
+        }
+        Table::ContextSubstitution(ContextSubstitution::Format2(ref table)) => {
+            let mut classes = covered(&table.coverage)
+                .into_iter()
+                .map(|(glyph_id, _)| table.class.get(glyph_id))
+                .collect::<Vec<_>>();
+            classes.sort_unstable();
+            classes.dedup();
+            for class in classes {
+                let set = table.sets.get(class as usize).and_then(Option::as_ref);
+                for rule in set.iter().flat_map(|set| &set.records) {
+                    if reaches(&table.class, &rule.input_class_ids) {
+                        nested.extend(lookups(&rule.operations));
+                    }
+                }
+            }
+        }
+        Table::ContextSubstitution(ContextSubstitution::Format3(ref table)) => {
+            if table.coverages.iter().all(intersects) {
+                nested.extend(lookups(&table.operations));
+            }
+        }
+        Table::ChainContextSubstitution(ChainContextSubstitution::Format1(ref table)) => {
+            for (_, index) in covered(&table.coverage) {
+                for rule in table.sets.get(index).iter().flat_map(|set| &set.records) {
+                    if contains(&rule.backward_glyph_ids)
+                        && contains(&rule.input_glyph_ids)
+                        && contains(&rule.forward_glyph_ids)
+                    {
+                        nested.extend(lookups(&rule.operations));
+                    }
+                }
+            }
+        }
+        Table::ChainContextSubstitution(ChainContextSubstitution::Format2(ref table)) => {
+            let mut classes = covered(&table.coverage)
+                .into_iter()
+                .map(|(glyph_id, _)| table.input_class.get(glyph_id))
+                .collect::<Vec<_>>();
+            classes.sort_unstable();
+            classes.dedup();
+            for class in classes {
+                let set = table.sets.get(class as usize).and_then(Option::as_ref);
+                for rule in set.iter().flat_map(|set| &set.records) {
+                    if reaches(&table.backward_class, &rule.backward_class_ids)
+                        && reaches(&table.input_class, &rule.input_class_ids)
+                        && reaches(&table.forward_class, &rule.forward_class_ids)
+                    {
+                        nested.extend(lookups(&rule.operations));
+                    }
+                }
+            }
+        }
+        Table::ChainContextSubstitution(ChainContextSubstitution::Format3(ref table)) => {
+            if table.backward_coverages.iter().all(intersects)
+                && table.input_coverages.iter().all(intersects)
+                && table.forward_coverages.iter().all(intersects)
+            {
+                nested.extend(lookups(&table.operations));
+            }
+        }
+        Table::ExtensionSubstitution(ref table) => close(&table.table, glyph_ids, found, nested),
+        Table::ReverseChainContextSubstitution(ref table) => {
+            if table.backward_coverages.iter().all(intersects)
+                && table.forward_coverages.iter().all(intersects)
+            {
+                for (_, index) in covered(&table.coverage) {
+                    found.extend(table.glyph_ids.get(index));
+                }
+            }
+        }
+        Table::Unknown(_) => {}
+    }
+}
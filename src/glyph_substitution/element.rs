@@ -1,4 +1,6 @@
-use truetype::GlyphID;
+use truetype::{GlyphID, Result};
+
+use crate::writer::{Write, Writer};
 
 table! {
     #[doc = "A set of alternate substitutions."]
@@ -227,3 +229,126 @@ table! {
         lookup_index   (u16), // LookupListIndex
     }
 }
+
+impl Write for Alternates {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give_count(self.glyph_ids.len())?;
+        writer.give(&self.glyph_ids)
+    }
+}
+
+impl Write for ChainClassRule {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give_count(self.backward_class_ids.len())?;
+        writer.give(&self.backward_class_ids)?;
+        writer.give_count(self.input_class_ids.len() + 1)?;
+        writer.give(&self.input_class_ids)?;
+        writer.give_count(self.forward_class_ids.len())?;
+        writer.give(&self.forward_class_ids)?;
+        writer.give_count(self.operations.len())?;
+        writer.give(&self.operations)
+    }
+}
+
+impl Write for ChainClassRules {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give_count(self.records.len())?;
+        for record in &self.records {
+            writer.link(record)?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for ChainRule {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give_count(self.backward_glyph_ids.len())?;
+        writer.give(&self.backward_glyph_ids)?;
+        writer.give_count(self.input_glyph_ids.len() + 1)?;
+        writer.give(&self.input_glyph_ids)?;
+        writer.give_count(self.forward_glyph_ids.len())?;
+        writer.give(&self.forward_glyph_ids)?;
+        writer.give_count(self.operations.len())?;
+        writer.give(&self.operations)
+    }
+}
+
+impl Write for ChainRules {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give_count(self.records.len())?;
+        for record in &self.records {
+            writer.link(record)?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for ClassRule {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give_count(self.input_class_ids.len() + 1)?;
+        writer.give_count(self.operations.len())?;
+        writer.give(&self.input_class_ids)?;
+        writer.give(&self.operations)
+    }
+}
+
+impl Write for ClassRules {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give_count(self.records.len())?;
+        for record in &self.records {
+            writer.link(record)?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for Ligature {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give(&self.glyph_id)?;
+        writer.give_count(self.component_ids.len() + 1)?;
+        writer.give(&self.component_ids)
+    }
+}
+
+impl Write for Ligatures {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give_count(self.records.len())?;
+        for record in &self.records {
+            writer.link(record)?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for Rule {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give_count(self.input_glyph_ids.len() + 1)?;
+        writer.give_count(self.operations.len())?;
+        writer.give(&self.input_glyph_ids)?;
+        writer.give(&self.operations)
+    }
+}
+
+impl Write for Rules {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give_count(self.records.len())?;
+        for record in &self.records {
+            writer.link(record)?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for Sequence {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give_count(self.glyph_ids.len())?;
+        writer.give(&self.glyph_ids)
+    }
+}
+
+impl Write for Substitution {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give(&self.sequence_index)?;
+        writer.give(&self.lookup_index)
+    }
+}
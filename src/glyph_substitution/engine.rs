@@ -0,0 +1,373 @@
+use truetype::GlyphID;
+
+use crate::glyph_definition::GlyphDefinition;
+use crate::glyph_substitution::*;
+use crate::layout::{Buffer, Glyph, Matcher};
+
+const MAXIMUM_DEPTH: usize = 64;
+
+impl GlyphSubstitution {
+    /// Apply lookups to a glyph buffer.
+    ///
+    /// The lookups are given by their indices and applied in the given order;
+    /// see `Directory::lookup_indices`. The glyph-definition table is used to
+    /// skip glyphs according to the flags of the lookups. Alternate
+    /// substitutions choose the alternate given by the `alternate` field of
+    /// the glyph, which is the first alternate by default, and leave the glyph
+    /// intact if there is no such alternate.
+    pub fn apply(
+        &self,
+        buffer: &mut Buffer,
+        lookup_indices: &[u16],
+        definition: Option<&GlyphDefinition>,
+    ) {
+        let engine = Engine {
+            table: self,
+            definition: definition,
+        };
+        for &lookup_index in lookup_indices {
+            let record = match self.lookups.records.get(lookup_index as usize) {
+                Some(record) => record,
+                _ => continue,
+            };
+            let matcher = Matcher::new(record, definition);
+            if record.resolved_kind() == 8 {
+                for index in (0..buffer.len()).rev() {
+                    if !matcher.ignores(buffer[index].glyph_id) {
+                        engine.apply(lookup_index, buffer, index, 0);
+                    }
+                }
+                continue;
+            }
+            let mut index = 0;
+            while index < buffer.len() {
+                if !matcher.ignores(buffer[index].glyph_id) {
+                    if let Some(next) = engine.apply(lookup_index, buffer, index, 0) {
+                        index = next;
+                        continue;
+                    }
+                }
+                index += 1;
+            }
+        }
+    }
+}
+
+struct Engine<'l> {
+    table: &'l GlyphSubstitution,
+    definition: Option<&'l GlyphDefinition>,
+}
+
+impl<'l> Engine<'l> {
+    fn apply(
+        &self,
+        lookup_index: u16,
+        buffer: &mut Buffer,
+        index: usize,
+        depth: usize,
+    ) -> Option<usize> {
+        let record = self.table.lookups.records.get(lookup_index as usize)?;
+        let matcher = Matcher::new(record, self.definition);
+        record
+            .resolved_tables()
+            .find_map(|table| self.apply_table(table, &matcher, buffer, index, depth))
+    }
+
+    fn apply_table(
+        &self,
+        table: &Table,
+        matcher: &Matcher,
+        buffer: &mut Buffer,
+        index: usize,
+        depth: usize,
+    ) -> Option<usize> {
+        let glyph_id = buffer.get(index)?.glyph_id;
+        match *table {
+            Table::SingleSubstitution(SingleSubstitution::Format1(ref table)) => {
+                table.coverage.index(glyph_id)?;
+                buffer[index].glyph_id = (glyph_id as i32 + table.delta_glyph_id as i32) as GlyphID;
+                Some(index + 1)
+            }
+            Table::SingleSubstitution(SingleSubstitution::Format2(ref table)) => {
+                let coverage_index = table.coverage.index(glyph_id)?;
+                buffer[index].glyph_id = *table.glyph_ids.get(coverage_index as usize)?;
+                Some(index + 1)
+            }
+            Table::MultipleSubstitution(ref table) => {
+                let coverage_index = table.coverage.index(glyph_id)?;
+                let glyph_ids = &table.sequences.get(coverage_index as usize)?.glyph_ids;
+                let glyph = buffer[index];
+                buffer.glyphs.splice(
+                    index..(index + 1),
+                    glyph_ids.iter().map(|&glyph_id| Glyph {
+                        glyph_id: glyph_id,
+                        ..glyph
+                    }),
+                );
+                Some(index + glyph_ids.len())
+            }
+            Table::AlternateSubstitution(ref table) => {
+                let coverage_index = table.coverage.index(glyph_id)?;
+                let set = table.sets.get(coverage_index as usize)?;
+                let alternate = buffer[index].alternate as usize;
+                buffer[index].glyph_id = *set.glyph_ids.get(alternate)?;
+                Some(index + 1)
+            }
+            Table::LigatureSubstitution(ref table) => {
+                let coverage_index = table.coverage.index(glyph_id)?;
+                for ligature in &table.sets.get(coverage_index as usize)?.records {
+                    let glyph_ids = &ligature.component_ids;
+                    let indices = matcher.matches(
+                        buffer,
+                        index,
+                        (0, |_, _| true),
+                        (glyph_ids.len(), |i, glyph_id| glyph_ids[i] == glyph_id),
+                        (0, |_, _| true),
+                    );
+                    if let Some(indices) = indices {
+                        ligate(buffer, &indices, ligature.glyph_id);
+                        return Some(index + 1);
+                    }
+                }
+                None
+            }
+            Table::ContextSubstitution(ContextSubstitution::Format1(ref table)) => {
+                let coverage_index = table.coverage.index(glyph_id)?;
+                for rule in &table.sets.get(coverage_index as usize)?.records {
+                    let glyph_ids = &rule.input_glyph_ids;
+                    let indices = matcher.matches(
+                        buffer,
+                        index,
+                        (0, |_, _| true),
+                        (glyph_ids.len(), |i, glyph_id| glyph_ids[i] == glyph_id),
+                        (0, |_, _| true),
+                    );
+                    if let Some(indices) = indices {
+                        return Some(self.apply_operations(
+                            buffer,
+                            indices,
+                            &rule.operations,
+                            depth,
+                        ));
+                    }
+                }
+                None
+            }
+            Table::ContextSubstitution(ContextSubstitution::Format2(ref table)) => {
+                table.coverage.index(glyph_id)?;
+                let class = &table.class;
+                let set = table.sets.get(class.get(glyph_id) as usize)?.as_ref()?;
+                for rule in &set.records {
+                    let class_ids = &rule.input_class_ids;
+                    let indices = matcher.matches(
+                        buffer,
+                        index,
+                        (0, |_, _| true),
+                        (class_ids.len(), |i, glyph_id| {
+                            class_ids[i] == class.get(glyph_id)
+                        }),
+                        (0, |_, _| true),
+                    );
+                    if let Some(indices) = indices {
+                        return Some(self.apply_operations(
+                            buffer,
+                            indices,
+                            &rule.operations,
+                            depth,
+                        ));
+                    }
+                }
+                None
+            }
+            Table::ContextSubstitution(ContextSubstitution::Format3(ref table)) => {
+                let coverages = &table.coverages;
+                if !coverages.first()?.contains(glyph_id) {
+                    return None;
+                }
+                let indices = matcher.matches(
+                    buffer,
+                    index,
+                    (0, |_, _| true),
+                    (coverages.len() - 1, |i, glyph_id| {
+                        coverages[i + 1].contains(glyph_id)
+                    }),
+                    (0, |_, _| true),
+                )?;
+                Some(self.apply_operations(buffer, indices, &table.operations, depth))
+            }
+            Table::ChainContextSubstitution(ChainContextSubstitution::Format1(ref table)) => {
+                let coverage_index = table.coverage.index(glyph_id)?;
+                for rule in &table.sets.get(coverage_index as usize)?.records {
+                    let (backward, input, forward) = (
+                        &rule.backward_glyph_ids,
+                        &rule.input_glyph_ids,
+                        &rule.forward_glyph_ids,
+                    );
+                    let indices = matcher.matches(
+                        buffer,
+                        index,
+                        (backward.len(), |i, glyph_id| backward[i] == glyph_id),
+                        (input.len(), |i, glyph_id| input[i] == glyph_id),
+                        (forward.len(), |i, glyph_id| forward[i] == glyph_id),
+                    );
+                    if let Some(indices) = indices {
+                        return Some(self.apply_operations(
+                            buffer,
+                            indices,
+                            &rule.operations,
+                            depth,
+                        ));
+                    }
+                }
+                None
+            }
+            Table::ChainContextSubstitution(ChainContextSubstitution::Format2(ref table)) => {
+                table.coverage.index(glyph_id)?;
+                let (backward_class, input_class, forward_class) = (
+                    &table.backward_class,
+                    &table.input_class,
+                    &table.forward_class,
+                );
+                let set = table
+                    .sets
+                    .get(input_class.get(glyph_id) as usize)?
+                    .as_ref()?;
+                for rule in &set.records {
+                    let (backward, input, forward) = (
+                        &rule.backward_class_ids,
+                        &rule.input_class_ids,
+                        &rule.forward_class_ids,
+                    );
+                    let indices = matcher.matches(
+                        buffer,
+                        index,
+                        (backward.len(), |i, glyph_id| {
+                            backward[i] == backward_class.get(glyph_id)
+                        }),
+                        (input.len(), |i, glyph_id| {
+                            input[i] == input_class.get(glyph_id)
+                        }),
+                        (forward.len(), |i, glyph_id| {
+                            forward[i] == forward_class.get(glyph_id)
+                        }),
+                    );
+                    if let Some(indices) = indices {
+                        return Some(self.apply_operations(
+                            buffer,
+                            indices,
+                            &rule.operations,
+                            depth,
+                        ));
+                    }
+                }
+                None
+            }
+            Table::ChainContextSubstitution(ChainContextSubstitution::Format3(ref table)) => {
+                let (backward, input, forward) = (
+                    &table.backward_coverages,
+                    &table.input_coverages,
+                    &table.forward_coverages,
+                );
+                if !input.first()?.contains(glyph_id) {
+                    return None;
+                }
+                let indices = matcher.matches(
+                    buffer,
+                    index,
+                    (backward.len(), |i, glyph_id| backward[i].contains(glyph_id)),
+                    (input.len() - 1, |i, glyph_id| {
+                        input[i + 1].contains(glyph_id)
+                    }),
+                    (forward.len(), |i, glyph_id| forward[i].contains(glyph_id)),
+                )?;
+                Some(self.apply_operations(buffer, indices, &table.operations, depth))
+            }
+            Table::ExtensionSubstitution(ref table) => {
+                self.apply_table(&table.table, matcher, buffer, index, depth)
+            }
+            Table::ReverseChainContextSubstitution(ref table) => {
+                let coverage_index = table.coverage.index(glyph_id)?;
+                let (backward, forward) = (&table.backward_coverages, &table.forward_coverages);
+                matcher.matches(
+                    buffer,
+                    index,
+                    (backward.len(), |i, glyph_id| backward[i].contains(glyph_id)),
+                    (0, |_, _| true),
+                    (forward.len(), |i, glyph_id| forward[i].contains(glyph_id)),
+                )?;
+                buffer[index].glyph_id = *table.glyph_ids.get(coverage_index as usize)?;
+                Some(index + 1)
+            }
+            Table::Unknown(_) => None,
+        }
+    }
+
+    fn apply_operations(
+        &self,
+        buffer: &mut Buffer,
+        mut indices: Vec<usize>,
+        operations: &[Substitution],
+        depth: usize,
+    ) -> usize {
+        let mut end = indices[indices.len() - 1] as isize + 1;
+        if depth >= MAXIMUM_DEPTH {
+            return end as usize;
+        }
+        for operation in operations {
+            let sequence_index = operation.sequence_index as usize;
+            let index = match indices.get(sequence_index) {
+                Some(&index) => index,
+                _ => continue,
+            };
+            let length = buffer.len();
+            if self
+                .apply(operation.lookup_index, buffer, index, depth + 1)
+                .is_none()
+            {
+                continue;
+            }
+            let delta = buffer.len() as isize - length as isize;
+            if delta == 0 {
+                continue;
+            }
+            end += delta;
+            let tail = indices
+                .split_off(sequence_index + 1)
+                .into_iter()
+                .map(|other| other as isize + delta)
+                .filter(|&other| other > index as isize && other < buffer.len() as isize)
+                .map(|other| other as usize);
+            indices.extend(tail);
+        }
+        end.max(indices[0] as isize + 1) as usize
+    }
+}
+
+fn ligate(buffer: &mut Buffer, indices: &[usize], glyph_id: GlyphID) {
+    let ligature_id = buffer.next_ligature_id();
+    let (first, last) = (indices[0], indices[indices.len() - 1]);
+    let mut component = 0;
+    for index in first..=last {
+        if indices.contains(&index) {
+            component += 1;
+        } else {
+            buffer[index].ligature_id = ligature_id;
+            buffer[index].component = component;
+        }
+    }
+    let cluster = indices
+        .iter()
+        .map(|&index| buffer[index].cluster)
+        .min()
+        .unwrap_or(0);
+    buffer[first] = Glyph {
+        glyph_id: glyph_id,
+        cluster: cluster,
+        ligature_id: ligature_id,
+        component: 0,
+        alternate: buffer[first].alternate,
+    };
+    for &index in indices[1..].iter().rev() {
+        buffer.glyphs.remove(index);
+    }
+}
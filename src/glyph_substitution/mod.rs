@@ -2,11 +2,17 @@
 //!
 //! [1]: https://www.microsoft.com/typography/otspec/GSUB.htm
 
-use truetype::{GlyphID, Result, Tape, Value, Walue};
+#![allow(unused_parens)]
 
+use truetype::{Error, GlyphID, Result, Tape, Value, Walue};
+
+use crate::layout::lookup::{Extension, Placeholder};
 use crate::layout::{Class, Coverage, Directory};
+use crate::writer::{Write, Writer};
 
+mod closure;
 mod element;
+mod engine;
 
 pub use element::*;
 
@@ -24,6 +30,8 @@ pub enum Table {
     ChainContextSubstitution(ChainContextSubstitution),
     ExtensionSubstitution(ExtensionSubstitution),
     ReverseChainContextSubstitution(ReverseChainContextSubstitution),
+    /// A table that could not be read along with the reason.
+    Unknown(String),
 }
 
 /// A table for substituting one glyph with one glyph.
@@ -183,6 +191,10 @@ table! {
             jump_take!(tape, position, this.coverage_offset)
         },
 
+        class (Class) |this, tape, position| {
+            jump_take!(tape, position, this.class_offset)
+        },
+
         sets (Vec<Option<ClassRules>>) |this, tape, position| {
             jump_take_maybe!(tape, position, this.set_count, this.set_offsets)
         },
@@ -324,13 +336,14 @@ table! {
     }
 }
 
-table! {
-    #[doc = "A table for other types of substitution."]
-    pub ExtensionSubstitution { // ExtensionSubstFormat1
-        format (u16) = { 1 }, // SubstFormat
-        kind   (u16), // ExtensionLookupType
-        offset (u32), // ExtensionOffset
-    }
+/// A table for other types of substitution.
+#[derive(Clone, Debug)]
+#[rustfmt::skip]
+pub struct ExtensionSubstitution { // ExtensionSubstFormat1
+    pub format: (u16       ), // SubstFormat
+    pub kind:   (u16       ), // ExtensionLookupType
+    pub offset: (u32       ), // ExtensionOffset
+    pub table:  (Box<Table>),
 }
 
 table! {
@@ -371,6 +384,24 @@ table! {
     }
 }
 
+impl Extension for Table {
+    const KIND: u16 = 7;
+
+    fn extension(&self) -> Option<(u16, &Self)> {
+        match *self {
+            Table::ExtensionSubstitution(ref table) => Some((table.kind, &table.table)),
+            _ => None,
+        }
+    }
+}
+
+impl Placeholder for Table {
+    #[inline]
+    fn placeholder(error: Error) -> Self {
+        Table::Unknown(error.to_string())
+    }
+}
+
 impl Walue<'static> for Table {
     type Parameter = u16;
 
@@ -420,3 +451,227 @@ impl Value for ChainContextSubstitution {
         })
     }
 }
+
+impl Value for ExtensionSubstitution {
+    fn read<T: Tape>(tape: &mut T) -> Result<Self> {
+        let position = tape.position()?;
+        if tape.take::<u16>()? != 1 {
+            raise!("found an unknown format of the extension-substitution table");
+        }
+        let kind = tape.take()?;
+        if kind == 7 {
+            raise!("found a malformed extension-substitution table");
+        }
+        let offset = tape.take()?;
+        let table = jump_take_given!(@unwrap tape, position, offset, kind);
+        Ok(ExtensionSubstitution {
+            format: 1,
+            kind: kind,
+            offset: offset,
+            table: Box::new(table),
+        })
+    }
+}
+
+impl Write for Table {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        match *self {
+            Table::SingleSubstitution(ref table) => writer.give(table),
+            Table::MultipleSubstitution(ref table) => writer.give(table),
+            Table::AlternateSubstitution(ref table) => writer.give(table),
+            Table::LigatureSubstitution(ref table) => writer.give(table),
+            Table::ContextSubstitution(ref table) => writer.give(table),
+            Table::ChainContextSubstitution(ref table) => writer.give(table),
+            Table::ExtensionSubstitution(ref table) => writer.give(table),
+            Table::ReverseChainContextSubstitution(ref table) => writer.give(table),
+            Table::Unknown(_) => raise!("found an unknown glyph-substitution table"),
+        }
+    }
+}
+
+impl Write for SingleSubstitution {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        match *self {
+            SingleSubstitution::Format1(ref table) => writer.give(table),
+            SingleSubstitution::Format2(ref table) => writer.give(table),
+        }
+    }
+}
+
+impl Write for SingleSubstitution1 {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give(&1u16)?;
+        writer.link(&self.coverage)?;
+        writer.give(&self.delta_glyph_id)
+    }
+}
+
+impl Write for SingleSubstitution2 {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give(&2u16)?;
+        writer.link(&self.coverage)?;
+        writer.give_count(self.glyph_ids.len())?;
+        writer.give(&self.glyph_ids)
+    }
+}
+
+impl Write for MultipleSubstitution {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give(&1u16)?;
+        writer.link(&self.coverage)?;
+        writer.give_count(self.sequences.len())?;
+        for record in &self.sequences {
+            writer.link(record)?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for AlternateSubstitution {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give(&1u16)?;
+        writer.link(&self.coverage)?;
+        writer.give_count(self.sets.len())?;
+        for record in &self.sets {
+            writer.link(record)?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for LigatureSubstitution {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give(&1u16)?;
+        writer.link(&self.coverage)?;
+        writer.give_count(self.sets.len())?;
+        for record in &self.sets {
+            writer.link(record)?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for ContextSubstitution {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        match *self {
+            ContextSubstitution::Format1(ref table) => writer.give(table),
+            ContextSubstitution::Format2(ref table) => writer.give(table),
+            ContextSubstitution::Format3(ref table) => writer.give(table),
+        }
+    }
+}
+
+impl Write for ContextSubstitution1 {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give(&1u16)?;
+        writer.link(&self.coverage)?;
+        writer.give_count(self.sets.len())?;
+        for record in &self.sets {
+            writer.link(record)?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for ContextSubstitution2 {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give(&2u16)?;
+        writer.link(&self.coverage)?;
+        writer.link(&self.class)?;
+        writer.give_count(self.sets.len())?;
+        for record in &self.sets {
+            writer.link_maybe(record.as_ref())?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for ContextSubstitution3 {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give(&3u16)?;
+        writer.give_count(self.coverages.len())?;
+        writer.give_count(self.operations.len())?;
+        for record in &self.coverages {
+            writer.link(record)?;
+        }
+        writer.give(&self.operations)
+    }
+}
+
+impl Write for ChainContextSubstitution {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        match *self {
+            ChainContextSubstitution::Format1(ref table) => writer.give(table),
+            ChainContextSubstitution::Format2(ref table) => writer.give(table),
+            ChainContextSubstitution::Format3(ref table) => writer.give(table),
+        }
+    }
+}
+
+impl Write for ChainContextSubstitution1 {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give(&1u16)?;
+        writer.link(&self.coverage)?;
+        writer.give_count(self.sets.len())?;
+        for record in &self.sets {
+            writer.link(record)?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for ChainContextSubstitution2 {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give(&2u16)?;
+        writer.link(&self.coverage)?;
+        writer.link(&self.backward_class)?;
+        writer.link(&self.input_class)?;
+        writer.link(&self.forward_class)?;
+        writer.give_count(self.sets.len())?;
+        for record in &self.sets {
+            writer.link_maybe(record.as_ref())?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for ChainContextSubstitution3 {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give(&3u16)?;
+        for records in &[
+            &self.backward_coverages,
+            &self.input_coverages,
+            &self.forward_coverages,
+        ] {
+            writer.give_count(records.len())?;
+            for record in records.iter() {
+                writer.link(record)?;
+            }
+        }
+        writer.give_count(self.operations.len())?;
+        writer.give(&self.operations)
+    }
+}
+
+impl Write for ExtensionSubstitution {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give(&1u16)?;
+        writer.give(&self.kind)?;
+        writer.link_detached(&*self.table)
+    }
+}
+
+impl Write for ReverseChainContextSubstitution {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give(&1u16)?;
+        writer.link(&self.coverage)?;
+        for records in &[&self.backward_coverages, &self.forward_coverages] {
+            writer.give_count(records.len())?;
+            for record in records.iter() {
+                writer.link(record)?;
+            }
+        }
+        writer.give_count(self.glyph_ids.len())?;
+        writer.give(&self.glyph_ids)
+    }
+}
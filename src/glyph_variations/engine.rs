@@ -0,0 +1,267 @@
+use std::convert::TryFrom;
+use truetype::glyph_data::{Arguments, Description, Glyph, PointFlags};
+use truetype::{GlyphID, Result};
+
+use crate::glyph_variations::{GlyphVariations, TupleHeader};
+use crate::variation::item::{Region, RegionAxis};
+
+type Point = (f32, f32);
+
+impl GlyphVariations {
+    /// Apply the variations to a glyph at normalized coordinates given in
+    /// format F2DOT14.
+    ///
+    /// The result contains the glyph with the deltas applied and the deltas of
+    /// the four phantom points, which are the left side bearing, the advance
+    /// width, the top side bearing, and the advance height. For simple glyphs,
+    /// the bounding box is recomputed; for composite glyphs, only the offsets
+    /// of the components are adjusted. Coordinates that do not fit into 16
+    /// bits result in an error.
+    pub fn apply(
+        &self,
+        glyph_id: GlyphID,
+        glyph: &Glyph,
+        coordinates: &[i16],
+    ) -> Result<(Glyph, [Point; 4])> {
+        let deltas = self.deltas(glyph_id, Some(glyph), coordinates)?;
+        let count = deltas.len() - 4;
+        let mut glyph = glyph.clone();
+        match glyph.description {
+            Description::Simple(ref mut description) => {
+                let (mut x, mut y) = (0i32, 0i32);
+                let (mut last_x, mut last_y) = (0i32, 0i32);
+                let (mut min_x, mut min_y) = (i32::MAX, i32::MAX);
+                let (mut max_x, mut max_y) = (i32::MIN, i32::MIN);
+                for (i, &(delta_x, delta_y)) in deltas[..count].iter().enumerate() {
+                    x += description.x[i] as i32;
+                    y += description.y[i] as i32;
+                    let next_x = (x as f32 + delta_x).round() as i32;
+                    let next_y = (y as f32 + delta_y).round() as i32;
+                    description.x[i] = coordinate(next_x - last_x)?;
+                    description.y[i] = coordinate(next_y - last_y)?;
+                    description.flags[i] =
+                        encode(description.flags[i], description.x[i], description.y[i]);
+                    last_x = next_x;
+                    last_y = next_y;
+                    min_x = min_x.min(next_x);
+                    min_y = min_y.min(next_y);
+                    max_x = max_x.max(next_x);
+                    max_y = max_y.max(next_y);
+                }
+                if count > 0 {
+                    glyph.min_x = coordinate(min_x)?;
+                    glyph.min_y = coordinate(min_y)?;
+                    glyph.max_x = coordinate(max_x)?;
+                    glyph.max_y = coordinate(max_y)?;
+                }
+            }
+            Description::Composite(ref mut description) => {
+                for (component, &(delta_x, delta_y)) in
+                    description.components.iter_mut().zip(&deltas[..count])
+                {
+                    if let Arguments::Offsets(ref mut x, ref mut y) = component.arguments {
+                        *x = coordinate((*x as f32 + delta_x).round() as i32)?;
+                        *y = coordinate((*y as f32 + delta_y).round() as i32)?;
+                        if !(-128..=127).contains(x) || !(-128..=127).contains(y) {
+                            component.flags.0 |= 0x0001;
+                        }
+                    }
+                }
+            }
+        }
+        Ok((
+            glyph,
+            [
+                deltas[count],
+                deltas[count + 1],
+                deltas[count + 2],
+                deltas[count + 3],
+            ],
+        ))
+    }
+
+    /// Compute the deltas of the points of a glyph at normalized coordinates
+    /// given in format F2DOT14.
+    ///
+    /// The points are the points of the outline for simple glyphs and the
+    /// offsets of the components for composite glyphs, and they are followed
+    /// by the four phantom points. For simple glyphs, the deltas of the points
+    /// not referred to by a tuple variation are inferred from the neighboring
+    /// points of the same contour.
+    pub fn deltas(
+        &self,
+        glyph_id: GlyphID,
+        glyph: Option<&Glyph>,
+        coordinates: &[i16],
+    ) -> Result<Vec<Point>> {
+        let (points, end_points) = match glyph.map(|glyph| &glyph.description) {
+            Some(Description::Simple(description)) => {
+                let (mut x, mut y) = (0i32, 0i32);
+                let points = description
+                    .x
+                    .iter()
+                    .zip(&description.y)
+                    .map(|(&delta_x, &delta_y)| {
+                        x += delta_x as i32;
+                        y += delta_y as i32;
+                        (x as f32, y as f32)
+                    })
+                    .collect();
+                (points, &description.end_points[..])
+            }
+            Some(Description::Composite(description)) => {
+                (vec![(0.0, 0.0); description.components.len()], &[][..])
+            }
+            _ => (vec![], &[][..]),
+        };
+        let count = points.len() + 4;
+        let mut deltas = vec![(0.0, 0.0); count];
+        let record = match self.records.get(glyph_id as usize) {
+            Some(Some(record)) => record,
+            _ => return Ok(deltas),
+        };
+        for (header, values) in record.headers.iter().zip(&record.deltas) {
+            let scalar = self.scalar(header, coordinates)?;
+            if scalar == 0.0 {
+                continue;
+            }
+            match values.points {
+                Some(ref numbers) => {
+                    let mut touched = vec![false; count];
+                    let mut tuple = vec![(0.0, 0.0); count];
+                    for (i, &number) in numbers.iter().enumerate() {
+                        let number = number as usize;
+                        if number >= count {
+                            raise!("found malformed glyph variation data");
+                        }
+                        touched[number] = true;
+                        tuple[number] = (values.x[i] as f32, values.y[i] as f32);
+                    }
+                    infer(&points, end_points, &touched, &mut tuple);
+                    for (delta, value) in deltas.iter_mut().zip(tuple) {
+                        delta.0 += scalar * value.0;
+                        delta.1 += scalar * value.1;
+                    }
+                }
+                _ => {
+                    if values.x.len() != count {
+                        raise!("found malformed glyph variation data");
+                    }
+                    for (i, delta) in deltas.iter_mut().enumerate() {
+                        delta.0 += scalar * values.x[i] as f32;
+                        delta.1 += scalar * values.y[i] as f32;
+                    }
+                }
+            }
+        }
+        Ok(deltas)
+    }
+
+    fn scalar(&self, header: &TupleHeader, coordinates: &[i16]) -> Result<f32> {
+        let peak = match header.peak {
+            Some(ref peak) => peak,
+            _ => match self.shared_tuples.get(header.index.index()) {
+                Some(peak) => peak,
+                _ => raise!("found a malformed glyph-variations table"),
+            },
+        };
+        let axes = match (&header.start, &header.end) {
+            (Some(start), Some(end)) => peak
+                .iter()
+                .zip(start.iter().zip(end))
+                .map(|(&peak, (&start, &end))| RegionAxis {
+                    start: start,
+                    peak: peak,
+                    end: end,
+                })
+                .collect(),
+            _ => peak
+                .iter()
+                .map(|&peak| RegionAxis {
+                    start: peak.min(0),
+                    peak: peak,
+                    end: peak.max(0),
+                })
+                .collect(),
+        };
+        Ok(Region { axes: axes }.scalar(coordinates))
+    }
+}
+
+fn coordinate(value: i32) -> Result<i16> {
+    match i16::try_from(value) {
+        Ok(value) => Ok(value),
+        _ => raise!("found a coordinate overflow"),
+    }
+}
+
+fn encode(flags: PointFlags, x: i16, y: i16) -> PointFlags {
+    let mut value = flags.0 & !0b0011_1110;
+    if x == 0 {
+        value |= 0b0001_0000;
+    } else if x.unsigned_abs() <= 255 {
+        value |= 0b0000_0010 | if x > 0 { 0b0001_0000 } else { 0 };
+    }
+    if y == 0 {
+        value |= 0b0010_0000;
+    } else if y.unsigned_abs() <= 255 {
+        value |= 0b0000_0100 | if y > 0 { 0b0010_0000 } else { 0 };
+    }
+    PointFlags(value)
+}
+
+fn infer(points: &[Point], end_points: &[u16], touched: &[bool], deltas: &mut [Point]) {
+    let mut start = 0;
+    for &end in end_points {
+        let end = end as usize;
+        if end < start || end >= points.len() {
+            break;
+        }
+        let indices = (start..=end).filter(|&i| touched[i]).collect::<Vec<_>>();
+        if indices.len() == 1 {
+            let delta = deltas[indices[0]];
+            deltas[start..=end]
+                .iter_mut()
+                .for_each(|value| *value = delta);
+        } else {
+            for (k, &first) in indices.iter().enumerate() {
+                let last = indices[(k + 1) % indices.len()];
+                let mut i = if first == end { start } else { first + 1 };
+                while i != last {
+                    deltas[i] = (
+                        interpolate(
+                            points[i].0,
+                            (points[first].0, deltas[first].0),
+                            (points[last].0, deltas[last].0),
+                        ),
+                        interpolate(
+                            points[i].1,
+                            (points[first].1, deltas[first].1),
+                            (points[last].1, deltas[last].1),
+                        ),
+                    );
+                    i = if i == end { start } else { i + 1 };
+                }
+            }
+        }
+        start = end + 1;
+    }
+}
+
+fn interpolate(value: f32, first: (f32, f32), last: (f32, f32)) -> f32 {
+    if first.0 == last.0 {
+        return if first.1 == last.1 { first.1 } else { 0.0 };
+    }
+    let (lower, upper) = if first.0 < last.0 {
+        (first, last)
+    } else {
+        (last, first)
+    };
+    if value <= lower.0 {
+        lower.1
+    } else if value >= upper.0 {
+        upper.1
+    } else {
+        lower.1 + (value - lower.0) * (upper.1 - lower.1) / (upper.0 - lower.0)
+    }
+}
@@ -0,0 +1,282 @@
+//! The [glyph-variations table][1].
+//!
+//! [1]: https://www.microsoft.com/typography/otspec/gvar.htm
+
+use std::io::Cursor;
+use truetype::{Result, Tape, Walue};
+
+mod engine;
+
+table! {
+    @position
+    #[doc = "A glyph-variations table."]
+    pub GlyphVariations {
+        major_version        (u16  ) = { 1 }, // majorVersion
+        minor_version        (u16  ) = { 0 }, // minorVersion
+        axis_count           (u16  ), // axisCount
+        shared_tuple_count   (u16  ), // sharedTupleCount
+        shared_tuples_offset (u32  ), // sharedTuplesOffset
+        glyph_count          (u16  ), // glyphCount
+        flags                (Flags), // flags
+        records_offset       (u32  ), // glyphVariationDataArrayOffset
+
+        record_offsets (Vec<u32>) |this, tape, _| { // glyphVariationDataOffsets
+            let count = this.glyph_count as usize + 1;
+            if this.flags.has_long_offsets() {
+                tape.take_given(count)
+            } else {
+                let offsets: Vec<u16> = tape.take_given(count)?;
+                Ok(offsets.into_iter().map(|offset| 2 * offset as u32).collect())
+            }
+        },
+
+        shared_tuples (Vec<Vec<i16>>) |this, tape, position| { // sharedTuples
+            tape.jump(position + this.shared_tuples_offset as u64)?;
+            let mut values = Vec::with_capacity(this.shared_tuple_count as usize);
+            for _ in 0..this.shared_tuple_count {
+                values.push(tape.take_given(this.axis_count as usize)?);
+            }
+            Ok(values)
+        },
+
+        records (Vec<Option<Record>>) |this, tape, position| { // glyphVariationData
+            let offsets = &this.record_offsets;
+            let mut values = Vec::with_capacity(this.glyph_count as usize);
+            for i in 0..(this.glyph_count as usize) {
+                if offsets[i] > offsets[i + 1] {
+                    raise!("found a malformed glyph-variations table");
+                }
+                if offsets[i] == offsets[i + 1] {
+                    values.push(None);
+                    continue;
+                }
+                let offset = this.records_offset as u64 + offsets[i] as u64;
+                tape.jump(position + offset)?;
+                values.push(Some(tape.take_given(this.axis_count as usize)?));
+            }
+            Ok(values)
+        },
+    }
+}
+
+table! {
+    @define
+    #[doc = "Variation data of a glyph."]
+    pub Record { // GlyphVariationData
+        tuple_count (TupleCount      ), // tupleVariationCount
+        data_offset (u16             ), // dataOffset
+        headers     (Vec<TupleHeader>), // tupleVariationHeaders
+        deltas      (Vec<Deltas>     ),
+    }
+}
+
+table! {
+    @define
+    #[doc = "A tuple-variation header."]
+    pub TupleHeader { // TupleVariationHeader
+        size  (u16             ), // variationDataSize
+        index (TupleIndex      ), // tupleIndex
+        peak  (Option<Vec<i16>>), // peakTuple
+        start (Option<Vec<i16>>), // intermediateStartTuple
+        end   (Option<Vec<i16>>), // intermediateEndTuple
+    }
+}
+
+/// The deltas of a tuple variation.
+#[derive(Clone, Debug, Default)]
+pub struct Deltas {
+    /// The numbers of the points with deltas or `None` if all points have
+    /// deltas.
+    pub points: Option<Vec<u16>>,
+    /// The deltas along the x axis.
+    pub x: Vec<i16>,
+    /// The deltas along the y axis.
+    pub y: Vec<i16>,
+}
+
+flags! {
+    #[doc = "Glyph-variations flags."]
+    pub Flags(u16) {
+        0b0000_0000_0000_0001 => has_long_offsets,
+        0b1111_1111_1111_1110 => is_invalid,
+    }
+}
+
+flags! {
+    #[doc = "A tuple-variation count with flags."]
+    pub TupleCount(u16) {
+        0b1000_0000_0000_0000 => has_shared_points,
+        0b0111_0000_0000_0000 => is_invalid,
+    }
+}
+
+flags! {
+    #[doc = "A tuple index with flags."]
+    pub TupleIndex(u16) {
+        0b1000_0000_0000_0000 => has_embedded_peak,
+        0b0100_0000_0000_0000 => has_intermediate_region,
+        0b0010_0000_0000_0000 => has_private_points,
+        0b0001_0000_0000_0000 => is_invalid,
+    }
+}
+
+impl TupleCount {
+    /// Return the number of tuple variations.
+    #[inline]
+    pub fn count(&self) -> usize {
+        (self.0 & 0x0FFF) as usize
+    }
+}
+
+impl TupleIndex {
+    /// Return the index of the shared tuple used as the peak if the peak is not
+    /// embedded.
+    #[inline]
+    pub fn index(&self) -> usize {
+        (self.0 & 0x0FFF) as usize
+    }
+}
+
+impl Walue<'static> for Record {
+    type Parameter = usize;
+
+    fn read<T: Tape>(tape: &mut T, axis_count: usize) -> Result<Self> {
+        let position = tape.position()?;
+        let tuple_count = tape.take::<TupleCount>()?;
+        let data_offset = tape.take::<u16>()?;
+        let mut headers = Vec::with_capacity(tuple_count.count());
+        for _ in 0..tuple_count.count() {
+            headers.push(tape.take_given::<TupleHeader>(axis_count)?);
+        }
+        tape.jump(position + data_offset as u64)?;
+        let shared_points = if tuple_count.has_shared_points() {
+            read_points(tape)?
+        } else {
+            None
+        };
+        let mut deltas = Vec::with_capacity(headers.len());
+        for header in &headers {
+            let data = tape.take_bytes(header.size as usize)?;
+            let tape = &mut Cursor::new(&data);
+            let points = if header.index.has_private_points() {
+                read_points(tape)?
+            } else {
+                shared_points.clone()
+            };
+            let values = match points {
+                Some(ref points) => read_deltas(tape, 2 * points.len())?,
+                _ => {
+                    let mut values = vec![];
+                    while (tape.position()? as usize) < data.len() {
+                        values.extend(read_deltas(tape, 1)?);
+                    }
+                    values
+                }
+            };
+            let count = match points {
+                Some(ref points) => 2 * points.len(),
+                _ => values.len() + values.len() % 2,
+            };
+            if values.len() != count {
+                raise!("found malformed packed deltas");
+            }
+            let (x, y) = values.split_at(values.len() / 2);
+            deltas.push(Deltas {
+                points: points,
+                x: x.to_vec(),
+                y: y.to_vec(),
+            });
+        }
+        Ok(Record {
+            tuple_count: tuple_count,
+            data_offset: data_offset,
+            headers: headers,
+            deltas: deltas,
+        })
+    }
+}
+
+impl Walue<'static> for TupleHeader {
+    type Parameter = usize;
+
+    fn read<T: Tape>(tape: &mut T, axis_count: usize) -> Result<Self> {
+        let size = tape.take()?;
+        let index = tape.take::<TupleIndex>()?;
+        let peak = if index.has_embedded_peak() {
+            Some(tape.take_given(axis_count)?)
+        } else {
+            None
+        };
+        let (start, end) = if index.has_intermediate_region() {
+            (
+                Some(tape.take_given(axis_count)?),
+                Some(tape.take_given(axis_count)?),
+            )
+        } else {
+            (None, None)
+        };
+        Ok(TupleHeader {
+            size: size,
+            index: index,
+            peak: peak,
+            start: start,
+            end: end,
+        })
+    }
+}
+
+/// Read packed point numbers and return `None` if all points are referred to.
+fn read_points<T: Tape>(tape: &mut T) -> Result<Option<Vec<u16>>> {
+    let first = tape.take::<u8>()? as usize;
+    let count = if first & 0x80 != 0 {
+        ((first & 0x7F) << 8) | tape.take::<u8>()? as usize
+    } else {
+        first
+    };
+    if count == 0 {
+        return Ok(None);
+    }
+    let mut values = Vec::with_capacity(count);
+    let mut value = 0u16;
+    while values.len() < count {
+        let control = tape.take::<u8>()?;
+        let run_count = (control & 0x7F) as usize + 1;
+        if values.len() + run_count > count {
+            raise!("found malformed packed point numbers");
+        }
+        for _ in 0..run_count {
+            let delta = if control & 0x80 != 0 {
+                tape.take::<u16>()?
+            } else {
+                tape.take::<u8>()? as u16
+            };
+            value = value.wrapping_add(delta);
+            values.push(value);
+        }
+    }
+    Ok(Some(values))
+}
+
+/// Read packed deltas until at least a given number of them has been read.
+fn read_deltas<T: Tape>(tape: &mut T, count: usize) -> Result<Vec<i16>> {
+    let mut values = Vec::with_capacity(count);
+    while values.len() < count {
+        let control = tape.take::<u8>()?;
+        let run_count = (control & 0x3F) as usize + 1;
+        match control & 0xC0 {
+            0x80 => values.extend(std::iter::repeat(0).take(run_count)),
+            0x40 => {
+                for _ in 0..run_count {
+                    values.push(tape.take::<i16>()?);
+                }
+            }
+            0x00 => {
+                for _ in 0..run_count {
+                    values.push(tape.take::<i8>()? as i16);
+                }
+            }
+            _ => raise!("found malformed packed deltas"),
+        }
+    }
+    Ok(values)
+}
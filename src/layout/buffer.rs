@@ -0,0 +1,85 @@
+//! The glyph buffer.
+
+use std::ops::{Deref, DerefMut};
+use truetype::GlyphID;
+
+/// A glyph buffer.
+#[derive(Clone, Debug, Default)]
+pub struct Buffer {
+    /// The glyphs.
+    pub glyphs: Vec<Glyph>,
+
+    ligature_count: u16,
+}
+
+/// A glyph of a glyph buffer.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Glyph {
+    /// The identifier of the glyph.
+    pub glyph_id: GlyphID,
+    /// The index of the cluster the glyph belongs to.
+    pub cluster: usize,
+    /// The identifier of the ligature the glyph is or is attached to if any.
+    pub ligature_id: u16,
+    /// The one-based index of the component of the ligature the glyph is
+    /// attached to if any.
+    pub component: u16,
+    /// The zero-based index of the alternate to choose if the glyph is subject
+    /// to an alternate substitution.
+    pub alternate: u16,
+}
+
+impl Buffer {
+    /// Create a buffer.
+    #[inline]
+    pub fn new(glyphs: Vec<Glyph>) -> Self {
+        Buffer {
+            glyphs: glyphs,
+            ligature_count: 0,
+        }
+    }
+
+    /// Return the glyph identifiers.
+    #[inline]
+    pub fn glyph_ids(&self) -> Vec<GlyphID> {
+        self.glyphs.iter().map(|glyph| glyph.glyph_id).collect()
+    }
+
+    pub(crate) fn next_ligature_id(&mut self) -> u16 {
+        self.ligature_count = self.ligature_count.wrapping_add(1).max(1);
+        self.ligature_count
+    }
+}
+
+impl Deref for Buffer {
+    type Target = [Glyph];
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.glyphs
+    }
+}
+
+impl DerefMut for Buffer {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.glyphs
+    }
+}
+
+impl From<&[GlyphID]> for Buffer {
+    /// Create a buffer with one cluster per glyph.
+    fn from(glyph_ids: &[GlyphID]) -> Self {
+        Buffer::new(
+            glyph_ids
+                .iter()
+                .enumerate()
+                .map(|(i, &glyph_id)| Glyph {
+                    glyph_id: glyph_id,
+                    cluster: i,
+                    ..Default::default()
+                })
+                .collect(),
+        )
+    }
+}
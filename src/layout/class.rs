@@ -1,5 +1,8 @@
+use std::cmp::Ordering;
 use truetype::{GlyphID, Result, Tape, Value};
 
+use crate::writer::{Write, Writer};
+
 /// A class definition.
 #[derive(Clone, Debug)]
 pub enum Class {
@@ -44,6 +47,65 @@ table! {
     }
 }
 
+impl Class {
+    /// Return the class of a glyph.
+    ///
+    /// Glyphs that are not listed belong to class zero.
+    pub fn get(&self, glyph_id: GlyphID) -> u16 {
+        match *self {
+            Class::Format1(ref table) => {
+                if glyph_id < table.start {
+                    return 0;
+                }
+                let index = (glyph_id - table.start) as usize;
+                table.values.get(index).cloned().unwrap_or(0)
+            }
+            Class::Format2(ref table) => table
+                .ranges
+                .binary_search_by(|range| {
+                    if range.end < glyph_id {
+                        Ordering::Less
+                    } else if range.start > glyph_id {
+                        Ordering::Greater
+                    } else {
+                        Ordering::Equal
+                    }
+                })
+                .map(|index| table.ranges[index].index)
+                .unwrap_or(0),
+        }
+    }
+
+    /// Iterate over the glyphs of a class given the number of glyphs in the
+    /// font.
+    pub fn glyphs_in(&self, class: u16, glyph_count: u16) -> impl Iterator<Item = GlyphID> + '_ {
+        (0..glyph_count).filter(move |&glyph_id| self.get(glyph_id) == class)
+    }
+
+    /// Iterate over the glyphs that are listed and their classes in order.
+    pub fn iter(&self) -> impl Iterator<Item = (GlyphID, u16)> + '_ {
+        let (table1, table2) = match *self {
+            Class::Format1(ref table) => (Some(table), None),
+            Class::Format2(ref table) => (None, Some(table)),
+        };
+        let values = table1.into_iter().flat_map(|table| {
+            let start = table.start as usize;
+            table
+                .values
+                .iter()
+                .enumerate()
+                .map(move |(i, &class)| ((start + i) as GlyphID, class))
+        });
+        let ranges = table2
+            .into_iter()
+            .flat_map(|table| &table.ranges)
+            .flat_map(|range| {
+                (range.start..=range.end).map(move |glyph_id| (glyph_id, range.index))
+            });
+        values.chain(ranges)
+    }
+}
+
 impl Default for Class {
     #[inline]
     fn default() -> Self {
@@ -60,3 +122,37 @@ impl Value for Class {
         })
     }
 }
+
+impl Write for Class {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        match *self {
+            Class::Format1(ref table) => writer.give(table),
+            Class::Format2(ref table) => writer.give(table),
+        }
+    }
+}
+
+impl Write for Class1 {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give(&1u16)?;
+        writer.give(&self.start)?;
+        writer.give_count(self.values.len())?;
+        writer.give(&self.values)
+    }
+}
+
+impl Write for Class2 {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give(&2u16)?;
+        writer.give_count(self.ranges.len())?;
+        writer.give(&self.ranges)
+    }
+}
+
+impl Write for ClassRange {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give(&self.start)?;
+        writer.give(&self.end)?;
+        writer.give(&self.index)
+    }
+}
@@ -1,5 +1,8 @@
 use truetype::{Result, Tape, Value};
 
+use crate::variation::item::Variations;
+use crate::writer::{Write, Writer};
+
 /// A correction.
 #[derive(Clone, Debug)]
 pub enum Correction {
@@ -46,6 +49,29 @@ impl Value for Correction {
     }
 }
 
+impl Correction {
+    /// Compute the adjustment at normalized coordinates given in format
+    /// F2DOT14.
+    ///
+    /// Device corrections do not depend on the coordinates and yield zero.
+    pub fn vary(&self, variations: &Variations, coordinates: &[i16]) -> f32 {
+        match *self {
+            Correction::Device(_) => 0.0,
+            Correction::Variation(ref correction) => correction.delta(variations, coordinates),
+        }
+    }
+
+    /// Compute the adjustment in pixels at a size given in pixels per em.
+    ///
+    /// Variation corrections do not depend on the size and yield zero.
+    pub fn hint(&self, size: u16) -> i16 {
+        match *self {
+            Correction::Device(ref correction) => correction.delta(size),
+            Correction::Variation(_) => 0,
+        }
+    }
+}
+
 impl Default for Correction {
     #[inline]
     fn default() -> Self {
@@ -53,6 +79,26 @@ impl Default for Correction {
     }
 }
 
+impl Device {
+    /// Compute the delta in pixels at a size given in pixels per em.
+    ///
+    /// Sizes outside the range of the table yield zero.
+    pub fn delta(&self, size: u16) -> i16 {
+        if size < self.start_size || size > self.end_size {
+            return 0;
+        }
+        let bits = 1 << self.format as usize;
+        let index = (size - self.start_size) as usize;
+        let word = match self.deltas.get(index * bits / 16) {
+            Some(&word) => word,
+            _ => return 0,
+        };
+        let shift = 16 - bits - index * bits % 16;
+        let value = (word >> shift) & ((1 << bits) - 1);
+        ((value << (16 - bits)) as i16) >> (16 - bits)
+    }
+}
+
 impl Value for Device {
     fn read<T: Tape>(tape: &mut T) -> Result<Self> {
         let start_size = tape.take()?;
@@ -66,7 +112,7 @@ impl Value for Device {
         }
         let count = (end_size - start_size) as usize + 1;
         let bit_count = (1 << format as usize) * count;
-        let short_count = (bit_count + 16 - bit_count % 16) >> 4;
+        let short_count = (bit_count + 15) >> 4;
         let deltas = tape.take_given(short_count)?;
         Ok(Device {
             start_size: start_size,
@@ -76,3 +122,46 @@ impl Value for Device {
         })
     }
 }
+
+impl Variation {
+    /// Compute the delta at normalized coordinates given in format F2DOT14.
+    ///
+    /// Items missing from the variation store yield zero.
+    #[inline]
+    pub fn delta(&self, variations: &Variations, coordinates: &[i16]) -> f32 {
+        variations
+            .delta(self.outer_index, self.inner_index, coordinates)
+            .unwrap_or(0.0)
+    }
+}
+
+impl Write for Correction {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        match *self {
+            Correction::Device(ref table) => writer.give(table),
+            Correction::Variation(ref table) => writer.give(table),
+        }
+    }
+}
+
+impl Write for Device {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        let count = (self.end_size as usize + 1).saturating_sub(self.start_size as usize);
+        let short_count = ((1 << self.format as usize) * count + 15) >> 4;
+        if self.start_size > self.end_size || self.deltas.len() != short_count {
+            raise!("found a malformed device table");
+        }
+        writer.give(&self.start_size)?;
+        writer.give(&self.end_size)?;
+        writer.give(&self.format)?;
+        writer.give(&self.deltas)
+    }
+}
+
+impl Write for Variation {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give(&self.outer_index)?;
+        writer.give(&self.inner_index)?;
+        writer.give(&0x8000u16)
+    }
+}
@@ -1,5 +1,8 @@
+use std::cmp::Ordering;
 use truetype::{GlyphID, Result, Tape, Value};
 
+use crate::writer::{Write, Writer};
+
 /// A coverage table.
 #[derive(Clone, Debug)]
 pub enum Coverage {
@@ -43,6 +46,80 @@ table! {
     }
 }
 
+impl Coverage {
+    /// Return the coverage index of a glyph if the glyph is covered.
+    pub fn index(&self, glyph_id: GlyphID) -> Option<u16> {
+        match *self {
+            Coverage::Format1(ref table) => table
+                .glyph_ids
+                .binary_search(&glyph_id)
+                .ok()
+                .map(|index| index as u16),
+            Coverage::Format2(ref table) => {
+                let index = table
+                    .ranges
+                    .binary_search_by(|range| {
+                        if range.end < glyph_id {
+                            Ordering::Less
+                        } else if range.start > glyph_id {
+                            Ordering::Greater
+                        } else {
+                            Ordering::Equal
+                        }
+                    })
+                    .ok()?;
+                let range = &table.ranges[index];
+                range.index.checked_add(glyph_id - range.start)
+            }
+        }
+    }
+
+    /// Check if a glyph is covered.
+    #[inline]
+    pub fn contains(&self, glyph_id: GlyphID) -> bool {
+        self.index(glyph_id).is_some()
+    }
+
+    /// Iterate over the covered glyphs and their coverage indices in order.
+    pub fn iter(&self) -> impl Iterator<Item = (GlyphID, u16)> + '_ {
+        let (glyph_ids, ranges) = match *self {
+            Coverage::Format1(ref table) => (Some(&table.glyph_ids), None),
+            Coverage::Format2(ref table) => (None, Some(&table.ranges)),
+        };
+        let glyph_ids = glyph_ids
+            .into_iter()
+            .flatten()
+            .enumerate()
+            .map(|(index, &glyph_id)| (glyph_id, index as u16));
+        let ranges = ranges.into_iter().flatten().flat_map(|range| {
+            (range.start..=range.end).map_while(move |glyph_id| {
+                let index = range.index.checked_add(glyph_id - range.start)?;
+                Some((glyph_id, index))
+            })
+        });
+        glyph_ids.chain(ranges)
+    }
+
+    /// Return the number of covered glyphs.
+    pub fn len(&self) -> usize {
+        match *self {
+            Coverage::Format1(ref table) => table.glyph_ids.len(),
+            Coverage::Format2(ref table) => table
+                .ranges
+                .iter()
+                .filter(|range| range.start <= range.end)
+                .map(|range| (range.end - range.start) as usize + 1)
+                .sum(),
+        }
+    }
+
+    /// Check if no glyph is covered.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
 impl Default for Coverage {
     #[inline]
     fn default() -> Self {
@@ -59,3 +136,36 @@ impl Value for Coverage {
         })
     }
 }
+
+impl Write for Coverage {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        match *self {
+            Coverage::Format1(ref table) => writer.give(table),
+            Coverage::Format2(ref table) => writer.give(table),
+        }
+    }
+}
+
+impl Write for Coverage1 {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give(&1u16)?;
+        writer.give_count(self.glyph_ids.len())?;
+        writer.give(&self.glyph_ids)
+    }
+}
+
+impl Write for Coverage2 {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give(&2u16)?;
+        writer.give_count(self.ranges.len())?;
+        writer.give(&self.ranges)
+    }
+}
+
+impl Write for CoverageRange {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give(&self.start)?;
+        writer.give(&self.end)?;
+        writer.give(&self.index)
+    }
+}
@@ -1,9 +1,11 @@
 #![allow(unused_parens)]
 
-use truetype::{Result, Tape, Value, Walue};
+use truetype::{Result, Tag, Tape, Value, Walue};
 
 use crate::layout::feature::Variations;
+use crate::layout::lookup::{Extension, Placeholder};
 use crate::layout::{Features, Lookups, Scripts};
+use crate::writer::{Write, Writer};
 
 /// A layout directory.
 #[derive(Clone, Debug)]
@@ -22,11 +24,51 @@ pub struct Directory<T> {
     pub variations: (Option<Variations>),
 }
 
+impl<T> Directory<T> {
+    /// Return the features with the substitutions applicable to normalized
+    /// coordinates given in format F2DOT14.
+    pub fn features_at(&self, coordinates: &[i16]) -> Features {
+        match self
+            .variations
+            .as_ref()
+            .and_then(|variations| variations.find(coordinates))
+        {
+            Some(substitutions) => self.features.substitute(substitutions),
+            _ => self.features.clone(),
+        }
+    }
+
+    /// Return the indices of the lookups to apply for a script, a language,
+    /// and a set of features given by their tags.
+    ///
+    /// See `Scripts::find` and `Features::lookup_indices` for the details.
+    pub fn lookup_indices(&self, script: Tag, language: Option<Tag>, features: &[Tag]) -> Vec<u16> {
+        match self.scripts.find(script, language) {
+            Some(language) => self.features.lookup_indices(language, features),
+            _ => vec![],
+        }
+    }
+}
+
 impl<U> Value for Directory<U>
 where
-    U: Walue<'static, Parameter = u16>,
+    U: Walue<'static, Parameter = u16> + Placeholder,
 {
+    #[inline]
     fn read<T: Tape>(tape: &mut T) -> Result<Self> {
+        tape.take_given(false)
+    }
+}
+
+impl<U> Walue<'static> for Directory<U>
+where
+    U: Walue<'static, Parameter = u16> + Placeholder,
+{
+    /// Whether lookup tables that cannot be read are to be replaced with
+    /// placeholders instead of failing.
+    type Parameter = bool;
+
+    fn read<T: Tape>(tape: &mut T, lenient: bool) -> Result<Self> {
         let position = tape.position()?;
         let major_version = tape.take()?;
         let minor_version = tape.take()?;
@@ -43,7 +85,7 @@ where
         };
         let scripts = jump_take!(@unwrap tape, position, scripts_offset);
         let features = jump_take!(@unwrap tape, position, features_offset);
-        let lookups = jump_take!(@unwrap tape, position, lookups_offset);
+        let lookups = jump_take_given!(@unwrap tape, position, lookups_offset, lenient);
         let variations = jump_take_maybe!(@unwrap tape, position, variations_offset);
         Ok(Directory {
             major_version: major_version,
@@ -59,3 +101,24 @@ where
         })
     }
 }
+
+impl<T> Write for Directory<T>
+where
+    T: Write + Extension,
+{
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give(&1u16)?;
+        writer.give(&if self.variations.is_some() {
+            1u16
+        } else {
+            0u16
+        })?;
+        writer.link(&self.scripts)?;
+        writer.link(&self.features)?;
+        writer.link(&self.lookups)?;
+        if let Some(ref variations) = self.variations {
+            writer.link_long(variations)?;
+        }
+        Ok(())
+    }
+}
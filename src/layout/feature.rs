@@ -1,6 +1,9 @@
 //! The feature list.
 
-use truetype::Tag;
+use truetype::{Result, Tag, Tape, Value};
+
+use crate::layout::script::LanguageRecord;
+use crate::writer::{Write, Writer};
 
 table! {
     @position
@@ -50,11 +53,287 @@ table! {
 }
 
 table! {
+    @position
     #[doc = "Feature variations."]
-    #[derive(Copy)]
     pub Variations { // FeatureVariations
         major_version (u16) = { 1 }, // MajorVersion
         minor_version (u16) = { 0 }, // MinorVersion
         count         (u32), // FeatureVariationRecordsCount
+
+        headers (Vec<VariationHeader>) |this, tape, _| { // FeatureVariationRecord
+            tape.take_given(this.count as usize)
+        },
+
+        conditions (Vec<Option<Conditions>>) |this, tape, position| {
+            Ok(jump_take_maybe!(@unwrap tape, position, this.count,
+                                i => this.headers[i].conditions_offset))
+        },
+
+        substitutions (Vec<Option<Substitutions>>) |this, tape, position| {
+            Ok(jump_take_maybe!(@unwrap tape, position, this.count,
+                                i => this.headers[i].substitutions_offset))
+        },
+    }
+}
+
+table! {
+    #[doc = "A feature-variation header."]
+    #[derive(Copy)]
+    pub VariationHeader { // FeatureVariationRecord
+        conditions_offset    (u32), // ConditionSet
+        substitutions_offset (u32), // FeatureTableSubstitution
+    }
+}
+
+table! {
+    @position
+    #[doc = "A set of conditions."]
+    pub Conditions { // ConditionSet
+        count (u16), // ConditionCount
+
+        offsets (Vec<u32>) |this, tape, _| { // Condition
+            tape.take_given(this.count as usize)
+        },
+
+        records (Vec<Condition>) |this, tape, position| {
+            jump_take!(tape, position, this.count, this.offsets)
+        },
+    }
+}
+
+/// A condition.
+#[derive(Clone, Debug)]
+pub enum Condition {
+    /// Format 1.
+    Format1(Condition1),
+    /// An unknown format, which is never satisfied.
+    Unknown(u16),
+}
+
+table! {
+    #[doc = "A condition in format 1."]
+    #[derive(Copy)]
+    pub Condition1 { // ConditionTableFormat1
+        format     (u16), // Format
+        axis_index (u16), // AxisIndex
+        min_value  (i16), // FilterRangeMinValue
+        max_value  (i16), // FilterRangeMaxValue
+    }
+}
+
+table! {
+    @position
+    #[doc = "A set of feature substitutions."]
+    pub Substitutions { // FeatureTableSubstitution
+        major_version (u16) = { 1 }, // MajorVersion
+        minor_version (u16) = { 0 }, // MinorVersion
+        count         (u16), // SubstitutionCount
+
+        headers (Vec<SubstitutionHeader>) |this, tape, _| { // FeatureTableSubstitutionRecord
+            tape.take_given(this.count as usize)
+        },
+
+        records (Vec<Record>) |this, tape, position| {
+            jump_take!(tape, position, this.count, i => this.headers[i].offset)
+        },
+    }
+}
+
+table! {
+    #[doc = "A feature-substitution header."]
+    #[derive(Copy)]
+    pub SubstitutionHeader { // FeatureTableSubstitutionRecord
+        index  (u16), // FeatureIndex
+        offset (u32), // AlternateFeatureTable
+    }
+}
+
+impl Features {
+    /// Return the features with substitutions applied.
+    pub fn substitute(&self, substitutions: &Substitutions) -> Features {
+        let mut features = self.clone();
+        for (header, record) in substitutions.headers.iter().zip(&substitutions.records) {
+            if let Some(value) = features.records.get_mut(header.index as usize) {
+                *value = record.clone();
+            }
+        }
+        features
+    }
+
+    /// Return the indices of the lookups of a language system that belong to
+    /// the required feature or to features with the given tags.
+    ///
+    /// The indices are sorted and deduplicated, which is the order in which
+    /// the lookups are to be applied.
+    pub fn lookup_indices(&self, language: &LanguageRecord, tags: &[Tag]) -> Vec<u16> {
+        let mut indices = vec![];
+        let required = Some(language.required_feature_index).filter(|&index| index != 0xFFFF);
+        for index in required
+            .into_iter()
+            .chain(language.feature_indices.iter().cloned())
+        {
+            let index = index as usize;
+            let (header, record) = match (self.headers.get(index), self.records.get(index)) {
+                (Some(header), Some(record)) => (header, record),
+                _ => continue,
+            };
+            if Some(index as u16) == required || tags.contains(&header.tag) {
+                indices.extend_from_slice(&record.lookup_indices);
+            }
+        }
+        indices.sort();
+        indices.dedup();
+        indices
+    }
+}
+
+impl Variations {
+    /// Return the substitutions of the first set of conditions satisfied by
+    /// normalized coordinates given in format F2DOT14.
+    ///
+    /// A missing set of conditions is always satisfied, and missing
+    /// substitutions result in no substitutions.
+    pub fn find(&self, coordinates: &[i16]) -> Option<&Substitutions> {
+        for (i, conditions) in self.conditions.iter().enumerate() {
+            let satisfied = match conditions {
+                Some(conditions) => conditions.check(coordinates),
+                _ => true,
+            };
+            if satisfied {
+                return self.substitutions.get(i).and_then(Option::as_ref);
+            }
+        }
+        None
+    }
+}
+
+impl Conditions {
+    /// Check if all the conditions are satisfied by normalized coordinates
+    /// given in format F2DOT14.
+    pub fn check(&self, coordinates: &[i16]) -> bool {
+        self.records
+            .iter()
+            .all(|condition| condition.check(coordinates))
+    }
+}
+
+impl Condition {
+    /// Check if the condition is satisfied by normalized coordinates given in
+    /// format F2DOT14.
+    ///
+    /// Axes without a coordinate are considered to be at the default location.
+    /// Conditions of unknown formats are not satisfied.
+    pub fn check(&self, coordinates: &[i16]) -> bool {
+        match *self {
+            Condition::Format1(ref condition) => {
+                let value = coordinates
+                    .get(condition.axis_index as usize)
+                    .cloned()
+                    .unwrap_or(0);
+                condition.min_value <= value && value <= condition.max_value
+            }
+            Condition::Unknown(_) => false,
+        }
+    }
+}
+
+impl Default for Condition {
+    #[inline]
+    fn default() -> Self {
+        Condition::Format1(Condition1::default())
+    }
+}
+
+impl Value for Condition {
+    fn read<T: Tape>(tape: &mut T) -> Result<Self> {
+        Ok(match tape.peek::<u16>()? {
+            1 => Condition::Format1(tape.take()?),
+            format => Condition::Unknown(format),
+        })
+    }
+}
+
+impl Write for Features {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        if self.headers.len() != self.records.len() {
+            raise!("found a malformed feature list");
+        }
+        writer.give_count(self.records.len())?;
+        for (header, record) in self.headers.iter().zip(&self.records) {
+            writer.give(&header.tag)?;
+            writer.link(record)?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for Record {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        match self.parameters {
+            Some(ref parameters) if !parameters.is_empty() => writer.link(parameters)?,
+            _ => writer.give(&0u16)?,
+        }
+        writer.give_count(self.lookup_indices.len())?;
+        writer.give(&self.lookup_indices)
+    }
+}
+
+impl Write for Variations {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        if self.conditions.len() != self.substitutions.len() {
+            raise!("found malformed feature variations");
+        }
+        writer.give(&1u16)?;
+        writer.give(&0u16)?;
+        writer.give(&(self.conditions.len() as u32))?;
+        for (conditions, substitutions) in self.conditions.iter().zip(&self.substitutions) {
+            writer.link_long_maybe(conditions.as_ref())?;
+            writer.link_long_maybe(substitutions.as_ref())?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for Conditions {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give_count(self.records.len())?;
+        for record in &self.records {
+            writer.link_long(record)?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for Condition {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        match *self {
+            Condition::Format1(ref table) => writer.give(table),
+            Condition::Unknown(_) => raise!("found an unknown condition"),
+        }
+    }
+}
+
+impl Write for Condition1 {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give(&1u16)?;
+        writer.give(&self.axis_index)?;
+        writer.give(&self.min_value)?;
+        writer.give(&self.max_value)
+    }
+}
+
+impl Write for Substitutions {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        if self.headers.len() != self.records.len() {
+            raise!("found a malformed set of feature substitutions");
+        }
+        writer.give(&1u16)?;
+        writer.give(&0u16)?;
+        writer.give_count(self.records.len())?;
+        for (header, record) in self.headers.iter().zip(&self.records) {
+            writer.give(&header.index)?;
+            writer.link_long(record)?;
+        }
+        Ok(())
     }
 }
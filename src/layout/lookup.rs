@@ -2,7 +2,9 @@
 
 #![allow(unused_parens)]
 
-use truetype::{Result, Tape, Value, Walue};
+use truetype::{Error, Result, Tape, Value, Walue};
+
+use crate::writer::{Write, Writer};
 
 /// A lookup list.
 #[derive(Clone, Debug)]
@@ -25,6 +27,21 @@ pub struct Record<T> { // Lookup
     pub tables:             (Vec<T>     ),
 }
 
+/// A lookup table that might be an extension of another lookup table.
+pub trait Extension {
+    /// The type of extension lookups.
+    const KIND: u16;
+
+    /// Return the type and the table that the table extends if any.
+    fn extension(&self) -> Option<(u16, &Self)>;
+}
+
+/// A lookup table that can stand in for a lookup table that cannot be read.
+pub trait Placeholder {
+    /// Create a placeholder given the error encountered when reading.
+    fn placeholder(error: Error) -> Self;
+}
+
 flags! {
     #[doc = "Lookup flags."]
     pub Flags(u16) {
@@ -37,15 +54,37 @@ flags! {
     }
 }
 
+impl Flags {
+    /// Return the class of marks that are not to be skipped if nonzero.
+    #[inline]
+    pub fn mark_attachment_type(&self) -> u16 {
+        self.0 >> 8
+    }
+}
+
 impl<U> Value for Lookups<U>
 where
-    U: Walue<'static, Parameter = u16>,
+    U: Walue<'static, Parameter = u16> + Placeholder,
 {
+    #[inline]
     fn read<T: Tape>(tape: &mut T) -> Result<Self> {
+        tape.take_given(false)
+    }
+}
+
+impl<U> Walue<'static> for Lookups<U>
+where
+    U: Walue<'static, Parameter = u16> + Placeholder,
+{
+    /// Whether lookup tables that cannot be read are to be replaced with
+    /// placeholders instead of failing.
+    type Parameter = bool;
+
+    fn read<T: Tape>(tape: &mut T, lenient: bool) -> Result<Self> {
         let position = tape.position()?;
         let count = tape.take::<u16>()?;
         let offsets: Vec<u16> = tape.take_given(count as usize)?;
-        let records = jump_take!(@unwrap tape, position, count, offsets);
+        let records = jump_take_given!(@unwrap tape, position, count, offsets, lenient);
         Ok(Lookups {
             count: count,
             offsets: offsets,
@@ -56,9 +95,23 @@ where
 
 impl<U> Value for Record<U>
 where
-    U: Walue<'static, Parameter = u16>,
+    U: Walue<'static, Parameter = u16> + Placeholder,
 {
+    #[inline]
     fn read<T: Tape>(tape: &mut T) -> Result<Self> {
+        tape.take_given(false)
+    }
+}
+
+impl<U> Walue<'static> for Record<U>
+where
+    U: Walue<'static, Parameter = u16> + Placeholder,
+{
+    /// Whether lookup tables that cannot be read are to be replaced with
+    /// placeholders instead of failing.
+    type Parameter = bool;
+
+    fn read<T: Tape>(tape: &mut T, lenient: bool) -> Result<Self> {
         let position = tape.position()?;
         let kind = tape.take()?;
         let flags = tape.take::<Flags>()?;
@@ -69,7 +122,19 @@ where
         } else {
             None
         };
-        let tables = jump_take_given!(@unwrap tape, position, table_count, table_offsets, kind);
+        let tables = if lenient {
+            let mut tables = Vec::with_capacity(table_count as usize);
+            for &offset in &table_offsets {
+                tape.jump(position + offset as u64)?;
+                tables.push(match tape.take_given(kind) {
+                    Ok(table) => table,
+                    Err(error) => U::placeholder(error),
+                });
+            }
+            tables
+        } else {
+            jump_take_given!(@unwrap tape, position, table_count, table_offsets, kind)
+        };
         Ok(Record {
             kind: kind,
             flags: flags,
@@ -80,3 +145,74 @@ where
         })
     }
 }
+
+impl<T> Record<T>
+where
+    T: Extension,
+{
+    /// Return the lookup type with extensions resolved.
+    pub fn resolved_kind(&self) -> u16 {
+        match self.tables.first().and_then(|table| table.extension()) {
+            Some((kind, _)) => kind,
+            _ => self.kind,
+        }
+    }
+
+    /// Iterate over the tables with extensions resolved.
+    pub fn resolved_tables(&self) -> impl Iterator<Item = &T> {
+        self.tables.iter().map(|table| match table.extension() {
+            Some((_, table)) => table,
+            _ => table,
+        })
+    }
+}
+
+impl<T> Write for Lookups<T>
+where
+    T: Write + Extension,
+{
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give_count(self.records.len())?;
+        for record in &self.records {
+            writer.link(record)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T> Write for Record<T>
+where
+    T: Write + Extension,
+{
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        let extended = writer.is_extended() && self.kind != T::KIND;
+        writer.give(&if extended { T::KIND } else { self.kind })?;
+        let mut flags = self.flags.0 & !0b0000_0000_0001_0000;
+        if self.mark_filtering_set.is_some() {
+            flags |= 0b0000_0000_0001_0000;
+        }
+        writer.give(&flags)?;
+        writer.give_count(self.tables.len())?;
+        for table in &self.tables {
+            if extended {
+                writer.link(&Extended(self.kind, table))?;
+            } else {
+                writer.link(table)?;
+            }
+        }
+        if let Some(index) = self.mark_filtering_set {
+            writer.give(&index)?;
+        }
+        Ok(())
+    }
+}
+
+struct Extended<'l, T>(u16, &'l T);
+
+impl<'l, T: Write> Write for Extended<'l, T> {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give(&1u16)?;
+        writer.give(&self.0)?;
+        writer.link_detached(self.1)
+    }
+}
@@ -0,0 +1,151 @@
+use truetype::GlyphID;
+
+use crate::glyph_definition::GlyphDefinition;
+use crate::layout::lookup::{Flags, Record};
+use crate::layout::{Coverage, Glyph};
+
+/// A glyph matcher honoring the flags of a lookup.
+///
+/// Glyphs are skipped according to their classes in the glyph-definition
+/// table. Without the table, no glyph is skipped.
+#[derive(Clone, Copy, Debug)]
+pub struct Matcher<'l> {
+    flags: Flags,
+    mark_filtering_set: Option<&'l Coverage>,
+    definition: Option<&'l GlyphDefinition>,
+}
+
+impl<'l> Matcher<'l> {
+    /// Create a matcher for a lookup.
+    pub fn new<T>(record: &Record<T>, definition: Option<&'l GlyphDefinition>) -> Self {
+        let mark_filtering_set = match (record.mark_filtering_set, definition) {
+            (Some(index), Some(definition)) => definition
+                .marks
+                .as_ref()
+                .and_then(|marks| marks.coverages.get(index as usize)),
+            _ => None,
+        };
+        Matcher {
+            flags: record.flags,
+            mark_filtering_set: mark_filtering_set,
+            definition: definition,
+        }
+    }
+
+    /// Return the class of a glyph as defined in the glyph-definition table.
+    pub fn class(&self, glyph_id: GlyphID) -> u16 {
+        self.definition
+            .and_then(|definition| definition.glyph_class.as_ref())
+            .map(|class| class.get(glyph_id))
+            .unwrap_or(0)
+    }
+
+    /// Check if a glyph should be skipped.
+    pub fn ignores(&self, glyph_id: GlyphID) -> bool {
+        match self.class(glyph_id) {
+            1 => self.flags.should_ignore_base_glyphs(),
+            2 => self.flags.should_ignore_ligature(),
+            3 => {
+                if self.flags.should_ignore_marks() {
+                    return true;
+                }
+                if self.flags.has_mark_filtering() {
+                    return match self.mark_filtering_set {
+                        Some(coverage) => !coverage.contains(glyph_id),
+                        _ => true,
+                    };
+                }
+                let kind = self.flags.mark_attachment_type();
+                if kind != 0 {
+                    let class = self
+                        .definition
+                        .and_then(|definition| definition.mark_class.as_ref())
+                        .map(|class| class.get(glyph_id))
+                        .unwrap_or(0);
+                    return class != kind;
+                }
+                false
+            }
+            _ => false,
+        }
+    }
+
+    /// Return the index of the next glyph that should not be skipped.
+    pub fn next(&self, glyphs: &[Glyph], index: usize) -> Option<usize> {
+        self.following(glyphs, index).next()
+    }
+
+    /// Return the index of the previous glyph that should not be skipped.
+    pub fn previous(&self, glyphs: &[Glyph], index: usize) -> Option<usize> {
+        self.preceding(glyphs, index).next()
+    }
+
+    /// Iterate over the indices of the glyphs that follow a glyph and should
+    /// not be skipped.
+    pub fn following<'m>(
+        &'m self,
+        glyphs: &'m [Glyph],
+        index: usize,
+    ) -> impl Iterator<Item = usize> + 'm {
+        ((index + 1)..glyphs.len()).filter(move |&i| !self.ignores(glyphs[i].glyph_id))
+    }
+
+    /// Iterate over the indices of the glyphs that precede a glyph and should
+    /// not be skipped starting from the closest one.
+    pub fn preceding<'m>(
+        &'m self,
+        glyphs: &'m [Glyph],
+        index: usize,
+    ) -> impl Iterator<Item = usize> + 'm {
+        (0..index.min(glyphs.len()))
+            .rev()
+            .filter(move |&i| !self.ignores(glyphs[i].glyph_id))
+    }
+
+    /// Match a sequence of glyphs starting with the glyph at an index and
+    /// return the indices of the input glyphs.
+    ///
+    /// The backtrack, input, and lookahead sequences are given by their
+    /// lengths and predicates, which receive the position within the
+    /// sequence and a glyph. The first input glyph is assumed to have been
+    /// matched by the caller and is not counted. The backtrack sequence is
+    /// matched in reverse order.
+    pub(crate) fn matches<F, G, H>(
+        &self,
+        glyphs: &[Glyph],
+        index: usize,
+        (backward_count, mut backward): (usize, F),
+        (input_count, mut input): (usize, G),
+        (forward_count, mut forward): (usize, H),
+    ) -> Option<Vec<usize>>
+    where
+        F: FnMut(usize, GlyphID) -> bool,
+        G: FnMut(usize, GlyphID) -> bool,
+        H: FnMut(usize, GlyphID) -> bool,
+    {
+        let mut indices = Vec::with_capacity(input_count + 1);
+        indices.push(index);
+        let mut last = index;
+        for i in 0..input_count {
+            last = self.next(glyphs, last)?;
+            if !input(i, glyphs[last].glyph_id) {
+                return None;
+            }
+            indices.push(last);
+        }
+        let mut first = index;
+        for i in 0..backward_count {
+            first = self.previous(glyphs, first)?;
+            if !backward(i, glyphs[first].glyph_id) {
+                return None;
+            }
+        }
+        for i in 0..forward_count {
+            last = self.next(glyphs, last)?;
+            if !forward(i, glyphs[last].glyph_id) {
+                return None;
+            }
+        }
+        Some(indices)
+    }
+}
@@ -2,19 +2,23 @@
 //!
 //! [1]: https://www.microsoft.com/typography/otspec/chapter2.htm
 
+mod buffer;
 mod class;
 mod correction;
 mod coverage;
 mod directory;
+mod matcher;
 
 pub mod feature;
 pub mod lookup;
 pub mod script;
 
+pub use buffer::{Buffer, Glyph};
 pub use class::{Class, Class1, Class2, ClassRange};
 pub use correction::{Correction, Device, Variation};
 pub use coverage::{Coverage, Coverage1, Coverage2, CoverageRange};
 pub use directory::Directory;
 pub use feature::Features;
 pub use lookup::Lookups;
+pub use matcher::Matcher;
 pub use script::Scripts;
@@ -1,6 +1,8 @@
 //! The script list.
 
-use truetype::Tag;
+use truetype::{Result, Tag};
+
+use crate::writer::{Write, Writer};
 
 table! {
     @position
@@ -73,6 +75,69 @@ table! {
     }
 }
 
+impl Scripts {
+    /// Return the record of a language system given the tags of a script and
+    /// a language.
+    ///
+    /// If the script is absent, the default script (`DFLT`) is used instead.
+    /// If the language is absent or not given, the default language system of
+    /// the script is used instead.
+    pub fn find(&self, script: Tag, language: Option<Tag>) -> Option<&LanguageRecord> {
+        let position = |tag| self.headers.iter().position(|header| header.tag == tag);
+        let record = position(script)
+            .or_else(|| position(Tag(*b"DFLT")))
+            .and_then(|i| self.records.get(i))?;
+        language
+            .filter(|&tag| tag != Tag(*b"dflt"))
+            .and_then(|tag| {
+                record
+                    .language_headers
+                    .iter()
+                    .position(|header| header.tag == tag)
+            })
+            .and_then(|i| record.language_records.get(i))
+            .or(record.default_language.as_ref())
+    }
+}
+
+impl Write for Scripts {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        if self.headers.len() != self.records.len() {
+            raise!("found a malformed script list");
+        }
+        writer.give_count(self.records.len())?;
+        for (header, record) in self.headers.iter().zip(&self.records) {
+            writer.give(&header.tag)?;
+            writer.link(record)?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for Record {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        if self.language_headers.len() != self.language_records.len() {
+            raise!("found a malformed script table");
+        }
+        writer.link_maybe(self.default_language.as_ref())?;
+        writer.give_count(self.language_records.len())?;
+        for (header, record) in self.language_headers.iter().zip(&self.language_records) {
+            writer.give(&header.tag)?;
+            writer.link(record)?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for LanguageRecord {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give(&0u16)?;
+        writer.give(&self.required_feature_index)?;
+        writer.give_count(self.feature_indices.len())?;
+        writer.give(&self.feature_indices)
+    }
+}
+
 macro_rules! implement {
     ($($tag:expr => $name:expr => $token:ident,)*) => (
         /// A script.
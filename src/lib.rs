@@ -46,16 +46,27 @@ mod file;
 mod font;
 mod table;
 
+pub mod axis_variations;
+pub mod collection;
 pub mod compact2;
+pub mod font_variations;
 pub mod glyph_definition;
 pub mod glyph_positioning;
 pub mod glyph_substitution;
+pub mod glyph_variations;
 pub mod layout;
+pub mod subset;
 pub mod variation;
+pub mod woff;
+pub mod woff2;
+pub mod writer;
 
+pub use axis_variations::AxisVariations;
 pub use file::File;
-pub use font::Font;
+pub use font::{Builder, Font, Options};
+pub use font_variations::FontVariations;
 pub use glyph_definition::GlyphDefinition;
 pub use glyph_positioning::GlyphPositioning;
 pub use glyph_substitution::GlyphSubstitution;
+pub use glyph_variations::GlyphVariations;
 pub use table::Table;
@@ -0,0 +1,189 @@
+use std::collections::BTreeMap;
+use truetype::char_mapping::Encoding;
+use truetype::{CharMapping, GlyphID};
+
+/// Read the Unicode code points and their glyphs.
+pub fn read(table: &CharMapping) -> BTreeMap<u32, GlyphID> {
+    let mut characters = BTreeMap::new();
+    for (record, encoding) in table.records.iter().zip(&table.encodings) {
+        match (record.platform_id, record.encoding_id) {
+            (0, _) | (3, 1) | (3, 10) => {}
+            _ => continue,
+        }
+        match *encoding {
+            Encoding::Format4(ref encoding) => {
+                for (code_point, glyph_id) in encoding.mapping() {
+                    if code_point != 0xFFFF {
+                        characters.entry(code_point as u32).or_insert(glyph_id);
+                    }
+                }
+            }
+            Encoding::Format12(ref encoding) => {
+                for (code_point, glyph_id) in encoding.mapping() {
+                    characters.entry(code_point).or_insert(glyph_id);
+                }
+            }
+            _ => {}
+        }
+    }
+    characters.retain(|_, &mut glyph_id| glyph_id != 0);
+    characters
+}
+
+/// Write a char-to-glyph mapping with a subtable in format 4 and, if there are
+/// code points beyond the Basic Multilingual Plane, a subtable in format 12.
+///
+/// If the code points of the Basic Multilingual Plane do not fit into a
+/// subtable in format 4, only the subtable in format 12 is written.
+pub fn write(characters: &BTreeMap<u32, GlyphID>) -> Vec<u8> {
+    let mut subtables = vec![];
+    if let Some(subtable) = write4(characters) {
+        subtables.push((1, subtable));
+    }
+    if subtables.is_empty() || characters.keys().any(|&code_point| code_point > 0xFFFF) {
+        subtables.push((10, write12(characters)));
+    }
+    let mut data = vec![];
+    data.extend_from_slice(&0u16.to_be_bytes());
+    data.extend_from_slice(&(subtables.len() as u16).to_be_bytes());
+    let mut offset = 4 + 8 * subtables.len();
+    for (encoding_id, subtable) in &subtables {
+        data.extend_from_slice(&3u16.to_be_bytes());
+        data.extend_from_slice(&(*encoding_id as u16).to_be_bytes());
+        data.extend_from_slice(&(offset as u32).to_be_bytes());
+        offset += subtable.len();
+    }
+    for (_, subtable) in &subtables {
+        data.extend_from_slice(subtable);
+    }
+    data
+}
+
+/// Write a subtable in format 4 if it fits.
+///
+/// The segments map consecutive code points to consecutive glyphs via deltas.
+/// If there are too many of them, the segments cover runs of consecutive code
+/// points instead and list the glyphs of the runs that are not consecutive.
+fn write4(characters: &BTreeMap<u32, GlyphID>) -> Option<Vec<u8>> {
+    let characters = characters
+        .iter()
+        .filter(|&(&code_point, _)| code_point < 0xFFFF)
+        .map(|(&code_point, &glyph_id)| (code_point, glyph_id))
+        .collect::<Vec<_>>();
+    let mut segments = group(characters.iter().cloned())
+        .into_iter()
+        .map(|(start, end, glyph_id)| (start, end, glyph_id, vec![]))
+        .collect::<Vec<_>>();
+    if 16 + 8 * (segments.len() + 1) > u16::MAX as usize {
+        segments = run(&characters);
+    }
+    segments.push((0xFFFF, 0xFFFF, 0, vec![]));
+    let count = segments.len();
+    let glyph_count = segments
+        .iter()
+        .map(|(_, _, _, glyph_ids)| glyph_ids.len())
+        .sum::<usize>();
+    let length = 16 + 8 * count + 2 * glyph_count;
+    if length > u16::MAX as usize {
+        return None;
+    }
+    let mut selector = 0;
+    while 1 << (selector + 1) <= count {
+        selector += 1;
+    }
+    let range = 2 << selector;
+    let mut data = Vec::with_capacity(length);
+    for value in &[4, length, 0, 2 * count, range, selector, 2 * count - range] {
+        data.extend_from_slice(&(*value as u16).to_be_bytes());
+    }
+    for &(_, end, _, _) in &segments {
+        data.extend_from_slice(&(end as u16).to_be_bytes());
+    }
+    data.extend_from_slice(&0u16.to_be_bytes());
+    for &(start, _, _, _) in &segments {
+        data.extend_from_slice(&(start as u16).to_be_bytes());
+    }
+    for (start, _, glyph_id, glyph_ids) in &segments {
+        let delta = if glyph_ids.is_empty() {
+            (*glyph_id as u32).wrapping_sub(*start) as u16
+        } else {
+            0
+        };
+        data.extend_from_slice(&delta.to_be_bytes());
+    }
+    let mut position = 0;
+    for (i, (_, _, _, glyph_ids)) in segments.iter().enumerate() {
+        let offset = if glyph_ids.is_empty() {
+            0
+        } else {
+            2 * (count - i) + 2 * position
+        };
+        data.extend_from_slice(&(offset as u16).to_be_bytes());
+        position += glyph_ids.len();
+    }
+    for glyph_id in segments.iter().flat_map(|(_, _, _, glyph_ids)| glyph_ids) {
+        data.extend_from_slice(&glyph_id.to_be_bytes());
+    }
+    Some(data)
+}
+
+fn write12(characters: &BTreeMap<u32, GlyphID>) -> Vec<u8> {
+    let groups = group(
+        characters
+            .iter()
+            .map(|(&code_point, &glyph_id)| (code_point, glyph_id)),
+    );
+    let mut data = Vec::with_capacity(16 + 12 * groups.len());
+    data.extend_from_slice(&12u16.to_be_bytes());
+    data.extend_from_slice(&0u16.to_be_bytes());
+    data.extend_from_slice(&((16 + 12 * groups.len()) as u32).to_be_bytes());
+    data.extend_from_slice(&0u32.to_be_bytes());
+    data.extend_from_slice(&(groups.len() as u32).to_be_bytes());
+    for &(start, end, glyph_id) in &groups {
+        data.extend_from_slice(&start.to_be_bytes());
+        data.extend_from_slice(&end.to_be_bytes());
+        data.extend_from_slice(&(glyph_id as u32).to_be_bytes());
+    }
+    data
+}
+
+fn run(characters: &[(u32, GlyphID)]) -> Vec<(u32, u32, GlyphID, Vec<GlyphID>)> {
+    let mut runs: Vec<(u32, u32, GlyphID, Vec<GlyphID>)> = vec![];
+    for &(code_point, glyph_id) in characters {
+        match runs.last_mut() {
+            Some(last) if code_point == last.1 + 1 => {
+                last.1 = code_point;
+                last.3.push(glyph_id);
+            }
+            _ => runs.push((code_point, code_point, glyph_id, vec![glyph_id])),
+        }
+    }
+    for (_, _, first, glyph_ids) in &mut runs {
+        let consecutive = glyph_ids
+            .iter()
+            .enumerate()
+            .all(|(i, &glyph_id)| glyph_id as usize == *first as usize + i);
+        if consecutive {
+            glyph_ids.clear();
+        }
+    }
+    runs
+}
+
+fn group<T>(characters: T) -> Vec<(u32, u32, GlyphID)>
+where
+    T: Iterator<Item = (u32, GlyphID)>,
+{
+    let mut groups: Vec<(u32, u32, GlyphID)> = vec![];
+    for (code_point, glyph_id) in characters {
+        if let Some(last) = groups.last_mut() {
+            let (start, end, first) = *last;
+            if code_point == end + 1 && glyph_id as u32 == first as u32 + (code_point - start) {
+                last.1 = code_point;
+                continue;
+            }
+        }
+        groups.push((code_point, code_point, glyph_id));
+    }
+    groups
+}
@@ -0,0 +1,397 @@
+use postscript::compact1::{Header, Index, Operand, Operation, Operator};
+use postscript::Tape;
+use std::io::Cursor;
+use truetype::Result;
+
+use super::Mapping;
+
+const CHARSET: u16 = 15;
+const ENCODING: u16 = 16;
+const CHAR_STRINGS: u16 = 17;
+const PRIVATE: u16 = 18;
+const SUBROUTINES: u16 = 19;
+const ROS: u16 = 0x0C1E;
+const FD_ARRAY: u16 = 0x0C24;
+const FD_SELECT: u16 = 0x0C25;
+
+/// An entry of a dictionary.
+///
+/// The operands are kept in their original encoding, so that the entries that
+/// are not overridden are written back as they were.
+struct Entry {
+    operator: u16,
+    operands: Vec<u8>,
+    values: Vec<Operand>,
+}
+
+/// Subset a font set in the compact font format.
+///
+/// The header, the names, the strings, and the global subroutines are copied
+/// as is. The encoding is dropped, as it is superseded by the char-to-glyph
+/// mapping. The indices and dictionaries are read via the `postscript` crate;
+/// the charset and the font-dictionary selector are read here, since formats
+/// 2 and 3, respectively, are not supported there.
+pub fn subset(data: &[u8], mapping: &Mapping) -> Result<Vec<u8>> {
+    let tape = &mut Cursor::new(data);
+    let header_size = tape.take::<Header>()?.header_size as usize;
+    let (_, names_end) = index(tape, header_size)?;
+    let (tops, tops_end) = index(tape, names_end)?;
+    let (_, strings_end) = index(tape, tops_end)?;
+    let (_, subroutines_end) = index(tape, strings_end)?;
+    if tops.len() != 1 {
+        raise!("found an unsupported number of fonts in a font set");
+    }
+    let top = dictionary(&tops[0])?;
+
+    let (char_strings, _) = index(tape, value(&top, CHAR_STRINGS)?)?;
+    let glyph_count = char_strings.len();
+    if mapping
+        .iter()
+        .any(|glyph_id| glyph_id as usize >= glyph_count)
+    {
+        raise!("found a malformed font set");
+    }
+    let char_strings = mapping
+        .iter()
+        .map(|glyph_id| &char_strings[glyph_id as usize][..])
+        .collect::<Vec<_>>();
+    let char_strings = write_index(&char_strings);
+    let names = charset(tape, value(&top, CHARSET).unwrap_or(0), glyph_count)?;
+    let mut charset = vec![0];
+    for glyph_id in mapping.iter().skip(1) {
+        charset.extend_from_slice(&names[glyph_id as usize].to_be_bytes());
+    }
+
+    let top_size = write_index(&[&encode(&top, &overrides(&top, [0; 4]), Some(ENCODING))]).len();
+    let start = names_end + top_size + (subroutines_end - tops_end);
+    let mut sections = charset;
+    let (top, rest) = if top.iter().any(|entry| entry.operator == ROS) {
+        let selectors = select(tape, value(&top, FD_SELECT)?, glyph_count)?;
+        let selectors = mapping
+            .iter()
+            .map(|glyph_id| selectors[glyph_id as usize])
+            .collect::<Vec<_>>();
+        let (dictionaries, _) = index(tape, value(&top, FD_ARRAY)?)?;
+        let dictionaries = dictionaries
+            .iter()
+            .map(|data| dictionary(data))
+            .collect::<Result<Vec<_>>>()?;
+        let char_set_offset = start;
+        let select_offset = char_set_offset + sections.len();
+        sections.extend(write_select(&selectors));
+        let char_strings_offset = start + sections.len();
+        sections.extend(char_strings);
+        let array_offset = start + sections.len();
+        let mut privates = vec![];
+        for dictionary in &dictionaries {
+            let (size, offset) = private(dictionary)?;
+            privates.push(subset_private(data, size, offset)?);
+        }
+        let mut private_offset = array_offset
+            + write_index(
+                &dictionaries
+                    .iter()
+                    .map(|dictionary| encode(dictionary, &[(PRIVATE, vec![0, 0])], None))
+                    .collect::<Vec<_>>()
+                    .iter()
+                    .map(Vec::as_slice)
+                    .collect::<Vec<_>>(),
+            )
+            .len();
+        let mut array = vec![];
+        let mut rest = vec![];
+        for (dictionary, (private, size)) in dictionaries.iter().zip(privates) {
+            let values = vec![size as i32, private_offset as i32];
+            array.push(encode(dictionary, &[(PRIVATE, values)], None));
+            private_offset += private.len();
+            rest.extend(private);
+        }
+        let array = array.iter().map(Vec::as_slice).collect::<Vec<_>>();
+        sections.extend(write_index(&array));
+        sections.extend(rest);
+        let offsets = [
+            char_set_offset,
+            char_strings_offset,
+            array_offset,
+            select_offset,
+        ];
+        (
+            encode(&top, &overrides(&top, offsets), Some(ENCODING)),
+            sections,
+        )
+    } else {
+        let char_set_offset = start;
+        let char_strings_offset = start + sections.len();
+        sections.extend(char_strings);
+        let (size, offset) = private(&top)?;
+        let (private, size) = subset_private(data, size, offset)?;
+        let private_offset = start + sections.len();
+        sections.extend(private);
+        let offsets = [char_set_offset, char_strings_offset, size, private_offset];
+        (
+            encode(&top, &overrides(&top, offsets), Some(ENCODING)),
+            sections,
+        )
+    };
+
+    let mut result = data[..names_end].to_vec();
+    result.extend(write_index(&[&top[..]]));
+    result.extend_from_slice(&data[tops_end..subroutines_end]);
+    debug_assert_eq!(result.len(), start);
+    result.extend(rest);
+    Ok(result)
+}
+
+/// Return the new operands of the top dictionary given the offsets of the
+/// charset and the char strings followed by either the offsets of the
+/// font-dictionary array and selector or the size and offset of the private
+/// dictionary.
+fn overrides(top: &[Entry], values: [usize; 4]) -> Vec<(u16, Vec<i32>)> {
+    let mut overrides = vec![
+        (CHARSET, vec![values[0] as i32]),
+        (CHAR_STRINGS, vec![values[1] as i32]),
+    ];
+    if top.iter().any(|entry| entry.operator == ROS) {
+        overrides.push((FD_ARRAY, vec![values[2] as i32]));
+        overrides.push((FD_SELECT, vec![values[3] as i32]));
+    } else {
+        overrides.push((PRIVATE, vec![values[2] as i32, values[3] as i32]));
+    }
+    overrides
+}
+
+fn private(dictionary: &[Entry]) -> Result<(usize, usize)> {
+    match dictionary.iter().find(|entry| entry.operator == PRIVATE) {
+        Some(entry) if entry.values.len() == 2 => {
+            match (integer(entry.values[0]), integer(entry.values[1])) {
+                (Some(size), Some(offset)) => Ok((size, offset)),
+                _ => raise!("found a malformed font dictionary"),
+            }
+        }
+        _ => raise!("found a malformed font dictionary"),
+    }
+}
+
+/// Return a private dictionary followed by its local subroutines and the size
+/// of the dictionary.
+fn subset_private(data: &[u8], size: usize, offset: usize) -> Result<(Vec<u8>, usize)> {
+    if offset + size > data.len() {
+        raise!("found a malformed private dictionary");
+    }
+    let dictionary = self::dictionary(&data[offset..(offset + size)])?;
+    let subroutines = match dictionary
+        .iter()
+        .find(|entry| entry.operator == SUBROUTINES)
+    {
+        Some(entry) if entry.values.len() == 1 && integer(entry.values[0]).is_some() => {
+            let start = offset + entry.values[0] as usize;
+            let (_, end) = index(&mut Cursor::new(data), start)?;
+            Some(&data[start..end])
+        }
+        Some(_) => raise!("found a malformed private dictionary"),
+        _ => None,
+    };
+    let mut result = match subroutines {
+        Some(_) => encode(&dictionary, &[(SUBROUTINES, vec![0])], None),
+        _ => encode(&dictionary, &[], None),
+    };
+    let size = result.len();
+    if let Some(subroutines) = subroutines {
+        result = encode(&dictionary, &[(SUBROUTINES, vec![size as i32])], None);
+        result.extend_from_slice(subroutines);
+    }
+    Ok((result, size))
+}
+
+fn value(dictionary: &[Entry], operator: u16) -> Result<usize> {
+    match dictionary.iter().find(|entry| entry.operator == operator) {
+        Some(entry) if entry.values.len() == 1 => match integer(entry.values[0]) {
+            Some(value) => Ok(value),
+            _ => raise!("found a malformed top dictionary"),
+        },
+        _ => raise!("found a malformed top dictionary"),
+    }
+}
+
+/// Convert an operand referring to a size or an offset.
+///
+/// The operands are read as single-precision numbers, which represent integers
+/// exactly only up to 2^24, and hence larger values are rejected along with
+/// negative and real ones.
+fn integer(value: Operand) -> Option<usize> {
+    if (0.0..=16_777_216.0).contains(&value) && value.fract() == 0.0 {
+        Some(value as usize)
+    } else {
+        None
+    }
+}
+
+/// Read an index and return its elements and its end.
+fn index<T: Tape>(tape: &mut T, position: usize) -> Result<(Vec<Vec<u8>>, usize)> {
+    tape.jump(position as u64)?;
+    let Index { data, .. } = tape.take()?;
+    Ok((data, tape.position()? as usize))
+}
+
+fn write_index(elements: &[&[u8]]) -> Vec<u8> {
+    let mut data = vec![];
+    data.extend_from_slice(&(elements.len() as u16).to_be_bytes());
+    if elements.is_empty() {
+        return data;
+    }
+    let total = elements.iter().map(|element| element.len()).sum::<usize>() + 1;
+    let offset_size = match total {
+        0..=0xFF => 1,
+        0x100..=0xFFFF => 2,
+        0x10000..=0xFF_FFFF => 3,
+        _ => 4,
+    };
+    data.push(offset_size as u8);
+    let mut offset = 1;
+    for i in 0..(elements.len() + 1) {
+        data.extend_from_slice(&(offset as u32).to_be_bytes()[(4 - offset_size)..]);
+        if let Some(element) = elements.get(i) {
+            offset += element.len();
+        }
+    }
+    for element in elements {
+        data.extend_from_slice(element);
+    }
+    data
+}
+
+fn dictionary(data: &[u8]) -> Result<Vec<Entry>> {
+    let tape = &mut Cursor::new(data);
+    let mut entries = vec![];
+    while (tape.position()? as usize) < data.len() {
+        let start = tape.position()? as usize;
+        let (operator, values) = tape.take::<Operation>()?;
+        let end = tape.position()? as usize;
+        // The operator is encoded in the last byte unless it is escaped.
+        let last = data[end - 1] as u16;
+        let (operator, size) = if Operator::from(last).ok() == Some(operator) {
+            (last, 1)
+        } else {
+            (0x0C00 | last, 2)
+        };
+        entries.push(Entry {
+            operator: operator,
+            operands: data[start..(end - size)].to_vec(),
+            values: values,
+        });
+    }
+    Ok(entries)
+}
+
+/// Encode a dictionary with the operands of some operators replaced by
+/// integers of fixed width and another operator removed.
+fn encode(dictionary: &[Entry], overrides: &[(u16, Vec<i32>)], removed: Option<u16>) -> Vec<u8> {
+    let mut data = vec![];
+    let mut write = |operator: u16, values: &[i32], operands: Option<&[u8]>| {
+        match operands {
+            Some(operands) => data.extend_from_slice(operands),
+            _ => {
+                for &value in values {
+                    data.push(29);
+                    data.extend_from_slice(&value.to_be_bytes());
+                }
+            }
+        }
+        if operator > 0xFF {
+            data.push(12);
+        }
+        data.push(operator as u8);
+    };
+    for entry in dictionary {
+        if Some(entry.operator) == removed {
+            continue;
+        }
+        match overrides
+            .iter()
+            .find(|(operator, _)| *operator == entry.operator)
+        {
+            Some((_, values)) => write(entry.operator, values, None),
+            _ => write(entry.operator, &[], Some(&entry.operands)),
+        }
+    }
+    for (operator, values) in overrides {
+        if dictionary.iter().all(|entry| entry.operator != *operator) {
+            write(*operator, values, None);
+        }
+    }
+    data
+}
+
+/// Read a charset and return the string or CID identifiers of all glyphs.
+fn charset<T: Tape>(tape: &mut T, offset: usize, glyph_count: usize) -> Result<Vec<u16>> {
+    let mut names = vec![0];
+    match offset {
+        0 => names.extend((1..glyph_count).map(|glyph_id| glyph_id as u16)),
+        1 | 2 => raise!("found an unsupported charset"),
+        _ => {
+            tape.jump(offset as u64)?;
+            let format = tape.take::<u8>()?;
+            while names.len() < glyph_count {
+                match format {
+                    0 => names.push(tape.take()?),
+                    1 | 2 => {
+                        let first = tape.take::<u16>()?;
+                        let left = if format == 1 {
+                            tape.take::<u8>()? as u16
+                        } else {
+                            tape.take::<u16>()?
+                        };
+                        names.extend((0..=left).map(|i| first.wrapping_add(i)));
+                    }
+                    _ => raise!("found an unknown format of the charset"),
+                }
+            }
+            names.truncate(glyph_count);
+        }
+    }
+    Ok(names)
+}
+
+/// Read a font-dictionary selector and return the font dictionaries of all
+/// glyphs.
+fn select<T: Tape>(tape: &mut T, offset: usize, glyph_count: usize) -> Result<Vec<u8>> {
+    tape.jump(offset as u64)?;
+    match tape.take::<u8>()? {
+        0 => tape.take_given(glyph_count),
+        3 => {
+            let count = tape.take::<u16>()? as usize;
+            let mut selectors = vec![0; glyph_count];
+            let mut first = tape.take::<u16>()? as usize;
+            for _ in 0..count {
+                let selector = tape.take::<u8>()?;
+                let next = tape.take::<u16>()? as usize;
+                if first > next || next > glyph_count {
+                    raise!("found a malformed font-dictionary selector");
+                }
+                for value in &mut selectors[first..next] {
+                    *value = selector;
+                }
+                first = next;
+            }
+            Ok(selectors)
+        }
+        _ => raise!("found an unknown format of the font-dictionary selector"),
+    }
+}
+
+fn write_select(selectors: &[u8]) -> Vec<u8> {
+    let mut ranges: Vec<(usize, u8)> = vec![];
+    for (glyph_id, &selector) in selectors.iter().enumerate() {
+        if ranges.last().map_or(true, |&(_, last)| last != selector) {
+            ranges.push((glyph_id, selector));
+        }
+    }
+    let mut data = vec![3];
+    data.extend_from_slice(&(ranges.len() as u16).to_be_bytes());
+    for &(first, selector) in &ranges {
+        data.extend_from_slice(&(first as u16).to_be_bytes());
+        data.push(selector);
+    }
+    data.extend_from_slice(&(selectors.len() as u16).to_be_bytes());
+    data
+}
@@ -0,0 +1,124 @@
+use std::collections::BTreeSet;
+use truetype::{GlyphID, GlyphMapping, Result};
+
+use super::Mapping;
+
+/// Glyph data split into glyphs.
+pub struct Glyphs {
+    data: Vec<u8>,
+    offsets: Vec<usize>,
+}
+
+impl Glyphs {
+    /// Split glyph data given a glyph-to-location mapping.
+    pub fn new(data: Vec<u8>, mapping: &GlyphMapping) -> Result<Self> {
+        let offsets: Vec<usize> = match *mapping {
+            GlyphMapping::HalfOffsets(ref offsets) => {
+                offsets.iter().map(|&offset| 2 * offset as usize).collect()
+            }
+            GlyphMapping::Offsets(ref offsets) => {
+                offsets.iter().map(|&offset| offset as usize).collect()
+            }
+        };
+        if offsets.windows(2).any(|pair| pair[0] > pair[1])
+            || offsets.last().map_or(false, |&offset| offset > data.len())
+        {
+            raise!("found a malformed glyph-to-location mapping");
+        }
+        Ok(Glyphs {
+            data: data,
+            offsets: offsets,
+        })
+    }
+
+    /// Extend glyphs with the components of composite glyphs.
+    pub fn close(&self, glyph_ids: &mut BTreeSet<GlyphID>) -> Result<()> {
+        let mut pending = glyph_ids.iter().cloned().collect::<Vec<_>>();
+        while let Some(glyph_id) = pending.pop() {
+            let glyph = self.get(glyph_id);
+            for position in components(glyph)? {
+                let glyph_id = read(glyph, position);
+                if glyph_ids.insert(glyph_id) {
+                    pending.push(glyph_id);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the retained glyphs and return the glyph data, the
+    /// glyph-to-location mapping, and the format of the latter.
+    pub fn subset(&self, mapping: &Mapping) -> Result<(Vec<u8>, Vec<u8>, i16)> {
+        let mut data = vec![];
+        let mut offsets = vec![0];
+        for glyph_id in mapping.iter() {
+            let glyph = self.get(glyph_id);
+            let start = data.len();
+            data.extend_from_slice(glyph);
+            for position in components(glyph)? {
+                let glyph_id = match mapping.get(read(glyph, position)) {
+                    Some(glyph_id) => glyph_id,
+                    _ => raise!("found a malformed composite glyph"),
+                };
+                data[(start + position)..(start + position + 2)]
+                    .copy_from_slice(&glyph_id.to_be_bytes());
+            }
+            data.resize((data.len() + 1) & !1, 0);
+            offsets.push(data.len());
+        }
+        let mut result = vec![];
+        if data.len() / 2 <= u16::MAX as usize {
+            for offset in offsets {
+                result.extend_from_slice(&((offset / 2) as u16).to_be_bytes());
+            }
+            Ok((data, result, 0))
+        } else {
+            for offset in offsets {
+                result.extend_from_slice(&(offset as u32).to_be_bytes());
+            }
+            Ok((data, result, 1))
+        }
+    }
+
+    fn get(&self, glyph_id: GlyphID) -> &[u8] {
+        let i = glyph_id as usize;
+        match (self.offsets.get(i), self.offsets.get(i + 1)) {
+            (Some(&start), Some(&end)) => &self.data[start..end],
+            _ => &[],
+        }
+    }
+}
+
+/// Return the positions of the glyph identifiers of the components of a
+/// composite glyph.
+fn components(glyph: &[u8]) -> Result<Vec<usize>> {
+    let mut positions = vec![];
+    if glyph.len() < 10 || read(glyph, 0) as i16 >= 0 {
+        return Ok(positions);
+    }
+    let mut position = 10;
+    loop {
+        if position + 4 > glyph.len() {
+            raise!("found a malformed composite glyph");
+        }
+        let flags = read(glyph, position);
+        positions.push(position + 2);
+        position += if flags & 0x0001 != 0 { 8 } else { 6 };
+        if flags & 0x0008 != 0 {
+            position += 2;
+        } else if flags & 0x0040 != 0 {
+            position += 4;
+        } else if flags & 0x0080 != 0 {
+            position += 8;
+        }
+        if flags & 0x0020 == 0 {
+            break;
+        }
+    }
+    Ok(positions)
+}
+
+#[inline]
+fn read(glyph: &[u8], position: usize) -> u16 {
+    u16::from_be_bytes([glyph[position], glyph[position + 1]])
+}
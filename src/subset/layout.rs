@@ -0,0 +1,694 @@
+use truetype::GlyphID;
+
+use crate::glyph_definition::{Caret, Caret1, GlyphDefinition};
+use crate::glyph_positioning as positioning;
+use crate::glyph_substitution as substitution;
+use crate::layout::lookup::{Lookups, Record};
+use crate::layout::Directory;
+use crate::layout::{
+    Class, Class2, ClassRange, Correction, Coverage, Coverage1, Coverage2, CoverageRange,
+};
+
+use super::Mapping;
+
+/// A table that can be pruned to the retained glyphs.
+pub trait Prune: Sized {
+    /// Prune the table and return it unless it has become empty.
+    fn prune(&self, mapping: &Mapping) -> Option<Self>;
+}
+
+impl<T: Prune> Prune for Directory<T> {
+    fn prune(&self, mapping: &Mapping) -> Option<Self> {
+        let records = self
+            .lookups
+            .records
+            .iter()
+            .map(|record| Record {
+                kind: record.kind,
+                flags: record.flags,
+                table_count: record.table_count,
+                table_offsets: record.table_offsets.clone(),
+                mark_filtering_set: record.mark_filtering_set,
+                tables: record
+                    .tables
+                    .iter()
+                    .filter_map(|table| table.prune(mapping))
+                    .collect(),
+            })
+            .collect();
+        Some(Directory {
+            major_version: self.major_version,
+            minor_version: self.minor_version,
+            scripts_offset: self.scripts_offset,
+            features_offset: self.features_offset,
+            lookups_offset: self.lookups_offset,
+            variations_offset: self.variations_offset,
+            scripts: self.scripts.clone(),
+            features: self.features.clone(),
+            lookups: Lookups {
+                count: self.lookups.count,
+                offsets: self.lookups.offsets.clone(),
+                records: records,
+            },
+            variations: self.variations.clone(),
+        })
+    }
+}
+
+impl Prune for GlyphDefinition {
+    fn prune(&self, mapping: &Mapping) -> Option<Self> {
+        let mut table = self.clone();
+        table.glyph_class = self.glyph_class.as_ref().map(|value| class(value, mapping));
+        table.attachments = self.attachments.as_ref().and_then(|value| {
+            let (coverage, records) = cover(&value.coverage, mapping, |_, i| {
+                value.records.get(i).cloned()
+            })?;
+            let mut value = value.clone();
+            value.coverage = coverage;
+            value.records = records;
+            Some(value)
+        });
+        table.ligatures = self.ligatures.as_ref().and_then(|value| {
+            let (coverage, records) = cover(&value.coverage, mapping, |_, i| {
+                value.records.get(i).cloned()
+            })?;
+            let mut value = value.clone();
+            value.coverage = coverage;
+            value.records = records;
+            Some(value)
+        });
+        table.mark_class = self.mark_class.as_ref().map(|value| class(value, mapping));
+        if let Some(ref mut value) = table.marks {
+            for coverage in &mut value.coverages {
+                *coverage = prune(coverage, mapping).unwrap_or_default();
+            }
+        }
+        Some(table)
+    }
+}
+
+impl Prune for substitution::Table {
+    fn prune(&self, mapping: &Mapping) -> Option<Self> {
+        use substitution::Table;
+
+        Some(match *self {
+            Table::SingleSubstitution(ref table) => {
+                Table::SingleSubstitution(table.prune(mapping)?)
+            }
+            Table::MultipleSubstitution(ref table) => {
+                let (coverage, sequences) = cover(&table.coverage, mapping, |_, i| {
+                    let mut value = table.sequences.get(i)?.clone();
+                    value.glyph_ids = glyphs(&value.glyph_ids, mapping)?;
+                    Some(value)
+                })?;
+                let mut table = table.clone();
+                table.coverage = coverage;
+                table.sequences = sequences;
+                Table::MultipleSubstitution(table)
+            }
+            Table::AlternateSubstitution(ref table) => {
+                let (coverage, sets) = cover(&table.coverage, mapping, |_, i| {
+                    let mut value = table.sets.get(i)?.clone();
+                    value.glyph_ids = value
+                        .glyph_ids
+                        .iter()
+                        .filter_map(|&glyph_id| mapping.get(glyph_id))
+                        .collect();
+                    Some(value).filter(|value| !value.glyph_ids.is_empty())
+                })?;
+                let mut table = table.clone();
+                table.coverage = coverage;
+                table.sets = sets;
+                Table::AlternateSubstitution(table)
+            }
+            Table::LigatureSubstitution(ref table) => {
+                let (coverage, sets) = cover(&table.coverage, mapping, |_, i| {
+                    let mut value = table.sets.get(i)?.clone();
+                    value.records = value
+                        .records
+                        .iter()
+                        .filter_map(|record| {
+                            let mut record = record.clone();
+                            record.glyph_id = mapping.get(record.glyph_id)?;
+                            record.component_ids = glyphs(&record.component_ids, mapping)?;
+                            Some(record)
+                        })
+                        .collect();
+                    Some(value).filter(|value| !value.records.is_empty())
+                })?;
+                let mut table = table.clone();
+                table.coverage = coverage;
+                table.sets = sets;
+                Table::LigatureSubstitution(table)
+            }
+            Table::ContextSubstitution(ref table) => {
+                Table::ContextSubstitution(table.prune(mapping)?)
+            }
+            Table::ChainContextSubstitution(ref table) => {
+                Table::ChainContextSubstitution(table.prune(mapping)?)
+            }
+            Table::ExtensionSubstitution(ref table) => {
+                Table::ExtensionSubstitution(substitution::ExtensionSubstitution {
+                    format: table.format,
+                    kind: table.kind,
+                    offset: table.offset,
+                    table: Box::new(table.table.prune(mapping)?),
+                })
+            }
+            Table::ReverseChainContextSubstitution(ref table) => {
+                let (coverage, glyph_ids) = cover(&table.coverage, mapping, |_, i| {
+                    mapping.get(*table.glyph_ids.get(i)?)
+                })?;
+                let mut table = table.clone();
+                table.coverage = coverage;
+                table.glyph_ids = glyph_ids;
+                table.backward_coverages = prune_all(&table.backward_coverages, mapping)?;
+                table.forward_coverages = prune_all(&table.forward_coverages, mapping)?;
+                Table::ReverseChainContextSubstitution(table)
+            }
+            Table::Unknown(_) => return None,
+        })
+    }
+}
+
+impl Prune for substitution::SingleSubstitution {
+    fn prune(&self, mapping: &Mapping) -> Option<Self> {
+        use substitution::{SingleSubstitution, SingleSubstitution2};
+
+        let (coverage, glyph_ids) = match *self {
+            SingleSubstitution::Format1(ref table) => {
+                cover(&table.coverage, mapping, |glyph_id, _| {
+                    mapping.get((glyph_id as i32 + table.delta_glyph_id as i32) as GlyphID)
+                })?
+            }
+            SingleSubstitution::Format2(ref table) => cover(&table.coverage, mapping, |_, i| {
+                mapping.get(*table.glyph_ids.get(i)?)
+            })?,
+        };
+        Some(SingleSubstitution::Format2(SingleSubstitution2 {
+            format: 2,
+            coverage_offset: 0,
+            glyph_count: glyph_ids.len() as u16,
+            glyph_ids: glyph_ids,
+            coverage: coverage,
+        }))
+    }
+}
+
+impl Prune for positioning::Table {
+    fn prune(&self, mapping: &Mapping) -> Option<Self> {
+        use positioning::{PairAdjustment, SingleAdjustment, Table};
+
+        Some(match *self {
+            Table::SingleAdjustment(SingleAdjustment::Format1(ref table)) => {
+                let (coverage, _) = cover(&table.coverage, mapping, |_, _| Some(()))?;
+                let mut table = table.clone();
+                table.coverage = coverage;
+                Table::SingleAdjustment(SingleAdjustment::Format1(table))
+            }
+            Table::SingleAdjustment(SingleAdjustment::Format2(ref table)) => {
+                let (coverage, values) = cover(&table.coverage, mapping, |_, i| {
+                    table.values.get(i).cloned()
+                })?;
+                let mut table = table.clone();
+                table.coverage = coverage;
+                table.values = values;
+                Table::SingleAdjustment(SingleAdjustment::Format2(table))
+            }
+            Table::PairAdjustment(PairAdjustment::Format1(ref table)) => {
+                let (coverage, sets) = cover(&table.coverage, mapping, |_, i| {
+                    let mut value = table.sets.get(i)?.clone();
+                    value.records = value
+                        .records
+                        .iter()
+                        .filter_map(|record| {
+                            let mut record = record.clone();
+                            record.glyph2_id = mapping.get(record.glyph2_id)?;
+                            Some(record)
+                        })
+                        .collect();
+                    Some(value).filter(|value| !value.records.is_empty())
+                })?;
+                let mut table = table.clone();
+                table.coverage = coverage;
+                table.sets = sets;
+                Table::PairAdjustment(PairAdjustment::Format1(table))
+            }
+            Table::PairAdjustment(PairAdjustment::Format2(ref table)) => {
+                let (coverage, glyph_ids) =
+                    cover(&table.coverage, mapping, |glyph_id, _| Some(glyph_id))?;
+                let mut classes1 = glyph_ids
+                    .iter()
+                    .map(|&glyph_id| table.class1.get(glyph_id))
+                    .collect::<Vec<_>>();
+                let mut classes2 = mapping
+                    .iter()
+                    .map(|glyph_id| table.class2.get(glyph_id))
+                    .collect::<Vec<_>>();
+                for classes in [&mut classes1, &mut classes2] {
+                    classes.push(0);
+                    classes.sort_unstable();
+                    classes.dedup();
+                }
+                let mut table = table.clone();
+                table.coverage = coverage;
+                table.class1 = reclass(&table.class1, mapping, &classes1);
+                table.class2 = reclass(&table.class2, mapping, &classes2);
+                table.sets = classes1
+                    .iter()
+                    .filter_map(|&index| table.sets.get(index as usize))
+                    .map(|set| {
+                        let mut set = set.clone();
+                        set.records = classes2
+                            .iter()
+                            .filter_map(|&index| set.records.get(index as usize).cloned())
+                            .collect();
+                        set
+                    })
+                    .collect();
+                Table::PairAdjustment(PairAdjustment::Format2(table))
+            }
+            Table::CursiveAttachment(ref table) => {
+                let (coverage, passages) = cover(&table.coverage, mapping, |_, i| {
+                    table.passages.get(i).cloned()
+                })?;
+                let mut table = table.clone();
+                table.coverage = coverage;
+                table.passages = passages;
+                Table::CursiveAttachment(table)
+            }
+            Table::MarkToBaseAttachment(ref table) => {
+                let (mark_coverage, marks) = cover(&table.mark_coverage, mapping, |_, i| {
+                    table.marks.records.get(i).cloned()
+                })?;
+                let (base_coverage, bases) = cover(&table.base_coverage, mapping, |_, i| {
+                    table.bases.records.get(i).cloned()
+                })?;
+                let mut table = table.clone();
+                table.mark_coverage = mark_coverage;
+                table.marks.records = marks;
+                table.base_coverage = base_coverage;
+                table.bases.records = bases;
+                Table::MarkToBaseAttachment(table)
+            }
+            Table::MarkToLigatureAttachment(ref table) => {
+                let (mark_coverage, marks) = cover(&table.mark_coverage, mapping, |_, i| {
+                    table.marks.records.get(i).cloned()
+                })?;
+                let (ligature_coverage, ligatures) =
+                    cover(&table.ligature_coverage, mapping, |_, i| {
+                        table.ligatures.records.get(i).cloned()
+                    })?;
+                let mut table = table.clone();
+                table.mark_coverage = mark_coverage;
+                table.marks.records = marks;
+                table.ligature_coverage = ligature_coverage;
+                table.ligatures.records = ligatures;
+                Table::MarkToLigatureAttachment(table)
+            }
+            Table::MarkToMarkAttachment(ref table) => {
+                let (mark1_coverage, mark1s) = cover(&table.mark1_coverage, mapping, |_, i| {
+                    table.mark1s.records.get(i).cloned()
+                })?;
+                let (mark2_coverage, mark2s) = cover(&table.mark2_coverage, mapping, |_, i| {
+                    table.mark2s.records.get(i).cloned()
+                })?;
+                let mut table = table.clone();
+                table.mark1_coverage = mark1_coverage;
+                table.mark1s.records = mark1s;
+                table.mark2_coverage = mark2_coverage;
+                table.mark2s.records = mark2s;
+                Table::MarkToMarkAttachment(table)
+            }
+            Table::ContextPositioning(ref table) => {
+                Table::ContextPositioning(table.prune(mapping)?)
+            }
+            Table::ChainContextPositioning(ref table) => {
+                Table::ChainContextPositioning(table.prune(mapping)?)
+            }
+            Table::ExtensionPositioning(ref table) => {
+                Table::ExtensionPositioning(positioning::ExtensionPositioning {
+                    format: table.format,
+                    kind: table.kind,
+                    offset: table.offset,
+                    table: Box::new(table.table.prune(mapping)?),
+                })
+            }
+            Table::Unknown(_) => return None,
+        })
+    }
+}
+
+macro_rules! implement {
+    ($($module:ident::{$context:ident, $chain_context:ident}),*) => ($(
+        impl Prune for $module::$context {
+            fn prune(&self, mapping: &Mapping) -> Option<Self> {
+                Some(match *self {
+                    $module::$context::Format1(ref table) => {
+                        let (coverage, sets) = cover(&table.coverage, mapping, |_, i| {
+                            table.sets.get(i)?.prune(mapping)
+                        })?;
+                        let mut table = table.clone();
+                        table.coverage = coverage;
+                        table.sets = sets;
+                        $module::$context::Format1(table)
+                    }
+                    $module::$context::Format2(ref table) => {
+                        let (coverage, _) = cover(&table.coverage, mapping, |_, _| Some(()))?;
+                        let mut table = table.clone();
+                        table.coverage = coverage;
+                        table.class = class(&table.class, mapping);
+                        $module::$context::Format2(table)
+                    }
+                    $module::$context::Format3(ref table) => {
+                        let mut table = table.clone();
+                        table.coverages = prune_all(&table.coverages, mapping)?;
+                        $module::$context::Format3(table)
+                    }
+                })
+            }
+        }
+
+        impl Prune for $module::$chain_context {
+            fn prune(&self, mapping: &Mapping) -> Option<Self> {
+                Some(match *self {
+                    $module::$chain_context::Format1(ref table) => {
+                        let (coverage, sets) = cover(&table.coverage, mapping, |_, i| {
+                            table.sets.get(i)?.prune(mapping)
+                        })?;
+                        let mut table = table.clone();
+                        table.coverage = coverage;
+                        table.sets = sets;
+                        $module::$chain_context::Format1(table)
+                    }
+                    $module::$chain_context::Format2(ref table) => {
+                        let (coverage, _) = cover(&table.coverage, mapping, |_, _| Some(()))?;
+                        let mut table = table.clone();
+                        table.coverage = coverage;
+                        table.backward_class = class(&table.backward_class, mapping);
+                        table.input_class = class(&table.input_class, mapping);
+                        table.forward_class = class(&table.forward_class, mapping);
+                        $module::$chain_context::Format2(table)
+                    }
+                    $module::$chain_context::Format3(ref table) => {
+                        let mut table = table.clone();
+                        table.backward_coverages = prune_all(&table.backward_coverages, mapping)?;
+                        table.input_coverages = prune_all(&table.input_coverages, mapping)?;
+                        table.forward_coverages = prune_all(&table.forward_coverages, mapping)?;
+                        $module::$chain_context::Format3(table)
+                    }
+                })
+            }
+        }
+
+        impl Prune for $module::Rules {
+            fn prune(&self, mapping: &Mapping) -> Option<Self> {
+                let mut value = self.clone();
+                value.records = self
+                    .records
+                    .iter()
+                    .filter_map(|record| {
+                        let mut record = record.clone();
+                        record.input_glyph_ids = glyphs(&record.input_glyph_ids, mapping)?;
+                        Some(record)
+                    })
+                    .collect();
+                Some(value).filter(|value| !value.records.is_empty())
+            }
+        }
+
+        impl Prune for $module::ChainRules {
+            fn prune(&self, mapping: &Mapping) -> Option<Self> {
+                let mut value = self.clone();
+                value.records = self
+                    .records
+                    .iter()
+                    .filter_map(|record| {
+                        let mut record = record.clone();
+                        record.backward_glyph_ids = glyphs(&record.backward_glyph_ids, mapping)?;
+                        record.input_glyph_ids = glyphs(&record.input_glyph_ids, mapping)?;
+                        record.forward_glyph_ids = glyphs(&record.forward_glyph_ids, mapping)?;
+                        Some(record)
+                    })
+                    .collect();
+                Some(value).filter(|value| !value.records.is_empty())
+            }
+        }
+    )*);
+}
+
+implement! {
+    substitution::{ContextSubstitution, ChainContextSubstitution},
+    positioning::{ContextPositioning, ChainContextPositioning}
+}
+
+/// A table that can be reduced to the default instance of a variable font.
+pub trait Instance {
+    /// Drop the data that refer to the variations of the font.
+    fn instance(&mut self);
+}
+
+impl<T: Instance> Instance for Directory<T> {
+    fn instance(&mut self) {
+        self.variations_offset = 0;
+        self.variations = None;
+        for record in &mut self.lookups.records {
+            record.tables.iter_mut().for_each(Instance::instance);
+        }
+    }
+}
+
+impl Instance for GlyphDefinition {
+    fn instance(&mut self) {
+        self.variations = None;
+        let ligatures = self
+            .ligatures
+            .iter_mut()
+            .flat_map(|value| &mut value.records);
+        for caret in ligatures.flat_map(|ligature| &mut ligature.carets) {
+            if let Caret::Format3(ref value) = *caret {
+                if let Correction::Variation(_) = value.correction {
+                    *caret = Caret::Format1(Caret1 {
+                        format: 1,
+                        coordinate: value.coordinate,
+                    });
+                }
+            }
+        }
+    }
+}
+
+impl Instance for substitution::Table {
+    #[inline]
+    fn instance(&mut self) {}
+}
+
+impl Instance for positioning::Table {
+    fn instance(&mut self) {
+        use positioning::{PairAdjustment, SingleAdjustment, Table};
+
+        match *self {
+            Table::SingleAdjustment(SingleAdjustment::Format1(ref mut table)) => {
+                instance_single(&mut table.value);
+            }
+            Table::SingleAdjustment(SingleAdjustment::Format2(ref mut table)) => {
+                table.values.iter_mut().for_each(instance_single);
+            }
+            Table::PairAdjustment(PairAdjustment::Format1(ref mut table)) => {
+                for record in table.sets.iter_mut().flat_map(|set| &mut set.records) {
+                    instance_single(&mut record.value1);
+                    instance_single(&mut record.value2);
+                }
+            }
+            Table::PairAdjustment(PairAdjustment::Format2(ref mut table)) => {
+                for record in table.sets.iter_mut().flat_map(|set| &mut set.records) {
+                    instance_single(&mut record.value1);
+                    instance_single(&mut record.value2);
+                }
+            }
+            Table::CursiveAttachment(ref mut table) => {
+                for passage in &mut table.passages {
+                    passage.entry.iter_mut().for_each(instance_anchor);
+                    passage.exit.iter_mut().for_each(instance_anchor);
+                }
+            }
+            Table::MarkToBaseAttachment(ref mut table) => {
+                for mark in &mut table.marks.records {
+                    instance_anchor(&mut mark.anchor);
+                }
+                for base in &mut table.bases.records {
+                    base.anchors.iter_mut().flatten().for_each(instance_anchor);
+                }
+            }
+            Table::MarkToLigatureAttachment(ref mut table) => {
+                for mark in &mut table.marks.records {
+                    instance_anchor(&mut mark.anchor);
+                }
+                let ligatures = table.ligatures.records.iter_mut();
+                for component in ligatures.flat_map(|ligature| &mut ligature.components) {
+                    component
+                        .anchors
+                        .iter_mut()
+                        .flatten()
+                        .for_each(instance_anchor);
+                }
+            }
+            Table::MarkToMarkAttachment(ref mut table) => {
+                for mark in &mut table.mark1s.records {
+                    instance_anchor(&mut mark.anchor);
+                }
+                for mark in &mut table.mark2s.records {
+                    mark.anchors.iter_mut().flatten().for_each(instance_anchor);
+                }
+            }
+            Table::ExtensionPositioning(ref mut table) => table.table.instance(),
+            Table::ContextPositioning(_)
+            | Table::ChainContextPositioning(_)
+            | Table::Unknown(_) => {}
+        }
+    }
+}
+
+fn instance_anchor(anchor: &mut positioning::Anchor) {
+    if let positioning::Anchor::Format3(ref mut anchor) = *anchor {
+        for correction in [&mut anchor.x_correction, &mut anchor.y_correction] {
+            if let Some(Correction::Variation(_)) = *correction {
+                *correction = None;
+            }
+        }
+    }
+}
+
+fn instance_single(value: &mut positioning::Single) {
+    macro_rules! clear(
+        ($($offset:ident, $correction:ident,)*) => ($(
+            if let Some(Correction::Variation(_)) = value.$correction {
+                value.$offset = None;
+                value.$correction = None;
+            }
+        )*);
+    );
+    clear!(
+        x_placement_correction_offset,
+        x_placement_correction,
+        y_placement_correction_offset,
+        y_placement_correction,
+        x_advance_correction_offset,
+        x_advance_correction,
+        y_advance_correction_offset,
+        y_advance_correction,
+    );
+}
+
+/// Prune a coverage table and the values associated with the covered glyphs.
+///
+/// A glyph is dropped if it is not retained or its value is absent. If no
+/// glyph is left, nothing is returned.
+fn cover<T, F>(coverage: &Coverage, mapping: &Mapping, mut value: F) -> Option<(Coverage, Vec<T>)>
+where
+    F: FnMut(GlyphID, usize) -> Option<T>,
+{
+    let mut entries = coverage
+        .iter()
+        .filter_map(|(glyph_id, index)| {
+            Some((mapping.get(glyph_id)?, value(glyph_id, index as usize)?))
+        })
+        .collect::<Vec<_>>();
+    entries.sort_by_key(|&(glyph_id, _)| glyph_id);
+    entries.dedup_by_key(|&mut (glyph_id, _)| glyph_id);
+    if entries.is_empty() {
+        return None;
+    }
+    let (glyph_ids, values) = entries.into_iter().unzip();
+    Some((create(glyph_ids), values))
+}
+
+/// Create a coverage table in the more compact format.
+fn create(glyph_ids: Vec<GlyphID>) -> Coverage {
+    let mut ranges: Vec<CoverageRange> = vec![];
+    for (index, &glyph_id) in glyph_ids.iter().enumerate() {
+        match ranges.last_mut() {
+            Some(range) if range.end + 1 == glyph_id => range.end = glyph_id,
+            _ => ranges.push(CoverageRange {
+                start: glyph_id,
+                end: glyph_id,
+                index: index as u16,
+            }),
+        }
+    }
+    if 3 * ranges.len() < glyph_ids.len() {
+        Coverage::Format2(Coverage2 {
+            format: 2,
+            count: ranges.len() as u16,
+            ranges: ranges,
+        })
+    } else {
+        Coverage::Format1(Coverage1 {
+            format: 1,
+            count: glyph_ids.len() as u16,
+            glyph_ids: glyph_ids,
+        })
+    }
+}
+
+fn prune(coverage: &Coverage, mapping: &Mapping) -> Option<Coverage> {
+    cover(coverage, mapping, |_, _| Some(())).map(|(coverage, _)| coverage)
+}
+
+fn prune_all(coverages: &[Coverage], mapping: &Mapping) -> Option<Vec<Coverage>> {
+    coverages
+        .iter()
+        .map(|coverage| prune(coverage, mapping))
+        .collect()
+}
+
+fn class(class: &Class, mapping: &Mapping) -> Class {
+    let entries = class
+        .iter()
+        .filter_map(|(glyph_id, index)| Some((mapping.get(glyph_id)?, index)))
+        .collect();
+    define(entries)
+}
+
+/// Prune a class definition and renumber the classes given the retained ones
+/// in order.
+fn reclass(class: &Class, mapping: &Mapping, classes: &[u16]) -> Class {
+    let entries = class
+        .iter()
+        .filter_map(|(glyph_id, index)| {
+            let index = classes.binary_search(&index).ok()? as u16;
+            Some((mapping.get(glyph_id)?, index))
+        })
+        .collect();
+    define(entries)
+}
+
+fn define(mut entries: Vec<(GlyphID, u16)>) -> Class {
+    entries.retain(|&(_, index)| index != 0);
+    entries.sort_by_key(|&(glyph_id, _)| glyph_id);
+    let mut ranges: Vec<ClassRange> = vec![];
+    for (glyph_id, index) in entries {
+        match ranges.last_mut() {
+            Some(range) if range.end + 1 == glyph_id && range.index == index => {
+                range.end = glyph_id
+            }
+            _ => ranges.push(ClassRange {
+                start: glyph_id,
+                end: glyph_id,
+                index: index,
+            }),
+        }
+    }
+    Class::Format2(Class2 {
+        format: 2,
+        range_count: ranges.len() as u16,
+        ranges: ranges,
+    })
+}
+
+fn glyphs(glyph_ids: &[GlyphID], mapping: &Mapping) -> Option<Vec<GlyphID>> {
+    glyph_ids
+        .iter()
+        .map(|&glyph_id| mapping.get(glyph_id))
+        .collect()
+}
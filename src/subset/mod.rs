@@ -0,0 +1,275 @@
+//! The subsetter.
+//!
+//! A font is subset by retaining a set of glyphs together with the glyphs that
+//! they can be substituted with via the glyph-substitution table and the
+//! glyphs that they are composed of. The retained glyphs are renumbered
+//! preserving their order, and the tables referring to glyphs are pruned
+//! accordingly. Tables that refer to glyphs but are not supported are dropped.
+
+use std::collections::BTreeSet;
+use std::io::{Read, Seek};
+use truetype::{
+    CharMapping, GlyphID, GlyphMapping, HorizontalHeader, HorizontalMetrics, MaximumProfile,
+    Result, Tag,
+};
+
+use crate::glyph_substitution::GlyphSubstitution;
+use crate::{Builder, Font, GlyphDefinition, GlyphPositioning};
+
+mod char_mapping;
+mod font_set;
+mod glyph_data;
+mod layout;
+
+use layout::{Instance, Prune};
+
+/// The tables that do not refer to glyphs and are copied as is.
+const INDEPENDENT_TABLES: [&[u8; 4]; 9] = [
+    b"BASE", b"OS/2", b"STAT", b"cvt ", b"fpgm", b"gasp", b"meta", b"name", b"prep",
+];
+
+/// Subset a font given the code points and the glyphs to retain.
+///
+/// The notdef glyph is always retained. The character mapping retains the
+/// given code points and the code points of the given glyphs. Subroutines of
+/// the compact font format are retained as is. Variable fonts with TrueType
+/// outlines are reduced to their default instances: the variation tables and
+/// the style attributes are dropped, and so are the feature variations and
+/// the variation data of the layout tables. Fonts with outlines in the compact
+/// font format 2 are not supported.
+pub fn subset<T>(
+    font: &Font,
+    tape: &mut T,
+    code_points: &[u32],
+    glyph_ids: &[GlyphID],
+) -> Result<Vec<u8>>
+where
+    T: Read + Seek,
+{
+    macro_rules! take(
+        (@bytes $tag:expr) => (match font.take_bytes(tape, Tag(*$tag))? {
+            Some(data) => data,
+            _ => raise!("found no required font table"),
+        });
+        ($kind:ty) => (match font.take::<_, $kind>(tape)? {
+            Some(table) => table,
+            _ => raise!("found no required font table"),
+        });
+    );
+    let has = |tag: &[u8; 4]| {
+        font.offset_table
+            .records
+            .iter()
+            .any(|record| record.tag == Tag(*tag))
+    };
+    if has(b"CFF2") {
+        raise!("found an unsupported outline format");
+    }
+    let variable = has(b"fvar");
+    let profile = take!(MaximumProfile);
+    let glyph_count = profile.glyph_count();
+    let horizontal_header = take!(HorizontalHeader);
+    let horizontal_metrics: HorizontalMetrics =
+        match font.take_given(tape, (&horizontal_header, &profile))? {
+            Some(table) => table,
+            _ => raise!("found no required font table"),
+        };
+    let characters = char_mapping::read(&take!(CharMapping));
+    let substitution = font.take::<_, GlyphSubstitution>(tape)?;
+    let glyph_data = match font.take_bytes(tape, Tag(*b"glyf"))? {
+        Some(data) => {
+            let font_header = take!(truetype::FontHeader);
+            let mapping: GlyphMapping = match font.take_given(tape, (&font_header, &profile))? {
+                Some(table) => table,
+                _ => raise!("found no required font table"),
+            };
+            Some(glyph_data::Glyphs::new(data, &mapping)?)
+        }
+        _ => None,
+    };
+
+    let mut retained = BTreeSet::new();
+    retained.insert(0);
+    retained.extend(glyph_ids.iter().cloned());
+    retained.extend(
+        code_points
+            .iter()
+            .filter_map(|code_point| characters.get(code_point).cloned()),
+    );
+    if let Some(ref table) = substitution {
+        // Feature variations exist only in variable fonts, which are reduced to
+        // their default instances, and hence their lookups are not followed.
+        let lookup_indices = table
+            .features
+            .records
+            .iter()
+            .flat_map(|record| record.lookup_indices.iter().cloned())
+            .collect::<Vec<_>>();
+        table.close(&mut retained, &lookup_indices);
+    }
+    if let Some(ref glyph_data) = glyph_data {
+        glyph_data.close(&mut retained)?;
+    }
+    retained.retain(|&glyph_id| (glyph_id as usize) < glyph_count);
+    let mapping = Mapping::new(&retained, glyph_count);
+    let code_points = code_points.iter().cloned().collect::<BTreeSet<_>>();
+    let glyph_ids = glyph_ids.iter().cloned().collect::<BTreeSet<_>>();
+    let characters = characters
+        .into_iter()
+        .filter(|(code_point, glyph_id)| {
+            code_points.contains(code_point) || glyph_ids.contains(glyph_id)
+        })
+        .filter_map(|(code_point, glyph_id)| Some((code_point, mapping.get(glyph_id)?)))
+        .collect();
+
+    let mut builder = Builder::new(font.offset_table.header.version);
+    for record in &font.offset_table.records {
+        let tag = record.tag;
+        match &*tag {
+            b"cmap" => {
+                builder.add(tag, char_mapping::write(&characters));
+            }
+            b"glyf" => {
+                if let Some(ref glyph_data) = glyph_data {
+                    let (data, offsets, format) = glyph_data.subset(&mapping)?;
+                    let mut font_header = take!(@bytes b"head");
+                    if font_header.len() < 54 {
+                        raise!("found a malformed font header");
+                    }
+                    font_header[50..52].copy_from_slice(&format.to_be_bytes());
+                    builder.add(tag, data);
+                    builder.add(Tag(*b"loca"), offsets);
+                    builder.add(Tag(*b"head"), font_header);
+                }
+            }
+            b"head" if glyph_data.is_none() => {
+                builder.add(tag, take!(@bytes b"head"));
+            }
+            b"hmtx" => {
+                let (header, metrics) =
+                    subset_metrics(take!(@bytes b"hhea"), &horizontal_metrics, &mapping)?;
+                builder.add(Tag(*b"hhea"), header);
+                builder.add(Tag(*b"hmtx"), metrics);
+            }
+            b"maxp" => {
+                let mut data = take!(@bytes b"maxp");
+                if data.len() < 6 {
+                    raise!("found a malformed maximum profile");
+                }
+                data[4..6].copy_from_slice(&(mapping.len() as u16).to_be_bytes());
+                builder.add(tag, data);
+            }
+            b"post" => {
+                let mut data = take!(@bytes b"post");
+                if data.len() < 32 {
+                    raise!("found a malformed PostScript table");
+                }
+                data.truncate(32);
+                data[0..4].copy_from_slice(&0x00030000u32.to_be_bytes());
+                builder.add(tag, data);
+            }
+            b"CFF " => {
+                builder.add(tag, font_set::subset(&take!(@bytes b"CFF "), &mapping)?);
+            }
+            b"GDEF" => {
+                let table = font.take::<_, GlyphDefinition>(tape)?;
+                if let Some(mut table) = table.and_then(|table| table.prune(&mapping)) {
+                    if variable {
+                        table.instance();
+                    }
+                    builder.add_table(&table)?;
+                }
+            }
+            b"GPOS" => {
+                let table = font.take::<_, GlyphPositioning>(tape)?;
+                if let Some(mut table) = table.and_then(|table| table.prune(&mapping)) {
+                    if variable {
+                        table.instance();
+                    }
+                    builder.add_table(&table)?;
+                }
+            }
+            b"GSUB" => {
+                let table = substitution
+                    .as_ref()
+                    .and_then(|table| table.prune(&mapping));
+                if let Some(mut table) = table {
+                    if variable {
+                        table.instance();
+                    }
+                    builder.add_table(&table)?;
+                }
+            }
+            b"STAT" if variable => {}
+            value if INDEPENDENT_TABLES.contains(&value) => {
+                builder.add(tag, take!(@bytes value));
+            }
+            _ => {}
+        }
+    }
+    builder.build()
+}
+
+/// A mapping from the original glyphs to the retained ones.
+struct Mapping {
+    glyph_ids: Vec<Option<GlyphID>>,
+    retained: Vec<GlyphID>,
+}
+
+impl Mapping {
+    fn new(retained: &BTreeSet<GlyphID>, glyph_count: usize) -> Self {
+        let mut glyph_ids = vec![None; glyph_count];
+        for (i, &glyph_id) in retained.iter().enumerate() {
+            glyph_ids[glyph_id as usize] = Some(i as GlyphID);
+        }
+        Mapping {
+            glyph_ids: glyph_ids,
+            retained: retained.iter().cloned().collect(),
+        }
+    }
+
+    /// Return the new identifier of a glyph if the glyph is retained.
+    #[inline]
+    fn get(&self, glyph_id: GlyphID) -> Option<GlyphID> {
+        self.glyph_ids.get(glyph_id as usize).cloned().flatten()
+    }
+
+    /// Iterate over the original identifiers of the retained glyphs in order.
+    #[inline]
+    fn iter(&self) -> impl Iterator<Item = GlyphID> + '_ {
+        self.retained.iter().cloned()
+    }
+
+    /// Return the number of retained glyphs.
+    #[inline]
+    fn len(&self) -> usize {
+        self.retained.len()
+    }
+}
+
+fn subset_metrics(
+    mut header: Vec<u8>,
+    metrics: &HorizontalMetrics,
+    mapping: &Mapping,
+) -> Result<(Vec<u8>, Vec<u8>)> {
+    if header.len() < 36 {
+        raise!("found a malformed horizontal header");
+    }
+    let records = mapping
+        .iter()
+        .map(|glyph_id| metrics.get(glyph_id as usize))
+        .collect::<Vec<_>>();
+    let mut count = records.len();
+    while count > 1 && records[count - 2].0 == records[count - 1].0 {
+        count -= 1;
+    }
+    header[34..36].copy_from_slice(&(count as u16).to_be_bytes());
+    let mut data = Vec::with_capacity(4 * count + 2 * (records.len() - count));
+    for (i, &(advance_width, left_side_bearing)) in records.iter().enumerate() {
+        if i < count {
+            data.extend_from_slice(&advance_width.to_be_bytes());
+        }
+        data.extend_from_slice(&left_side_bearing.to_be_bytes());
+    }
+    Ok((header, data))
+}
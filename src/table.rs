@@ -7,7 +7,11 @@ use truetype::{
     MaximumProfile, NamingTable, PostScript, WindowsMetrics,
 };
 
-use crate::{GlyphDefinition, GlyphPositioning, GlyphSubstitution};
+use crate::compact2::FontSet as FontSet2;
+use crate::{
+    AxisVariations, FontVariations, GlyphDefinition, GlyphPositioning, GlyphSubstitution,
+    GlyphVariations,
+};
 
 /// A font table.
 pub trait Table<'l>: Sized {
@@ -21,12 +25,43 @@ pub trait Table<'l>: Sized {
     fn take<T>(tape: &mut T, parameter: Self::Parameter) -> Result<Self>
     where
         T: Read + Seek;
+
+    #[doc(hidden)]
+    #[inline]
+    fn take_lenient<T>(tape: &mut T, parameter: Self::Parameter) -> Result<Self>
+    where
+        T: Read + Seek,
+    {
+        Self::take(tape, parameter)
+    }
 }
 
 macro_rules! table {
     (@one $tag:expr => opentype::$kind:ident()) => (
         table! { @one $tag => truetype::$kind() }
     );
+    (@one $tag:expr => opentype::$kind:ident(lenient)) => (
+        impl Table<'static> for $kind {
+            type Parameter = ();
+
+            #[inline]
+            fn tag() -> Tag { Tag(*$tag) }
+
+            #[inline]
+            fn take<T>(tape: &mut T, _: Self::Parameter) -> Result<Self>
+                where T: Read + Seek
+            {
+                truetype::Tape::take(tape)
+            }
+
+            #[inline]
+            fn take_lenient<T>(tape: &mut T, _: Self::Parameter) -> Result<Self>
+                where T: Read + Seek
+            {
+                truetype::Tape::take_given(tape, true)
+            }
+        }
+    );
     (@one $tag:expr => $scope:ident::$kind:ident()) => (
         impl Table<'static> for $kind {
             type Parameter = ();
@@ -64,12 +99,16 @@ macro_rules! table {
 
 table! {
     b"CFF " => postscript::FontSet(),
+    b"CFF2" => opentype::FontSet2(),
     b"GDEF" => opentype::GlyphDefinition(),
-    b"GPOS" => opentype::GlyphPositioning(),
-    b"GSUB" => opentype::GlyphSubstitution(),
+    b"GPOS" => opentype::GlyphPositioning(lenient),
+    b"GSUB" => opentype::GlyphSubstitution(lenient),
     b"OS/2" => truetype::WindowsMetrics(),
+    b"avar" => opentype::AxisVariations(),
     b"cmap" => truetype::CharMapping(),
+    b"fvar" => opentype::FontVariations(),
     b"glyf" => truetype::GlyphData(..),
+    b"gvar" => opentype::GlyphVariations(),
     b"head" => truetype::FontHeader(),
     b"hhea" => truetype::HorizontalHeader(),
     b"hmtx" => truetype::HorizontalMetrics(..),
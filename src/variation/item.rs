@@ -1,5 +1,9 @@
 //! The item variation store.
 
+use truetype::{Result, Tape, Walue};
+
+use crate::writer::{Write, Writer};
+
 table! {
     @position
     #[doc = "An item variation store."]
@@ -12,6 +16,10 @@ table! {
             tape.take_given(this.count as usize)
         },
 
+        regions (Regions) |this, tape, position| {
+            jump_take!(tape, position, this.regions_offset)
+        },
+
         records (Vec<Record>) |this, tape, position| {
             jump_take!(tape, position, this.count, this.record_offsets)
         },
@@ -22,16 +30,209 @@ table! {
     #[doc = "A record of an item variation store."]
     pub Record { // ItemVariationData
         item_count        (u16), // itemCount
-        short_delta_count (u16), // shortDeltaCount
-        region_count      (u16), // regionCount
+        short_delta_count (u16), // wordDeltaCount
+        region_count      (u16), // regionIndexCount
 
-        region_indices (Vec<u16>) |this, tape| { // regionIndices
+        region_indices (Vec<u16>) |this, tape| { // regionIndexes
             tape.take_given(this.region_count as usize)
         },
 
-        deltas (Vec<u8>) |this, tape| { // deltaSets
-            let per_item_count = this.short_delta_count + this.region_count;
-            tape.take_given(this.item_count as usize * per_item_count as usize)
+        deltas (Vec<Vec<i32>>) |this, tape| { // deltaSets
+            let is_long = this.short_delta_count & 0x8000 != 0;
+            let long_count = (this.short_delta_count & 0x7FFF) as usize;
+            let count = this.region_count as usize;
+            if long_count > count {
+                raise!("found a malformed item variation store");
+            }
+            let mut values = Vec::with_capacity(this.item_count as usize);
+            for _ in 0..(this.item_count as usize) {
+                let mut deltas = Vec::with_capacity(count);
+                for i in 0..count {
+                    deltas.push(match (is_long, i < long_count) {
+                        (true, true) => tape.take::<u32>()? as i32,
+                        (false, true) | (true, false) => tape.take::<i16>()? as i32,
+                        (false, false) => tape.take::<i8>()? as i32,
+                    });
+                }
+                values.push(deltas);
+            }
+            Ok(values)
+        },
+    }
+}
+
+table! {
+    #[doc = "A list of variation regions."]
+    pub Regions { // VariationRegionList
+        axis_count (u16), // axisCount
+        count      (u16), // regionCount
+
+        records (Vec<Region>) |this, tape| { // variationRegions
+            let mut values = Vec::with_capacity(this.count as usize);
+            for _ in 0..(this.count as usize) {
+                values.push(tape.take_given(this.axis_count as usize)?);
+            }
+            Ok(values)
         },
     }
 }
+
+table! {
+    @define
+    #[doc = "A variation region."]
+    pub Region { // VariationRegion
+        axes (Vec<RegionAxis>), // regionAxes
+    }
+}
+
+table! {
+    #[doc = "The coordinates of a variation region along an axis."]
+    #[derive(Copy)]
+    pub RegionAxis { // RegionAxisCoordinates
+        start (i16), // startCoord
+        peak  (i16), // peakCoord
+        end   (i16), // endCoord
+    }
+}
+
+impl Variations {
+    /// Compute the delta of an item at normalized coordinates given in format
+    /// F2DOT14.
+    pub fn delta(&self, outer_index: u16, inner_index: u16, coordinates: &[i16]) -> Option<f32> {
+        let record = self.records.get(outer_index as usize)?;
+        let deltas = record.deltas.get(inner_index as usize)?;
+        let mut value = 0.0;
+        for (&index, &delta) in record.region_indices.iter().zip(deltas) {
+            let scalar = self
+                .regions
+                .records
+                .get(index as usize)?
+                .scalar(coordinates);
+            if scalar != 0.0 {
+                value += scalar * delta as f32;
+            }
+        }
+        Some(value)
+    }
+}
+
+impl Region {
+    /// Compute the scalar of the region at normalized coordinates given in
+    /// format F2DOT14.
+    ///
+    /// Axes without a coordinate are considered to be at the default location.
+    pub fn scalar(&self, coordinates: &[i16]) -> f32 {
+        let mut scalar = 1.0;
+        for (i, axis) in self.axes.iter().enumerate() {
+            let (start, peak, end) = (axis.start as i32, axis.peak as i32, axis.end as i32);
+            if start > peak || peak > end || start < 0 && end > 0 || peak == 0 {
+                continue;
+            }
+            let coordinate = coordinates.get(i).cloned().unwrap_or(0) as i32;
+            if coordinate < start || coordinate > end {
+                return 0.0;
+            }
+            if coordinate < peak {
+                scalar *= (coordinate - start) as f32 / (peak - start) as f32;
+            } else if coordinate > peak {
+                scalar *= (end - coordinate) as f32 / (end - peak) as f32;
+            }
+        }
+        scalar
+    }
+}
+
+impl Walue<'static> for Region {
+    type Parameter = usize;
+
+    fn read<T: Tape>(tape: &mut T, axis_count: usize) -> Result<Self> {
+        Ok(Region {
+            axes: tape.take_given(axis_count)?,
+        })
+    }
+}
+
+impl Write for Variations {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give(&1u16)?;
+        writer.link_long(&self.regions)?;
+        writer.give_count(self.records.len())?;
+        for record in &self.records {
+            writer.link_long(record)?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for Record {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        let count = self.region_indices.len();
+        if self.deltas.iter().any(|deltas| deltas.len() != count) {
+            raise!("found a malformed item variation store");
+        }
+        let widths = (0..count)
+            .map(|i| {
+                self.deltas
+                    .iter()
+                    .map(|deltas| match deltas[i] {
+                        -0x80..=0x7F => 1,
+                        -0x8000..=0x7FFF => 2,
+                        _ => 4,
+                    })
+                    .max()
+                    .unwrap_or(1)
+            })
+            .collect::<Vec<_>>();
+        let is_long = widths.contains(&4);
+        let wide = if is_long { 4 } else { 2 };
+        let mut order = (0..count).collect::<Vec<_>>();
+        order.sort_by_key(|&i| widths[i] != wide);
+        let long_count = widths.iter().filter(|&&width| width == wide).count();
+        writer.give_count(self.deltas.len())?;
+        writer.give(&(long_count as u16 | if is_long { 0x8000 } else { 0 }))?;
+        writer.give_count(count)?;
+        for &i in &order {
+            writer.give(&self.region_indices[i])?;
+        }
+        for deltas in &self.deltas {
+            for (j, &i) in order.iter().enumerate() {
+                match (is_long, j < long_count) {
+                    (true, true) => writer.give(&deltas[i])?,
+                    (false, true) | (true, false) => writer.give(&(deltas[i] as i16))?,
+                    (false, false) => writer.give(&(deltas[i] as i8))?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Write for Regions {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        let axis_count = match self.records.first() {
+            Some(record) => record.axes.len(),
+            _ => self.axis_count as usize,
+        };
+        if self
+            .records
+            .iter()
+            .any(|record| record.axes.len() != axis_count)
+        {
+            raise!("found a malformed variation region list");
+        }
+        writer.give_count(axis_count)?;
+        writer.give_count(self.records.len())?;
+        for record in &self.records {
+            writer.give(&record.axes)?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for RegionAxis {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give(&self.start)?;
+        writer.give(&self.peak)?;
+        writer.give(&self.end)
+    }
+}
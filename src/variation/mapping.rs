@@ -0,0 +1,58 @@
+//! The delta-set index map.
+
+use truetype::{Result, Tape, Value};
+
+table! {
+    @define
+    #[doc = "A delta-set index map."]
+    pub Mapping { // DeltaSetIndexMap
+        format       (u8            ), // format
+        entry_format (u8            ), // entryFormat
+        count        (u32           ), // mapCount
+        entries      (Vec<(u16, u16)>), // mapData
+    }
+}
+
+impl Mapping {
+    /// Return the outer and inner indices of an item in an item variation
+    /// store given the index of the item.
+    ///
+    /// Indices beyond the end of the map are mapped as the last index.
+    pub fn get(&self, index: usize) -> Option<(u16, u16)> {
+        self.entries
+            .get(index)
+            .or_else(|| self.entries.last())
+            .cloned()
+    }
+}
+
+impl Value for Mapping {
+    fn read<T: Tape>(tape: &mut T) -> Result<Self> {
+        let format = tape.take::<u8>()?;
+        let entry_format = tape.take::<u8>()?;
+        let count = match format {
+            0 => tape.take::<u16>()? as u32,
+            1 => tape.take::<u32>()?,
+            _ => raise!("found an unknown format of the delta-set index map"),
+        };
+        let size = ((entry_format & 0x30) >> 4) as usize + 1;
+        let inner_bit_count = (entry_format & 0x0F) as u32 + 1;
+        let mut entries = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let mut entry = 0u32;
+            for _ in 0..size {
+                entry = (entry << 8) | tape.take::<u8>()? as u32;
+            }
+            entries.push((
+                (entry >> inner_bit_count) as u16,
+                (entry & ((1 << inner_bit_count) - 1)) as u16,
+            ));
+        }
+        Ok(Mapping {
+            format: format,
+            entry_format: entry_format,
+            count: count,
+            entries: entries,
+        })
+    }
+}
@@ -3,3 +3,4 @@
 //! [1]: https://www.microsoft.com/typography/otspec/otvarcommonformats.htm
 
 pub mod item;
+pub mod mapping;
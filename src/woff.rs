@@ -0,0 +1,89 @@
+//! The [Web Open Font Format][1] of version 1.0.
+//!
+//! [1]: https://www.w3.org/TR/WOFF/
+
+use flate2::read::ZlibDecoder;
+use std::io::Read;
+use truetype::{Result, Tag, Tape};
+
+use crate::font::assemble;
+
+table! {
+    #[doc = "A table directory."]
+    pub Directory {
+        header (Header),
+
+        records (Vec<Record>) |this, tape| { // TableDirectory
+            tape.take_given(this.header.table_count as usize)
+        },
+    }
+}
+
+table! {
+    #[doc = "The header of a table directory."]
+    #[derive(Copy)]
+    pub Header { // WOFFHeader
+        signature                (Tag) = { Tag(*b"wOFF") }, // signature
+        flavor                   (u32), // flavor
+        length                   (u32), // length
+        table_count              (u16), // numTables
+        reserved                 (u16) = { 0 }, // reserved
+        sfnt_size                (u32), // totalSfntSize
+        major_version            (u16), // majorVersion
+        minor_version            (u16), // minorVersion
+        metadata_offset          (u32), // metaOffset
+        metadata_length          (u32), // metaLength
+        metadata_original_length (u32), // metaOrigLength
+        private_offset           (u32), // privOffset
+        private_length           (u32), // privLength
+    }
+}
+
+table! {
+    #[doc = "A record of a table directory."]
+    #[derive(Copy)]
+    pub Record { // TableDirectoryEntry
+        tag               (Tag), // tag
+        offset            (u32), // offset
+        compressed_length (u32), // compLength
+        original_length   (u32), // origLength
+        original_checksum (u32), // origChecksum
+    }
+}
+
+impl Directory {
+    /// Read and decompress the tables and assemble them into a font.
+    pub fn decompress<T: Tape>(&self, tape: &mut T) -> Result<Vec<u8>> {
+        let mut tables = Vec::with_capacity(self.records.len());
+        for record in &self.records {
+            tables.push((
+                record.tag,
+                record.original_checksum,
+                record.decompress(tape)?,
+            ));
+        }
+        assemble(self.header.flavor, &tables)
+    }
+}
+
+impl Record {
+    /// Read and decompress the table.
+    pub fn decompress<T: Tape>(&self, tape: &mut T) -> Result<Vec<u8>> {
+        tape.jump(self.offset as u64)?;
+        let data = tape.take_bytes(self.compressed_length as usize)?;
+        if self.compressed_length == self.original_length {
+            return Ok(data);
+        }
+        if self.compressed_length > self.original_length {
+            raise!("found a malformed table record");
+        }
+        let mut buffer = vec![];
+        ZlibDecoder::new(&data[..])
+            .take(self.original_length as u64 + 1)
+            .read_to_end(&mut buffer)?;
+        if buffer.len() != self.original_length as usize {
+            raise!("found a malformed compressed table");
+        }
+        Ok(buffer)
+    }
+}
@@ -0,0 +1,491 @@
+//! The [Web Open Font Format][1] of version 2.0.
+//!
+//! [1]: https://www.w3.org/TR/WOFF2/
+
+#![allow(unused_parens)]
+
+use brotli_decompressor::Decompressor;
+use std::io::{self, Cursor};
+use truetype::{Result, Tag, Tape, Value};
+
+use crate::font::{assemble, checksum};
+
+table! {
+    #[doc = "A table directory."]
+    pub Directory {
+        header (Header),
+
+        records (Vec<Record>) |this, tape| { // TableDirectory
+            if Tag::from(this.header.flavor) == Tag(*b"ttcf") {
+                raise!("found an unsupported font collection");
+            }
+            tape.take_given(this.header.table_count as usize)
+        },
+    }
+}
+
+table! {
+    #[doc = "The header of a table directory."]
+    #[derive(Copy)]
+    pub Header { // WOFF2Header
+        signature                (Tag) = { Tag(*b"wOF2") }, // signature
+        flavor                   (u32), // flavor
+        length                   (u32), // length
+        table_count              (u16), // numTables
+        reserved                 (u16) = { 0 }, // reserved
+        sfnt_size                (u32), // totalSfntSize
+        compressed_size          (u32), // totalCompressedSize
+        major_version            (u16), // majorVersion
+        minor_version            (u16), // minorVersion
+        metadata_offset          (u32), // metaOffset
+        metadata_length          (u32), // metaLength
+        metadata_original_length (u32), // metaOrigLength
+        private_offset           (u32), // privOffset
+        private_length           (u32), // privLength
+    }
+}
+
+/// A record of a table directory.
+#[derive(Clone, Copy, Debug, Default)]
+#[rustfmt::skip]
+pub struct Record { // TableDirectoryEntry
+    pub flags:            (u8         ), // flags
+    pub tag:              (Tag        ), // tag
+    pub original_length:  (u32        ), // origLength
+    pub transform_length: (Option<u32>), // transformLength
+}
+
+const TAGS: [&[u8; 4]; 63] = [
+    b"cmap", b"head", b"hhea", b"hmtx", b"maxp", b"name", b"OS/2", b"post", b"cvt ", b"fpgm",
+    b"glyf", b"loca", b"prep", b"CFF ", b"VORG", b"EBDT", b"EBLC", b"gasp", b"hdmx", b"kern",
+    b"LTSH", b"PCLT", b"VDMX", b"vhea", b"vmtx", b"BASE", b"GDEF", b"GPOS", b"GSUB", b"EBSC",
+    b"JSTF", b"MATH", b"CBDT", b"CBLC", b"COLR", b"CPAL", b"SVG ", b"sbix", b"acnt", b"avar",
+    b"bdat", b"bloc", b"bsln", b"cvar", b"fdsc", b"feat", b"fmtx", b"fvar", b"gvar", b"hsty",
+    b"just", b"lcar", b"mort", b"morx", b"opbd", b"prop", b"trak", b"Zapf", b"Silf", b"Glat",
+    b"Gloc", b"Feat", b"Sill",
+];
+
+impl Directory {
+    /// Read and decompress the tables and assemble them into a font.
+    ///
+    /// The tape is expected to be positioned right after the directory.
+    pub fn decompress<T: Tape>(&self, tape: &mut T) -> Result<Vec<u8>> {
+        let data = tape.take_bytes(self.header.compressed_size as usize)?;
+        let size = self
+            .records
+            .iter()
+            .map(|record| record.transform_length.unwrap_or(record.original_length) as u64)
+            .sum::<u64>();
+        let mut buffer = vec![];
+        let decompressor = Decompressor::new(&data[..], 4096);
+        io::Read::read_to_end(&mut io::Read::take(decompressor, size), &mut buffer)?;
+        let mut tables = Vec::with_capacity(self.records.len());
+        let mut offset = 0;
+        for record in &self.records {
+            let length = record.transform_length.unwrap_or(record.original_length) as usize;
+            if offset + length > buffer.len() {
+                raise!("found a malformed compressed stream");
+            }
+            tables.push((record.tag, buffer[offset..(offset + length)].to_vec()));
+            offset += length;
+        }
+        macro_rules! find(
+            ($tag:expr) => (tables.iter().position(|&(tag, _)| tag == Tag(*$tag)));
+        );
+        for (i, record) in self.records.iter().enumerate() {
+            if record.transform_length.is_none() {
+                continue;
+            }
+            match (&*record.tag, record.transform()) {
+                (b"glyf", 0) => {
+                    let j = match find!(b"loca") {
+                        Some(j) => j,
+                        _ => raise!("failed to find the glyph-to-location mapping"),
+                    };
+                    let (glyph_data, glyph_mapping) = reconstruct_glyphs(&tables[i].1)?;
+                    tables[i].1 = glyph_data;
+                    tables[j].1 = glyph_mapping;
+                }
+                (b"loca", 0) => {}
+                // The horizontal metrics are reconstructed once the glyph data
+                // have been.
+                (b"hmtx", 1) => {}
+                _ => raise!("found an unknown table transformation"),
+            }
+        }
+        for record in &self.records {
+            if record.transform_length.is_none() || &*record.tag != b"hmtx" {
+                continue;
+            }
+            let (head, hhea, maxp, glyf, loca, hmtx) = match (
+                find!(b"head"),
+                find!(b"hhea"),
+                find!(b"maxp"),
+                find!(b"glyf"),
+                find!(b"loca"),
+                find!(b"hmtx"),
+            ) {
+                (Some(a), Some(b), Some(c), Some(d), Some(e), Some(f)) => (a, b, c, d, e, f),
+                _ => raise!("failed to find the tables needed for horizontal metrics"),
+            };
+            let mapping_format = read::<i16>(&tables[head].1, 50)?;
+            let metric_count = read::<u16>(&tables[hhea].1, 34)? as usize;
+            let glyph_count = read::<u16>(&tables[maxp].1, 4)? as usize;
+            let min_x = minimal_x(
+                &tables[glyf].1,
+                &tables[loca].1,
+                mapping_format,
+                glyph_count,
+            )?;
+            tables[hmtx].1 =
+                reconstruct_metrics(&tables[hmtx].1, metric_count, glyph_count, &min_x)?;
+        }
+        let mut tables = tables
+            .into_iter()
+            .map(|(tag, table)| (tag, checksum(tag, &table), table))
+            .collect::<Vec<_>>();
+        tables.sort_by_key(|&(tag, _, _)| tag.0);
+        assemble(self.header.flavor, &tables)
+    }
+}
+
+impl Record {
+    /// Return the version of the transformation.
+    #[inline]
+    pub fn transform(&self) -> u8 {
+        self.flags >> 6
+    }
+}
+
+impl Value for Record {
+    fn read<T: Tape>(tape: &mut T) -> Result<Self> {
+        let flags = tape.take::<u8>()?;
+        let tag = match flags & 0x3F {
+            63 => tape.take()?,
+            index => Tag(*TAGS[index as usize]),
+        };
+        let original_length = read_base128(tape)?;
+        let is_transformed = match &*tag {
+            b"glyf" | b"loca" => flags >> 6 == 0,
+            _ => flags >> 6 != 0,
+        };
+        let transform_length = if is_transformed {
+            Some(read_base128(tape)?)
+        } else {
+            None
+        };
+        Ok(Record {
+            flags: flags,
+            tag: tag,
+            original_length: original_length,
+            transform_length: transform_length,
+        })
+    }
+}
+
+fn minimal_x(
+    glyph_data: &[u8],
+    glyph_mapping: &[u8],
+    mapping_format: i16,
+    glyph_count: usize,
+) -> Result<Vec<i16>> {
+    let mut values = Vec::with_capacity(glyph_count);
+    for i in 0..glyph_count {
+        let (start, end) = match mapping_format {
+            0 => (
+                2 * read::<u16>(glyph_mapping, 2 * i)? as usize,
+                2 * read::<u16>(glyph_mapping, 2 * i + 2)? as usize,
+            ),
+            _ => (
+                read::<u32>(glyph_mapping, 4 * i)? as usize,
+                read::<u32>(glyph_mapping, 4 * i + 4)? as usize,
+            ),
+        };
+        values.push(if start < end {
+            read::<i16>(glyph_data, start + 2)?
+        } else {
+            0
+        });
+    }
+    Ok(values)
+}
+
+fn read<T: Value>(data: &[u8], offset: usize) -> Result<T> {
+    let mut tape = Cursor::new(data);
+    tape.jump(offset as u64)?;
+    tape.take()
+}
+
+fn read_base128<T: Tape>(tape: &mut T) -> Result<u32> {
+    let mut value = 0u32;
+    for i in 0..5 {
+        let byte = tape.take::<u8>()?;
+        if i == 0 && byte == 0x80 || value & 0xFE00_0000 != 0 {
+            break;
+        }
+        value = (value << 7) | (byte & 0x7F) as u32;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    raise!("found a malformed base-128 number")
+}
+
+fn read_u255<T: Tape>(tape: &mut T) -> Result<u16> {
+    Ok(match tape.take::<u8>()? {
+        253 => tape.take::<u16>()?,
+        254 => tape.take::<u8>()? as u16 + 253 * 2,
+        255 => tape.take::<u8>()? as u16 + 253,
+        value => value as u16,
+    })
+}
+
+fn read_triplet<T: Tape>(tape: &mut T, flag: u8) -> Result<(i16, i16)> {
+    macro_rules! sign(
+        ($flag:expr, $value:expr) => (if $flag & 1 != 0 { $value } else { -$value });
+    );
+    let flag = flag & 0x7F;
+    let byte = |tape: &mut T| -> Result<i32> { Ok(tape.take::<u8>()? as i32) };
+    let (x, y) = if flag < 10 {
+        (0, sign!(flag, ((flag as i32 & 14) << 7) + byte(tape)?))
+    } else if flag < 20 {
+        (
+            sign!(flag, (((flag as i32 - 10) & 14) << 7) + byte(tape)?),
+            0,
+        )
+    } else if flag < 84 {
+        let b0 = flag as i32 - 20;
+        let b1 = byte(tape)?;
+        (
+            sign!(flag, 1 + (b0 & 0x30) + (b1 >> 4)),
+            sign!(flag >> 1, 1 + ((b0 & 0x0C) << 2) + (b1 & 0x0F)),
+        )
+    } else if flag < 120 {
+        let b0 = flag as i32 - 84;
+        (
+            sign!(flag, 1 + ((b0 / 12) << 8) + byte(tape)?),
+            sign!(flag >> 1, 1 + (((b0 % 12) >> 2) << 8) + byte(tape)?),
+        )
+    } else if flag < 124 {
+        let b0 = byte(tape)?;
+        let b1 = byte(tape)?;
+        let b2 = byte(tape)?;
+        (
+            sign!(flag, (b0 << 4) + (b1 >> 4)),
+            sign!(flag >> 1, ((b1 & 0x0F) << 8) + b2),
+        )
+    } else {
+        let x = (byte(tape)? << 8) + byte(tape)?;
+        let y = (byte(tape)? << 8) + byte(tape)?;
+        (sign!(flag, x), sign!(flag >> 1, y))
+    };
+    Ok((x as i16, y as i16))
+}
+
+fn reconstruct_glyphs(data: &[u8]) -> Result<(Vec<u8>, Vec<u8>)> {
+    macro_rules! push(
+        ($data:ident, $value:expr) => ($data.extend_from_slice(&$value.to_be_bytes()));
+    );
+    let mut tape = Cursor::new(data);
+    let _ = tape.take::<u16>()?;
+    let options = tape.take::<u16>()?;
+    let glyph_count = tape.take::<u16>()? as usize;
+    let mapping_format = tape.take::<u16>()?;
+    let mut offset = 36;
+    let mut streams = Vec::with_capacity(8);
+    for _ in 0..7 {
+        let size = tape.take::<u32>()? as usize;
+        if offset + size > data.len() {
+            raise!("found a malformed glyph transformation");
+        }
+        streams.push(Cursor::new(&data[offset..(offset + size)]));
+        offset += size;
+    }
+    let overlaps = if options & 1 != 0 {
+        let size = (glyph_count + 7) >> 3;
+        if offset + size > data.len() {
+            raise!("found a malformed glyph transformation");
+        }
+        &data[offset..(offset + size)]
+    } else {
+        &[][..]
+    };
+    let mut streams = streams.into_iter();
+    let mut contours = streams.next().unwrap();
+    let mut points = streams.next().unwrap();
+    let mut flags = streams.next().unwrap();
+    let mut glyphs = streams.next().unwrap();
+    let mut composites = streams.next().unwrap();
+    let mut boxes = streams.next().unwrap();
+    let mut instructions = streams.next().unwrap();
+    let box_flags = boxes.take_bytes(4 * ((glyph_count + 31) >> 5))?;
+
+    let mut glyph_data = vec![];
+    let mut offsets = Vec::with_capacity(glyph_count + 1);
+    for i in 0..glyph_count {
+        offsets.push(glyph_data.len() as u32);
+        let contour_count = contours.take::<i16>()?;
+        let has_box = box_flags[i >> 3] & (0x80 >> (i & 7)) != 0;
+        if contour_count == 0 {
+            if has_box {
+                raise!("found a malformed glyph transformation");
+            }
+            continue;
+        }
+        push!(glyph_data, contour_count);
+        if contour_count < 0 {
+            if !has_box {
+                raise!("found a malformed glyph transformation");
+            }
+            glyph_data.extend(boxes.take_bytes(8)?);
+            let mut has_instructions = false;
+            loop {
+                let flags = composites.take::<u16>()?;
+                let mut size = 2 + if flags & 0x0001 != 0 { 4 } else { 2 };
+                if flags & 0x0008 != 0 {
+                    size += 2;
+                } else if flags & 0x0040 != 0 {
+                    size += 4;
+                } else if flags & 0x0080 != 0 {
+                    size += 8;
+                }
+                push!(glyph_data, flags);
+                glyph_data.extend(composites.take_bytes(size)?);
+                has_instructions |= flags & 0x0100 != 0;
+                if flags & 0x0020 == 0 {
+                    break;
+                }
+            }
+            if has_instructions {
+                let size = read_u255(&mut glyphs)?;
+                push!(glyph_data, size);
+                glyph_data.extend(instructions.take_bytes(size as usize)?);
+            }
+        } else {
+            let mut end_points = Vec::with_capacity(contour_count as usize);
+            let mut point_count = 0usize;
+            for _ in 0..contour_count {
+                point_count += read_u255(&mut points)? as usize;
+                if point_count == 0 || point_count > 0x10000 {
+                    raise!("found a malformed glyph transformation");
+                }
+                end_points.push(point_count as u16 - 1);
+            }
+            let point_flags = flags.take_bytes(point_count)?;
+            let (mut x, mut y) = (0i16, 0i16);
+            let (mut min_x, mut min_y, mut max_x, mut max_y) = (0, 0, 0, 0);
+            let mut encoded_flags = Vec::with_capacity(point_count);
+            let mut encoded_x = vec![];
+            let mut encoded_y = vec![];
+            for (j, &flag) in point_flags.iter().enumerate() {
+                let (dx, dy) = read_triplet(&mut glyphs, flag)?;
+                x = x.wrapping_add(dx);
+                y = y.wrapping_add(dy);
+                if j == 0 {
+                    min_x = x;
+                    max_x = x;
+                    min_y = y;
+                    max_y = y;
+                } else {
+                    min_x = min_x.min(x);
+                    max_x = max_x.max(x);
+                    min_y = min_y.min(y);
+                    max_y = max_y.max(y);
+                }
+                let mut encoded_flag = if flag & 0x80 == 0 { 0x01 } else { 0x00 };
+                if j == 0
+                    && overlaps
+                        .get(i >> 3)
+                        .map_or(false, |&byte| byte & (0x80 >> (i & 7)) != 0)
+                {
+                    encoded_flag |= 0x40;
+                }
+                macro_rules! encode(
+                    ($delta:ident, $encoded:ident, $short:expr, $same:expr) => (
+                        if $delta == 0 {
+                            encoded_flag |= $same;
+                        } else if $delta > -256 && $delta < 256 {
+                            encoded_flag |= $short;
+                            if $delta > 0 {
+                                encoded_flag |= $same;
+                            }
+                            $encoded.push($delta.unsigned_abs() as u8);
+                        } else {
+                            push!($encoded, $delta);
+                        }
+                    );
+                );
+                encode!(dx, encoded_x, 0x02, 0x10);
+                encode!(dy, encoded_y, 0x04, 0x20);
+                encoded_flags.push(encoded_flag);
+            }
+            let instruction_size = read_u255(&mut glyphs)?;
+            if has_box {
+                glyph_data.extend(boxes.take_bytes(8)?);
+            } else {
+                push!(glyph_data, min_x);
+                push!(glyph_data, min_y);
+                push!(glyph_data, max_x);
+                push!(glyph_data, max_y);
+            }
+            for end_point in end_points {
+                push!(glyph_data, end_point);
+            }
+            push!(glyph_data, instruction_size);
+            glyph_data.extend(instructions.take_bytes(instruction_size as usize)?);
+            glyph_data.extend(encoded_flags);
+            glyph_data.extend(encoded_x);
+            glyph_data.extend(encoded_y);
+        }
+        glyph_data.resize((glyph_data.len() + 3) & !3, 0);
+    }
+    offsets.push(glyph_data.len() as u32);
+
+    let mut glyph_mapping = vec![];
+    for offset in offsets {
+        match mapping_format {
+            0 => push!(glyph_mapping, (offset >> 1) as u16),
+            _ => push!(glyph_mapping, offset),
+        }
+    }
+    Ok((glyph_data, glyph_mapping))
+}
+
+fn reconstruct_metrics(
+    data: &[u8],
+    metric_count: usize,
+    glyph_count: usize,
+    min_x: &[i16],
+) -> Result<Vec<u8>> {
+    macro_rules! push(
+        ($data:ident, $value:expr) => ($data.extend_from_slice(&$value.to_be_bytes()));
+    );
+    if metric_count == 0 || metric_count > glyph_count {
+        raise!("found a malformed horizontal header");
+    }
+    let mut tape = Cursor::new(data);
+    let flags = tape.take::<u8>()?;
+    if flags & 0b11 == 0 || flags & !0b11 != 0 {
+        raise!("found a malformed transformation of horizontal metrics");
+    }
+    let advance_widths: Vec<u16> = tape.take_given(metric_count)?;
+    let bearings: Vec<i16> = if flags & 0b01 == 0 {
+        tape.take_given(metric_count)?
+    } else {
+        min_x[..metric_count].to_vec()
+    };
+    let other_bearings: Vec<i16> = if flags & 0b10 == 0 {
+        tape.take_given(glyph_count - metric_count)?
+    } else {
+        min_x[metric_count..].to_vec()
+    };
+    let mut data = Vec::with_capacity(4 * metric_count + 2 * (glyph_count - metric_count));
+    for (advance_width, bearing) in advance_widths.into_iter().zip(bearings) {
+        push!(data, advance_width);
+        push!(data, bearing);
+    }
+    for bearing in other_bearings {
+        push!(data, bearing);
+    }
+    Ok(data)
+}
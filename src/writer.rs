@@ -0,0 +1,278 @@
+//! The writer.
+//!
+//! Tables are written with the counts and offsets recomputed from their
+//! contents; the corresponding fields of the tables are ignored.
+
+use std::collections::HashMap;
+use truetype::{Result, Tag};
+
+/// A type that can be written.
+pub trait Write {
+    /// Write the value.
+    fn write(&self, writer: &mut Writer) -> Result<()>;
+}
+
+/// A type that can be written given a parameter.
+pub trait WriteGiven {
+    /// The type of the parameter.
+    type Parameter;
+
+    /// Write the value.
+    fn write(&self, writer: &mut Writer, parameter: Self::Parameter) -> Result<()>;
+}
+
+/// A writer.
+///
+/// The tables that a table refers to via offsets are written into writers of
+/// their own and placed after the table when the writer is finished.
+/// Identical tables referred to by the same table are placed only once.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Writer {
+    data: Vec<u8>,
+    links: Vec<Link>,
+    extended: bool,
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+struct Link {
+    position: usize,
+    long: bool,
+    detached: bool,
+    writer: Writer,
+}
+
+struct Overflow;
+
+/// Write a value.
+///
+/// If a 16-bit offset overflows, the value is written once again with all
+/// lookups promoted to extension lookups, which refer to their tables via
+/// 32-bit offsets and have them placed at the end.
+pub fn write<T: Write + ?Sized>(value: &T) -> Result<Vec<u8>> {
+    let mut writer = Writer::new();
+    writer.give(value)?;
+    if let Ok(data) = writer.layout() {
+        return Ok(data);
+    }
+    let mut writer = Writer::new();
+    writer.extended = true;
+    writer.give(value)?;
+    writer.finish()
+}
+
+impl Writer {
+    /// Create a writer.
+    #[inline]
+    pub fn new() -> Self {
+        Writer::default()
+    }
+
+    /// Write a value.
+    #[inline]
+    pub fn give<T: Write + ?Sized>(&mut self, value: &T) -> Result<()> {
+        value.write(self)
+    }
+
+    /// Write a value given a parameter.
+    #[inline]
+    pub fn give_given<T: WriteGiven + ?Sized>(
+        &mut self,
+        value: &T,
+        parameter: T::Parameter,
+    ) -> Result<()> {
+        value.write(self, parameter)
+    }
+
+    /// Write a count as a 16-bit number.
+    pub fn give_count(&mut self, count: usize) -> Result<()> {
+        if count > u16::MAX as usize {
+            raise!("found a count overflow");
+        }
+        self.give(&(count as u16))
+    }
+
+    /// Write bytes.
+    #[inline]
+    pub fn give_bytes(&mut self, bytes: &[u8]) {
+        self.data.extend_from_slice(bytes);
+    }
+
+    /// Write a 16-bit offset to a value.
+    #[inline]
+    pub fn link<T: Write + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.attach(false, false, |writer| writer.give(value))
+    }
+
+    /// Write a 16-bit offset to a value given a parameter.
+    #[inline]
+    pub fn link_given<T: WriteGiven + ?Sized>(
+        &mut self,
+        value: &T,
+        parameter: T::Parameter,
+    ) -> Result<()> {
+        self.attach(false, false, |writer| writer.give_given(value, parameter))
+    }
+
+    /// Write a 32-bit offset to a value.
+    #[inline]
+    pub fn link_long<T: Write + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.attach(true, false, |writer| writer.give(value))
+    }
+
+    /// Write a 16-bit offset to a value if present or a null offset otherwise.
+    pub fn link_maybe<T: Write>(&mut self, value: Option<&T>) -> Result<()> {
+        match value {
+            Some(value) => self.link(value),
+            _ => self.give(&0u16),
+        }
+    }
+
+    /// Write a 32-bit offset to a value if present or a null offset otherwise.
+    pub fn link_long_maybe<T: Write>(&mut self, value: Option<&T>) -> Result<()> {
+        match value {
+            Some(value) => self.link_long(value),
+            _ => self.give(&0u32),
+        }
+    }
+
+    /// Write a 32-bit offset to a value placed at the very end.
+    #[inline]
+    pub(crate) fn link_detached<T: Write + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.attach(true, true, |writer| writer.give(value))
+    }
+
+    /// Check if lookups are to be promoted to extension lookups.
+    #[inline]
+    pub(crate) fn is_extended(&self) -> bool {
+        self.extended
+    }
+
+    /// Finish writing and return the bytes.
+    pub fn finish(self) -> Result<Vec<u8>> {
+        match self.layout() {
+            Ok(data) => Ok(data),
+            _ => raise!("found an offset overflow"),
+        }
+    }
+
+    fn attach<F>(&mut self, long: bool, detached: bool, write: F) -> Result<()>
+    where
+        F: FnOnce(&mut Writer) -> Result<()>,
+    {
+        let mut writer = Writer::new();
+        writer.extended = self.extended;
+        write(&mut writer)?;
+        self.links.push(Link {
+            position: self.data.len(),
+            long: long,
+            detached: detached,
+            writer: writer,
+        });
+        if long {
+            self.give(&0u32)
+        } else {
+            self.give(&0u16)
+        }
+    }
+
+    fn layout(&self) -> std::result::Result<Vec<u8>, Overflow> {
+        let mut data = vec![];
+        let mut detached = vec![];
+        self.place(&mut data, &mut detached)?;
+        let mut i = 0;
+        while i < detached.len() {
+            let (start, link) = detached[i];
+            let position = link.writer.place(&mut data, &mut detached)?;
+            patch(&mut data, start, link, position - start)?;
+            i += 1;
+        }
+        Ok(data)
+    }
+
+    fn place<'l>(
+        &'l self,
+        data: &mut Vec<u8>,
+        detached: &mut Vec<(usize, &'l Link)>,
+    ) -> std::result::Result<usize, Overflow> {
+        let start = data.len();
+        data.extend_from_slice(&self.data);
+        let mut placed: HashMap<&Writer, usize> = HashMap::new();
+        for link in &self.links {
+            if link.detached {
+                detached.push((start, link));
+                continue;
+            }
+            let position = match placed.get(&link.writer) {
+                Some(&position) => position,
+                _ => {
+                    let position = link.writer.place(data, detached)?;
+                    placed.insert(&link.writer, position);
+                    position
+                }
+            };
+            patch(data, start, link, position - start)?;
+        }
+        Ok(start)
+    }
+}
+
+fn patch(
+    data: &mut [u8],
+    start: usize,
+    link: &Link,
+    offset: usize,
+) -> std::result::Result<(), Overflow> {
+    let position = start + link.position;
+    if link.long {
+        if offset > u32::MAX as usize {
+            return Err(Overflow);
+        }
+        data[position..(position + 4)].copy_from_slice(&(offset as u32).to_be_bytes());
+    } else {
+        if offset > u16::MAX as usize {
+            return Err(Overflow);
+        }
+        data[position..(position + 2)].copy_from_slice(&(offset as u16).to_be_bytes());
+    }
+    Ok(())
+}
+
+macro_rules! implement {
+    ($($kind:ty,)*) => ($(
+        impl Write for $kind {
+            #[inline]
+            fn write(&self, writer: &mut Writer) -> Result<()> {
+                writer.give_bytes(&self.to_be_bytes());
+                Ok(())
+            }
+        }
+    )*);
+}
+
+implement! {
+    i8, u8, i16, u16, i32, u32,
+}
+
+impl Write for Tag {
+    #[inline]
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give_bytes(&self.0);
+        Ok(())
+    }
+}
+
+impl<T: Write> Write for [T] {
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        for value in self {
+            writer.give(value)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: Write> Write for Vec<T> {
+    #[inline]
+    fn write(&self, writer: &mut Writer) -> Result<()> {
+        writer.give(&self[..])
+    }
+}
@@ -0,0 +1,73 @@
+extern crate opentype;
+extern crate truetype;
+
+use opentype::{AxisVariations, Font, FontVariations};
+use std::io::Cursor;
+use truetype::Tape;
+
+#[macro_use]
+mod common;
+
+#[rustfmt::skip]
+const SEGMENT_MAPS: &[i16] = &[
+    4, -16384, -16384, 0, 0, 8192, 13107, 16384, 16384, // axis 0
+    3, -16384, -16384, 0, 0, 16384, 16384, // axis 1
+];
+
+#[test]
+fn version1() {
+    let mut words = vec![1, 0, 0, 2];
+    words.extend_from_slice(SEGMENT_MAPS);
+    let table: AxisVariations = ok!(Cursor::new(serialize(&words)).take());
+    assert!(table.segment_maps.len() == 2);
+    assert!(table.segment_maps[0].records.len() == 4);
+    assert!(table.mapping.is_none());
+    assert!(table.variations.is_none());
+    let map = &table.segment_maps[0];
+    assert!(map.map(-16384) == -16384);
+    assert!(map.map(-8192) == -8192);
+    assert!(map.map(0) == 0);
+    assert!(map.map(4096) == 6554);
+    assert!(map.map(8192) == 13107);
+    assert!(map.map(12288) == 14746);
+    assert!(map.map(16384) == 16384);
+    assert!(table.map(&[8192, 8192]) == [13107, 8192]);
+
+    let mut reader = setup!(Gingham);
+    let font = ok!(Font::read(&mut reader));
+    let fvar = ok!(ok!(font.take::<_, FontVariations>(&mut reader)));
+    assert!(ok!(font.take::<_, AxisVariations>(&mut reader)).is_none());
+    assert!(fvar.normalize_given(&[500.0, 150.0], None) == [8192, 16384]);
+    assert!(fvar.normalize_given(&[500.0, 150.0], Some(&table)) == [13107, 16384]);
+    assert!(fvar.normalize_given(&[400.0], Some(&table)) == [6554, 0]);
+}
+
+#[test]
+fn version2() {
+    let mut words = vec![2, 0, 0, 2];
+    words.extend_from_slice(SEGMENT_MAPS);
+    #[rustfmt::skip]
+    words.extend_from_slice(&[
+        0, 0, // axisIndexMapOffset
+        0, 48, // varStoreOffset
+        1, 0, 12, 1, 0, 28, // ItemVariationStore
+        2, 1, 0, 16384, 16384, 0, 0, 0, // VariationRegionList
+        2, 1, 1, 0, 1000, 0, // ItemVariationData
+    ]);
+    let table: AxisVariations = ok!(Cursor::new(serialize(&words)).take());
+    assert!(table.major_version == 2);
+    assert!(table.mapping.is_none());
+    assert!(ok!(table.variations.as_ref()).records[0].deltas == [vec![1000], vec![0]]);
+    assert!(table.map(&[0, 0]) == [0, 0]);
+    assert!(table.map(&[8192, 4096]) == [13907, 4096]);
+    assert!(table.map(&[16384, -16384]) == [16384, -16384]);
+
+    let mut reader = setup!(Gingham);
+    let font = ok!(Font::read(&mut reader));
+    let fvar = ok!(ok!(font.take::<_, FontVariations>(&mut reader)));
+    assert!(fvar.normalize_given(&[500.0, 100.0], Some(&table)) == [13907, 10886]);
+}
+
+fn serialize(words: &[i16]) -> Vec<u8> {
+    words.iter().flat_map(|word| word.to_be_bytes()).collect()
+}
@@ -19,7 +19,9 @@ pub enum Fixture {
     AdobeVFPrototype,
     Gingham,
     OpenSans,
+    OpenSansWOFF2,
     SourceSerifPro,
+    SourceSerifProWOFF,
 }
 
 impl Fixture {
@@ -28,13 +30,18 @@ impl Fixture {
             Fixture::AdobeVFPrototype => "tests/fixtures/AdobeVFPrototype.otf".into(),
             Fixture::Gingham => "tests/fixtures/Gingham.ttf".into(),
             Fixture::OpenSans => "tests/fixtures/OpenSans-Italic.ttf".into(),
+            Fixture::OpenSansWOFF2 => "tests/fixtures/OpenSans-Italic.woff2".into(),
             Fixture::SourceSerifPro => "tests/fixtures/SourceSerifPro-Regular.otf".into(),
+            Fixture::SourceSerifProWOFF => "tests/fixtures/SourceSerifPro-Regular.woff".into(),
         }
     }
 
     pub fn offset(&self, table: &str) -> u64 {
         match *self {
             Fixture::AdobeVFPrototype => match table {
+                "GDEF" => 154636,
+                "GPOS" => 172812,
+                "GSUB" => 239580,
                 _ => unreachable!(),
             },
             Fixture::Gingham => match table {
@@ -49,6 +56,7 @@ impl Fixture {
                 "GSUB" => 57648,
                 _ => unreachable!(),
             },
+            Fixture::OpenSansWOFF2 | Fixture::SourceSerifProWOFF => unreachable!(),
         }
     }
 }
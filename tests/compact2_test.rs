@@ -0,0 +1,155 @@
+extern crate opentype;
+extern crate truetype;
+
+use opentype::compact2::{FontSet, Index, Operator, Segment, Selector, Selector0};
+use opentype::Font;
+use std::io::Cursor;
+use truetype::Tape;
+
+#[macro_use]
+mod common;
+
+#[test]
+fn font_set() {
+    let mut reader = setup!(AdobeVFPrototype);
+    let font = ok!(Font::read(&mut reader));
+    let font_set = ok!(ok!(font.take::<_, FontSet>(&mut reader)));
+    assert!(font_set.header.major == 2);
+    assert!(font_set.header.header_size == 5);
+    assert!(font_set.global_subroutines.is_empty());
+    assert!(font_set.char_strings.len() == 313);
+    assert!(ok!(font_set.char_strings.get(1)).is_empty());
+    assert!(ok!(font_set.char_strings.get(2)).len() == 327);
+    assert!(font_set.font_dictionaries.len() == 1);
+    assert!(font_set.font_selector.is_none());
+    assert!(font_set.font_index(42) == Some(0));
+    assert!(font_set.local_subroutines.len() == 1);
+    assert!(font_set.local_subroutines[0].is_empty());
+    let variations = ok!(font_set.variations.as_ref());
+    assert!(variations.regions.axis_count == 2);
+    assert!(variations.regions.records.len() == 5);
+    assert!(variations.records[0].region_indices == [0, 1, 2, 3, 4]);
+}
+
+#[test]
+fn index() {
+    let data = vec![0, 0, 0, 2, 1, 1, 2, 4, 10, 20, 30];
+    let index: Index = ok!(Cursor::new(data).take());
+    assert!(index.len() == 2);
+    assert!(index.get(0) == Some(&[10][..]));
+    assert!(index.get(1) == Some(&[20, 30][..]));
+
+    let data = vec![0xFF, 0xFF, 0xFF, 0xFF, 1, 1, 2];
+    assert!(Cursor::new(data).take::<Index>().is_err());
+
+    let data = vec![0, 0, 0, 1, 4, 0, 0, 0, 1, 0xFF, 0xFF, 0xFF, 0xFF, 10];
+    assert!(Cursor::new(data).take::<Index>().is_err());
+}
+
+#[test]
+fn outline() {
+    let mut reader = setup!(AdobeVFPrototype);
+    let font = ok!(Font::read(&mut reader));
+    let font_set = ok!(ok!(font.take::<_, FontSet>(&mut reader)));
+    for glyph_id in 0..313 {
+        let _ = ok!(font_set.outline(glyph_id, &[0, 0]));
+        let _ = ok!(font_set.outline(glyph_id, &[-16384, 16384]));
+    }
+    let segments = ok!(font_set.outline(2, &[0, 0]));
+    assert!(segments.len() == 13);
+    assert!(segments[0] == Segment::Move((185.0, -16.0)));
+    assert!(segments[5] == Segment::Move((185.0, 661.0)));
+    assert!(segments[8] == Segment::Line((155.0, 263.0)));
+    let other = ok!(font_set.outline(2, &[16384, 0]));
+    assert!(other == segments);
+    let other = ok!(font_set.outline(2, &[0, 16384]));
+    assert!(other[0] == segments[0]);
+    assert!(other[8] == Segment::Line((168.0, 253.0)));
+    let other = ok!(font_set.outline(2, &[-16384, 0]));
+    assert!(other[0] == Segment::Move((125.0, -10.0)));
+    assert!(other[8] == Segment::Line((117.0, 173.0)));
+    assert!(font_set.outline(313, &[]).is_err());
+}
+
+#[test]
+fn outline_subroutines() {
+    macro_rules! index(($($data:expr),*) => (Index {
+        data: vec![$($data),*],
+        ..Default::default()
+    }));
+    macro_rules! number(($value:expr) => ((139 + $value) as u8));
+
+    #[rustfmt::skip]
+    let char_string = vec![
+        number!(10), number!(20), number!(30), number!(40), 18, // hstemhm
+        19, 0b1100_0000, // hintmask
+        number!(10), number!(10), 21, // rmoveto
+        number!(5), number!(-107), 10, // callsubr
+        number!(-107), 29, // callgsubr
+        number!(1), number!(2), number!(3), number!(4), number!(5), number!(6), 8, // rrcurveto
+    ];
+    let font_set = FontSet {
+        header: Default::default(),
+        top_dictionary: Default::default(),
+        global_subroutines: index![vec![number!(0), number!(10), 5]],
+        char_strings: index![char_string],
+        variations: None,
+        font_dictionaries: vec![Default::default()],
+        font_selector: None,
+        private_dictionaries: vec![Default::default()],
+        local_subroutines: vec![index![vec![6]]],
+    };
+    let segments = ok!(font_set.outline(0, &[]));
+    assert!(
+        segments
+            == [
+                Segment::Move((10.0, 10.0)),
+                Segment::Line((15.0, 10.0)),
+                Segment::Line((15.0, 20.0)),
+                Segment::Curve((16.0, 22.0), (19.0, 26.0), (24.0, 32.0)),
+            ]
+    );
+    for &code in &[11, 14] {
+        let mut other = font_set.clone();
+        other.char_strings = index![vec![number!(10), number!(10), 21, code]];
+        assert!(other.outline(0, &[]).is_err());
+    }
+}
+
+#[test]
+fn private_dictionary() {
+    let mut reader = setup!(AdobeVFPrototype);
+    let font = ok!(Font::read(&mut reader));
+    let font_set = ok!(ok!(font.take::<_, FontSet>(&mut reader)));
+    let dictionary = &font_set.private_dictionaries[0];
+    assert!(dictionary.get(Operator::StdVW) == Some(&[190.0][..]));
+    assert!(dictionary.get(Operator::BlueFuzz) == Some(&[0.0][..]));
+    assert!(dictionary.get(Operator::BlueShift) == Some(&[7.0][..]));
+    assert!(dictionary.get(Operator::VSIndex) == Some(&[0.0][..]));
+    assert!(ok!(dictionary.get_blended(Operator::StdVW, &[0.0; 5])) == [190.0]);
+    assert!(ok!(dictionary.get_blended(Operator::StdVW, &[1.0, 0.0, 0.0, 0.0, 0.0])) == [80.0]);
+    assert!(
+        ok!(dictionary.get_blended(Operator::StemSnapH, &[0.0, 0.5, 0.0, 0.0, 0.0]))
+            == [40.0, 10.0]
+    );
+}
+
+#[test]
+fn selector() {
+    let data = vec![3, 0, 2, 0, 0, 0, 0, 10, 1, 0, 20];
+    let selector: Selector = ok!(Cursor::new(data).take_given(20));
+    assert!(selector.get(0) == Some(0));
+    assert!(selector.get(9) == Some(0));
+    assert!(selector.get(10) == Some(1));
+    assert!(selector.get(19) == Some(1));
+    assert!(selector.get(20).is_none());
+
+    let data = vec![0, 1, 0, 2];
+    let selector: Selector = ok!(Cursor::new(data).take_given(3));
+    assert!(selector.get(0) == Some(1));
+    assert!(selector.get(2) == Some(2));
+    assert!(selector.get(3).is_none());
+
+    let data = vec![3, 1, 0, 2];
+    assert!(Cursor::new(data).take_given::<Selector0>(3).is_err());
+}
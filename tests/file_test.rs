@@ -49,3 +49,311 @@ fn variable_ttf() {
     let file = ok!(File::read(&mut reader));
     let _ = ok!(ok!(file[0].take::<_, GlyphSubstitution>(&mut reader)));
 }
+
+#[test]
+fn collection() {
+    use opentype::collection::Header;
+    use std::io::{Cursor, Read};
+    use truetype::{FontHeader, MaximumProfile};
+
+    let mut data = vec![];
+    ok!(setup!(OpenSans).read_to_end(&mut data));
+    let size = 12 + 16 * (((data[4] as usize) << 8) | data[5] as usize);
+    let offset = data.len() as u32;
+    let directory = data[..size].to_vec();
+    data.extend(directory);
+    let header: &[u8] = &[
+        b't', b't', b'c', b'f', 0, 2, 0, 0, 0, 0, 0, 2, // header
+        0, 0, 0, 0, 0, 0, 0, 0, // offsets
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // signature
+    ];
+    data[..header.len()].copy_from_slice(header);
+    data[12..16].copy_from_slice(&offset.to_be_bytes());
+    data[16..20].copy_from_slice(&offset.to_be_bytes());
+
+    let mut reader = Cursor::new(data);
+    let file = ok!(File::read(&mut reader));
+    match &file.collection {
+        &Some(Header::Version2(ref header)) => {
+            assert!(header.count == 2);
+            assert!(header.signature_length == 0);
+        }
+        _ => unreachable!(),
+    }
+    assert!(file.len() == 2);
+    for font in file.iter() {
+        let font_header = ok!(ok!(font.take::<_, FontHeader>(&mut reader)));
+        assert!(font_header.units_per_em == 2048);
+        let _ = ok!(ok!(font.take::<_, MaximumProfile>(&mut reader)));
+    }
+}
+
+#[test]
+fn woff() {
+    use opentype::GlyphSubstitution;
+    use postscript::compact1::FontSet;
+    use truetype::FontHeader;
+
+    let mut reader = setup!(SourceSerifProWOFF);
+    let file = ok!(File::read(&mut reader));
+    assert!(file.collection.is_none());
+    let font_header = ok!(ok!(file[0].take::<_, FontHeader>(&mut reader)));
+    assert!(font_header.units_per_em == 1000);
+    let _ = ok!(ok!(file[0].take::<_, FontSet>(&mut reader)));
+    let table = ok!(ok!(file[0].take::<_, GlyphSubstitution>(&mut reader)));
+    assert!(table.lookups.records.len() == 19);
+}
+
+#[test]
+fn woff2() {
+    use truetype::{FontHeader, GlyphData, GlyphMapping, HorizontalHeader, HorizontalMetrics};
+    use truetype::{MaximumProfile, NamingTable};
+
+    let mut reader = setup!(OpenSansWOFF2);
+    let file = ok!(File::read(&mut reader));
+    let font_header = ok!(ok!(file[0].take::<_, FontHeader>(&mut reader)));
+    assert!(font_header.units_per_em == 2048);
+    let horizontal_header = ok!(ok!(file[0].take::<_, HorizontalHeader>(&mut reader)));
+    let maximum_profile = ok!(ok!(file[0].take::<_, MaximumProfile>(&mut reader)));
+    let _ = ok!(ok!(file[0].take::<_, NamingTable>(&mut reader)));
+    let glyph_mapping = ok!(ok!(
+        file[0].take_given::<_, GlyphMapping>(&mut reader, (&font_header, &maximum_profile))
+    ));
+    let glyph_data = ok!(ok!(
+        file[0].take_given::<_, GlyphData>(&mut reader, &glyph_mapping)
+    ));
+    let horizontal_metrics = ok!(ok!(file[0].take_given::<_, HorizontalMetrics>(
+        &mut reader,
+        (&horizontal_header, &maximum_profile)
+    )));
+    assert!(glyph_data.len() == maximum_profile.glyph_count());
+    for (i, glyph) in glyph_data.iter().enumerate() {
+        if let Some(glyph) = glyph {
+            assert!(horizontal_metrics.get(i).1 == glyph.min_x);
+        }
+    }
+}
+
+#[test]
+fn woff2_metrics() {
+    use opentype::woff2::Directory;
+    use std::io::{Cursor, Read};
+    use truetype::{Tag, Tape};
+
+    let mut reader = setup!(OpenSansWOFF2);
+    let directory = ok!(Tape::take::<Directory>(&mut reader));
+    let mut data = vec![];
+    ok!(reader.read_to_end(&mut data));
+    let mut buffer = vec![];
+    let size = directory.header.compressed_size as usize;
+    let mut decompressor = brotli_decompressor::Decompressor::new(&data[..size], 4096);
+    ok!(decompressor.read_to_end(&mut buffer));
+
+    let mut tables = vec![];
+    let mut offset = 0;
+    for record in &directory.records {
+        let length = record.transform_length.unwrap_or(record.original_length) as usize;
+        tables.push(buffer[offset..(offset + length)].to_vec());
+        offset += length;
+    }
+    let find = |tag: &[u8; 4]| {
+        let i = directory
+            .records
+            .iter()
+            .position(|record| record.tag == Tag(*tag));
+        ok!(i)
+    };
+    let metric_count = u16::from_be_bytes([tables[find(b"hhea")][34], tables[find(b"hhea")][35]]);
+    let mut metrics = vec![0b11];
+    for i in 0..(metric_count as usize) {
+        metrics.extend_from_slice(&tables[find(b"hmtx")][(4 * i)..(4 * i + 2)]);
+    }
+    let hmtx = find(b"hmtx");
+    tables[hmtx] = metrics;
+
+    let stream = store(&tables.concat());
+    let mut records = vec![];
+    let mut position = 0;
+    for (i, record) in directory.records.iter().enumerate() {
+        if i == hmtx {
+            position = 48 + records.len();
+        }
+        let transform = if i == hmtx { 1 } else { record.transform() };
+        records.push(63 | (transform << 6));
+        records.extend_from_slice(&*record.tag);
+        base128(&mut records, record.original_length);
+        if i == hmtx {
+            base128(&mut records, tables[i].len() as u32);
+        } else if let Some(length) = record.transform_length {
+            base128(&mut records, length);
+        }
+    }
+    let header = &directory.header;
+    let mut other = vec![];
+    other.extend_from_slice(b"wOF2");
+    other.extend_from_slice(&header.flavor.to_be_bytes());
+    other.extend_from_slice(&((48 + records.len() + stream.len()) as u32).to_be_bytes());
+    other.extend_from_slice(&header.table_count.to_be_bytes());
+    other.extend_from_slice(&[0, 0]);
+    other.extend_from_slice(&header.sfnt_size.to_be_bytes());
+    other.extend_from_slice(&(stream.len() as u32).to_be_bytes());
+    other.extend_from_slice(&header.major_version.to_be_bytes());
+    other.extend_from_slice(&header.minor_version.to_be_bytes());
+    other.extend_from_slice(&[0; 20]);
+    other.extend(records);
+    other.extend(stream);
+
+    let mut reader = setup!(OpenSansWOFF2);
+    let file = ok!(File::read(&mut reader));
+    let table = ok!(ok!(file[0].take_bytes(&mut reader, Tag(*b"hmtx"))));
+    let mut reader = Cursor::new(&other);
+    let file = ok!(File::read(&mut reader));
+    let other_table = ok!(ok!(file[0].take_bytes(&mut reader, Tag(*b"hmtx"))));
+    assert!(table == other_table);
+
+    other[position] = 63 | (2 << 6);
+    assert!(File::read(&mut Cursor::new(&other)).is_err());
+}
+
+#[test]
+fn build() {
+    use opentype::{Builder, Font, GlyphSubstitution};
+    use std::io::{Cursor, Read};
+    use truetype::{FontHeader, NamingTable, Tag};
+
+    let mut data = vec![];
+    ok!(setup!(SourceSerifPro).read_to_end(&mut data));
+    let font = ok!(Font::read(&mut Cursor::new(&data)));
+    let mut builder = Builder::new(font.offset_table.header.version);
+    for record in font.offset_table.records.iter().rev() {
+        let (offset, length) = (record.offset as usize, record.length as usize);
+        builder.add(record.tag, data[offset..(offset + length)].to_vec());
+    }
+    let other = ok!(builder.build());
+    let records = &ok!(Font::read(&mut Cursor::new(&other)))
+        .offset_table
+        .records;
+    assert!(records.len() == font.offset_table.records.len());
+    for (record, other_record) in font.offset_table.records.iter().zip(records) {
+        assert!(record.tag == other_record.tag);
+        assert!(record.checksum == other_record.checksum);
+        assert!(record.length == other_record.length);
+        assert!(other_record.offset % 4 == 0);
+        let mut table = data[(record.offset as usize)..][..(record.length as usize)].to_vec();
+        let mut other_table =
+            other[(other_record.offset as usize)..][..(other_record.length as usize)].to_vec();
+        if record.tag == Tag(*b"head") {
+            table[8..12].copy_from_slice(&[0; 4]);
+            other_table[8..12].copy_from_slice(&[0; 4]);
+        }
+        assert!(table == other_table);
+    }
+
+    let mut reader = setup!(SourceSerifPro);
+    let table = ok!(ok!(font.take::<_, GlyphSubstitution>(&mut reader)));
+    ok!(builder.add_table(&table));
+    let data = ok!(builder.build());
+    let mut reader = Cursor::new(&data);
+    let font = ok!(Font::read(&mut reader));
+    assert!(font.offset_table.header.search_range == 128);
+    assert!(font.offset_table.header.entry_selector == 3);
+    assert!(font.offset_table.header.range_shift == 64);
+    let font_header = ok!(ok!(font.take::<_, FontHeader>(&mut reader)));
+    let _ = ok!(ok!(font.take::<_, NamingTable>(&mut reader)));
+    let _ = ok!(ok!(font.take::<_, GlyphSubstitution>(&mut reader)));
+    let sum = data.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    });
+    assert!(sum == 0xB1B0AFBA);
+    assert!(font_header.checksum_adjustment != 0);
+
+    let mut builder = Builder::new(font.offset_table.header.version);
+    for i in 0..4095u32 {
+        builder.add(Tag(i.to_be_bytes()), vec![]);
+    }
+    let data = ok!(builder.build());
+    let font = ok!(Font::read(&mut Cursor::new(&data)));
+    assert!(font.offset_table.header.search_range == 32768);
+    assert!(font.offset_table.header.entry_selector == 11);
+    assert!(font.offset_table.header.range_shift == 32752);
+    builder.add(Tag(*b"last"), vec![]);
+    assert!(builder.build().is_err());
+}
+
+#[test]
+fn lenient() {
+    use opentype::glyph_substitution::Table;
+    use opentype::{File, Font, GlyphSubstitution, Options};
+    use std::io::{Cursor, Read};
+    use truetype::NamingTable;
+
+    let mut data = vec![];
+    ok!(setup!(SourceSerifPro).read_to_end(&mut data));
+    let mut reader = Cursor::new(&data);
+    let font = ok!(Font::read(&mut reader));
+    let table = ok!(ok!(font.take::<_, GlyphSubstitution>(&mut reader)));
+    let position = 57648
+        + table.lookups_offset as usize
+        + table.lookups.offsets[0] as usize
+        + table.lookups.records[0].table_offsets[0] as usize;
+    data[position..(position + 2)].copy_from_slice(&[0, 9]);
+
+    let mut reader = Cursor::new(&data);
+    let font = ok!(Font::read(&mut reader));
+    assert!(font.take::<_, GlyphSubstitution>(&mut reader).is_err());
+    let _ = ok!(ok!(font.take::<_, NamingTable>(&mut reader)));
+    let options = Options {
+        verify_checksums: false,
+        ..Default::default()
+    };
+    let mut reader = Cursor::new(&data);
+    let font = ok!(Font::read_with(&mut reader, options));
+    assert!(font.take::<_, GlyphSubstitution>(&mut reader).is_err());
+    let options = Options {
+        verify_checksums: false,
+        lenient: true,
+    };
+    let mut reader = Cursor::new(&data);
+    let file = ok!(File::read_with(&mut reader, options));
+    let other = ok!(ok!(file[0].take::<_, GlyphSubstitution>(&mut reader)));
+    assert!(other.lookups.records.len() == table.lookups.records.len());
+    match other.lookups.records[0].tables[0] {
+        Table::Unknown(ref message) => assert!(message.contains("single-substitution")),
+        _ => unreachable!(),
+    }
+    assert!(other.lookups.records[0].tables.len() == table.lookups.records[0].tables.len());
+    let mut buffer = opentype::layout::Buffer::from(&[33, 36][..]);
+    other.apply(&mut buffer, &[17], None);
+    assert!(buffer.glyph_ids() == [211]);
+}
+
+fn base128(data: &mut Vec<u8>, mut value: u32) {
+    let mut digits = vec![(value & 0x7F) as u8];
+    value >>= 7;
+    while value > 0 {
+        digits.push((value & 0x7F) as u8 | 0x80);
+        value >>= 7;
+    }
+    data.extend(digits.into_iter().rev());
+}
+
+fn store(data: &[u8]) -> Vec<u8> {
+    let mut stream = vec![];
+    for (i, chunk) in data.chunks(0x10000).enumerate() {
+        // ISLAST, MNIBBLES, MLEN - 1, and ISUNCOMPRESSED
+        let mut bits = ((chunk.len() as u32 - 1) << 3) | (1 << 19);
+        let mut count = 20usize;
+        if i == 0 {
+            // WBITS
+            bits <<= 1;
+            count += 1;
+        }
+        stream.extend_from_slice(&bits.to_le_bytes()[..(count + 7) / 8]);
+        stream.extend_from_slice(chunk);
+    }
+    // ISLAST and ISLASTEMPTY
+    stream.push(0b11);
+    stream
+}
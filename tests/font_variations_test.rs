@@ -0,0 +1,48 @@
+extern crate opentype;
+extern crate truetype;
+
+use opentype::{Font, FontVariations};
+use truetype::q32;
+
+#[macro_use]
+mod common;
+
+#[test]
+fn cff() {
+    let mut reader = setup!(AdobeVFPrototype);
+    let font = ok!(Font::read(&mut reader));
+    let table = ok!(ok!(font.take::<_, FontVariations>(&mut reader)));
+    let tags = table.axes.iter().map(|axis| axis.tag).collect::<Vec<_>>();
+    assert!(tags == tags![b"wght", b"xxxx"]);
+    let axis = &table.axes[0];
+    assert!(axis.min_value == q32(0));
+    assert!(axis.default_value == q32(1000 << 16));
+    assert!(axis.max_value == q32(1000 << 16));
+    assert!(!axis.flags.is_hidden());
+    assert!(axis.name_id == 256);
+    assert!(table.instances.len() == 8);
+    let instance = &table.instances[7];
+    assert!(instance.subfamily_name_id == 272);
+    assert!(instance.coordinates == [q32(1000 << 16), q32(100 << 16)]);
+    assert!(instance.postscript_name_id == Some(273));
+    assert!(table.normalize(&[500.0, 50.0]) == [-0.5, 0.5]);
+    assert!(table.normalize(&[2000.0]) == [0.0, 0.0]);
+}
+
+#[test]
+fn ttf() {
+    let mut reader = setup!(Gingham);
+    let font = ok!(Font::read(&mut reader));
+    let table = ok!(ok!(font.take::<_, FontVariations>(&mut reader)));
+    let tags = table.axes.iter().map(|axis| axis.tag).collect::<Vec<_>>();
+    assert!(tags == tags![b"wght", b"wdth"]);
+    assert!(table.instances.len() == 9);
+    let instance = &table.instances[4];
+    assert!(instance.subfamily_name_id == 256);
+    assert!(instance.coordinates == [q32(400 << 16), q32(100 << 16)]);
+    assert!(instance.postscript_name_id.is_none());
+    assert!(table.normalize(&[]) == [0.0, 0.0]);
+    assert!(table.normalize(&[500.0, 150.0]) == [0.5, 1.0]);
+    assert!(table.normalize(&[100.0, 0.0]) == [0.0, 0.0]);
+    assert!(table.axes[0].normalize(700.0) == 1.0);
+}
@@ -8,6 +8,32 @@ use truetype::Value;
 #[macro_use]
 mod common;
 
+#[test]
+fn marks() {
+    use opentype::layout::Coverage;
+    use std::io::Cursor;
+
+    #[rustfmt::skip]
+    let data: &[u8] = &[
+        0, 1, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 14, // GDEF
+        0, 1, 0, 1, 0, 0, 0, 8, // MarkGlyphSetsTable
+        0, 1, 0, 2, 0, 4, 0, 7, // CoverageFormat1
+    ];
+    let table: GlyphDefinition = ok!(Value::read(&mut Cursor::new(data)));
+    match table.header {
+        Header::Version12(ref header) => assert!(header.marks_offset == 14),
+        _ => unreachable!(),
+    }
+    match table.marks {
+        Some(ref table) => match table.coverages[..] {
+            [Coverage::Format1(ref table)] => assert!(table.glyph_ids == [4, 7]),
+            _ => unreachable!(),
+        },
+        _ => unreachable!(),
+    }
+    assert!(table.variations.is_none());
+}
+
 #[test]
 fn table() {
     let table: GlyphDefinition = ok!(Value::read(&mut setup!(OpenSans, "GDEF")));
@@ -29,3 +55,87 @@ fn table() {
         _ => unreachable!(),
     }
 }
+
+#[test]
+fn variations() {
+    let table: GlyphDefinition = ok!(Value::read(&mut setup!(AdobeVFPrototype, "GDEF")));
+    match table.header {
+        Header::Version13(ref header) => {
+            assert!(header.marks_offset == 0);
+            assert!(header.variations_offset == 18);
+        }
+        _ => unreachable!(),
+    }
+    assert!(table.marks.is_none());
+    match table.variations {
+        Some(ref table) => {
+            assert!(table.regions.axis_count == 2);
+            assert!(table.records[0].item_count == 3613);
+            assert!(ok!(table.delta(0, 0, &[-10355, 0])) == -20.0);
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn variations_font() {
+    use opentype::variation::item::RegionAxis;
+    use opentype::Font;
+
+    let mut reader = setup!(AdobeVFPrototype);
+    let font = ok!(Font::read(&mut reader));
+    let table = ok!(ok!(font.take::<_, GlyphDefinition>(&mut reader)));
+    match table.header {
+        Header::Version13(ref header) => assert!(header.variations_offset == 18),
+        _ => unreachable!(),
+    }
+    let variations = ok!(table.variations.as_ref());
+    assert!(variations.regions.records.len() == 5);
+    let axes = &variations.regions.records[3].axes;
+    let axis = |axis: &RegionAxis| (axis.start, axis.peak, axis.end);
+    assert!(axis(&axes[0]) == (-16384, -10355, 0));
+    assert!(axis(&axes[1]) == (0, 16384, 16384));
+    assert!(variations.records.len() == 1);
+    assert!(variations.records[0].region_indices == [0, 1, 2, 3, 4]);
+    assert!(variations.records[0].deltas[0] == [-20, -20, 0, 0, 0]);
+    assert!(ok!(variations.delta(0, 0, &[-16384, 0])) == -20.0);
+    assert!(ok!(variations.delta(0, 1013, &[-16384, 0])) == 50.0);
+    assert!(variations.delta(1, 0, &[]).is_none());
+
+    let mut reader = setup!(Gingham);
+    let font = ok!(Font::read(&mut reader));
+    assert!(ok!(font.take::<_, GlyphDefinition>(&mut reader)).is_none());
+}
+
+#[test]
+fn write() {
+    use opentype::writer;
+    use std::io::Cursor;
+
+    #[rustfmt::skip]
+    let data: &[u8] = &[
+        0, 1, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 14, // GDEF
+        0, 1, 0, 1, 0, 0, 0, 8, // MarkGlyphSetsTable
+        0, 1, 0, 2, 0, 4, 0, 7, // CoverageFormat1
+    ];
+    let table: GlyphDefinition = ok!(Value::read(&mut Cursor::new(data)));
+    assert!(ok!(writer::write(&table)) == data);
+
+    let table: GlyphDefinition = ok!(Value::read(&mut setup!(AdobeVFPrototype, "GDEF")));
+    let data = ok!(writer::write(&table));
+    let other: GlyphDefinition = ok!(Value::read(&mut Cursor::new(&data)));
+    assert!(ok!(writer::write(&other)) == data);
+    match &other.header {
+        &Header::Version13(ref header) => assert!(header.marks_offset == 0),
+        _ => unreachable!(),
+    }
+    match &other.variations {
+        &Some(ref table) => {
+            assert!(table.regions.axis_count == 2);
+            assert!(table.records[0].item_count == 3613);
+            assert!(ok!(table.delta(0, 0, &[-10355, 0])) == -20.0);
+            assert!(ok!(table.delta(0, 1013, &[-16384, 0])) == 50.0);
+        }
+        _ => unreachable!(),
+    }
+}
@@ -1,9 +1,12 @@
 extern crate opentype;
 extern crate truetype;
 
-use opentype::glyph_positioning::{GlyphPositioning, PairAdjustment, Table};
+use opentype::glyph_positioning::{Adjustment, GlyphPositioning, PairAdjustment, Table};
 use opentype::layout::script::{Language, Script};
-use truetype::Value;
+use opentype::layout::Correction;
+use opentype::variation::item::Variations;
+use std::io::{Seek, SeekFrom};
+use truetype::{Tag, Value};
 
 #[macro_use]
 mod common;
@@ -82,3 +85,595 @@ fn scripts() {
     assert!(record.language_count == 3);
     assert!(record.get(Language::Turkish).is_some());
 }
+
+#[test]
+fn extensions() {
+    use opentype::glyph_positioning::SingleAdjustment;
+    use opentype::layout::lookup::Record;
+    use std::io::Cursor;
+
+    #[rustfmt::skip]
+    let data: &[u8] = &[
+        0, 9, 0, 0, 0, 1, 0, 8, // Lookup
+        0, 1, 0, 1, 0, 0, 0, 8, // ExtensionPosFormat1
+        0, 1, 0, 8, 0, 4, 0, 42, // SinglePosFormat1
+        0, 1, 0, 1, 0, 10, // CoverageFormat1
+    ];
+    let record: Record<Table> = ok!(Value::read(&mut Cursor::new(data)));
+    assert!(record.resolved_kind() == 1);
+    let table = record.resolved_tables().next();
+    match table {
+        Some(&Table::SingleAdjustment(SingleAdjustment::Format1(ref table))) => {
+            assert!(table.value.x_advance == Some(42));
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn variations() {
+    let mut tape = setup!(AdobeVFPrototype, "GDEF");
+    ok!(tape.seek(SeekFrom::Current(18)));
+    let variations: Variations = ok!(Value::read(&mut tape));
+    let GlyphPositioning { lookups, .. } = ok!(Value::read(&mut setup!(AdobeVFPrototype, "GPOS")));
+    let record = &lookups.records[0];
+    match &record.tables[1] {
+        &Table::PairAdjustment(PairAdjustment::Format2(ref table)) => {
+            let value = &table.sets[0].records[5].value1;
+            assert!(value.x_advance == Some(-50));
+            match value.x_advance_correction {
+                Some(Correction::Variation(ref correction)) => {
+                    assert!((correction.outer_index, correction.inner_index) == (0, 1013));
+                }
+                _ => unreachable!(),
+            }
+            let adjustment = value.vary(&variations, &[0, 0]);
+            assert!(
+                adjustment
+                    == Adjustment {
+                        x_advance: -50.0,
+                        ..Default::default()
+                    }
+            );
+            let adjustment = value.vary(&variations, &[-16384, 0]);
+            assert!(adjustment == Adjustment::default());
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn corrections() {
+    use opentype::glyph_positioning::Anchor;
+    use std::io::Cursor;
+
+    #[rustfmt::skip]
+    let data: &[u8] = &[
+        0, 9, 0, 11, 0, 2, 0x87, 0xf0, // DeviceFormat2
+    ];
+    let correction: Correction = ok!(Value::read(&mut Cursor::new(data)));
+    let deltas = (8..13)
+        .map(|size| correction.hint(size))
+        .collect::<Vec<_>>();
+    assert!(deltas == [0, -8, 7, -1, 0]);
+
+    #[rustfmt::skip]
+    let data: &[u8] = &[
+        0, 20, 0, 21, 0, 3, 0x80, 0x05, // DeviceFormat3
+    ];
+    let mut tape = Cursor::new(data);
+    let correction: Correction = ok!(Value::read(&mut tape));
+    assert!(tape.position() == 8);
+    assert!(correction.hint(20) == -128);
+    assert!(correction.hint(21) == 5);
+
+    #[rustfmt::skip]
+    let data: &[u8] = &[
+        0, 3, 0, 100, 255, 206, 0, 10, 0, 0, // AnchorFormat3
+        0, 11, 0, 15, 0, 1, 0x55, 0x40, // DeviceFormat1
+    ];
+    let anchor: Anchor = ok!(Value::read(&mut Cursor::new(data)));
+    assert!(anchor.hint(10, 50) == (20.0, -10.0));
+    assert!(anchor.hint(11, 50) == (23.0, -11.0));
+}
+
+#[test]
+fn values() {
+    use opentype::glyph_positioning::SingleAdjustment;
+    use std::io::Cursor;
+
+    let start_size = |correction: &Option<Correction>| match *correction {
+        Some(Correction::Device(ref device)) => Some(device.start_size),
+        _ => None,
+    };
+
+    #[rustfmt::skip]
+    let data: &[u8] = &[
+        0, 1, 0, 28, 0, 0x44, 0, 0, 0, 1, 0, 12, // PairPosFormat1
+        0, 1, 0, 7, 0, 10, 0, 8, // PairSet
+        0, 11, 0, 15, 0, 1, 0x55, 0x40, // DeviceFormat1
+        0, 1, 0, 1, 0, 5, // CoverageFormat1
+    ];
+    match ok!(Value::read(&mut Cursor::new(data))) {
+        PairAdjustment::Format1(ref table) => {
+            let record = &table.sets[0].records[0];
+            assert!(record.glyph2_id == 7);
+            assert!(record.value1.x_advance == Some(10));
+            assert!(start_size(&record.value1.x_advance_correction) == Some(11));
+        }
+        _ => unreachable!(),
+    }
+
+    #[rustfmt::skip]
+    let data: &[u8] = &[
+        0, 2, 0, 24, 0, 0x44, 0, 2, // SinglePosFormat2
+        0, 10, 0, 0, // ValueRecord
+        0, 20, 0, 16, // ValueRecord
+        0, 11, 0, 15, 0, 1, 0x55, 0x40, // DeviceFormat1
+        0, 1, 0, 2, 0, 5, 0, 6, // CoverageFormat1
+    ];
+    match ok!(Value::read(&mut Cursor::new(data))) {
+        SingleAdjustment::Format2(ref table) => {
+            assert!(table.values[0].x_advance == Some(10));
+            assert!(table.values[0].x_advance_correction.is_none());
+            assert!(table.values[1].x_advance == Some(20));
+            assert!(start_size(&table.values[1].x_advance_correction) == Some(11));
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn apply() {
+    use opentype::glyph_definition::GlyphDefinition;
+    use opentype::glyph_positioning::Position;
+    use opentype::layout::Buffer;
+    use std::io::Cursor;
+
+    let table: GlyphPositioning = ok!(Value::read(&mut setup!(SourceSerifPro, "GPOS")));
+    let (glyph1_id, glyph2_id, x_advance) = match &table.lookups.records[0].tables[0] {
+        &Table::PairAdjustment(PairAdjustment::Format1(ref table)) => {
+            let (glyph_id, index) = ok!(table.coverage.iter().next());
+            let pair = &table.sets[index as usize].records[0];
+            (glyph_id, pair.glyph2_id, ok!(pair.value1.x_advance))
+        }
+        _ => unreachable!(),
+    };
+    assert!(x_advance != 0);
+    let indices = table.lookup_indices(Tag(*b"latn"), None, &[Tag(*b"kern")]);
+    let buffer = Buffer::from(&[glyph1_id, glyph2_id][..]);
+    let mut positions = vec![
+        Position {
+            x_advance: 500.0,
+            ..Default::default()
+        };
+        2
+    ];
+    ok!(table.apply(&buffer, &mut positions, &indices, None, &[]));
+    assert!(positions[0].x_advance == 500.0 + x_advance as f32);
+    assert!(positions[1].x_advance == 500.0);
+    assert!(table
+        .apply(&buffer, &mut positions[..1], &indices, None, &[])
+        .is_err());
+
+    #[rustfmt::skip]
+    let data: &[u8] = &[
+        0, 1, 0, 0, 0, 10, 0, 12, 0, 14, // GPOS
+        0, 0, // ScriptList
+        0, 0, // FeatureList
+        0, 1, 0, 4, // LookupList
+        0, 4, 0, 0, 0, 1, 0, 8, // Lookup
+        0, 1, 0, 12, 0, 20, 0, 1, 0, 28, 0, 50, // MarkBasePosFormat1
+        0, 1, 0, 2, 0, 5, 0, 6, // CoverageFormat1
+        0, 1, 0, 2, 0, 3, 0, 4, // CoverageFormat1
+        0, 2, 0, 0, 0, 10, 0, 0, 0, 16, // MarkArray
+        0, 1, 0, 100, 1, 244, // AnchorFormat1
+        0, 1, 0, 50, 255, 236, // AnchorFormat1
+        0, 2, 0, 6, 0, 0, // BaseArray
+        0, 1, 1, 44, 2, 88, // AnchorFormat1
+    ];
+    let table: GlyphPositioning = ok!(Value::read(&mut Cursor::new(data)));
+    #[rustfmt::skip]
+    let data: &[u8] = &[
+        0, 1, 0, 0, 0, 12, 0, 0, 0, 0, 0, 0, // GDEF
+        0, 2, 0, 1, 0, 5, 0, 6, 0, 3, // ClassDefFormat2
+    ];
+    let definition: GlyphDefinition = ok!(Value::read(&mut Cursor::new(data)));
+    let buffer = Buffer::from(&[3, 5, 6, 4, 5][..]);
+    let mut positions = vec![
+        Position {
+            x_advance: 500.0,
+            ..Default::default()
+        },
+        Position::default(),
+        Position::default(),
+        Position {
+            x_advance: 400.0,
+            ..Default::default()
+        },
+        Position::default(),
+    ];
+    ok!(table.apply(&buffer, &mut positions, &[0], Some(&definition), &[]));
+    let offsets = positions
+        .iter()
+        .map(|position| (position.x_offset, position.y_offset))
+        .collect::<Vec<_>>();
+    assert!(
+        offsets
+            == [
+                (0.0, 0.0),
+                (-300.0, 100.0),
+                (-250.0, 620.0),
+                (0.0, 0.0),
+                (0.0, 0.0)
+            ]
+    );
+}
+
+#[test]
+fn apply_cursive() {
+    use opentype::glyph_positioning::{CursiveAttachment, Passage};
+    use opentype::layout::Buffer;
+
+    let table = Table::CursiveAttachment(CursiveAttachment {
+        coverage: coverage(&[1, 2]),
+        passages: vec![
+            Passage {
+                exit: Some(anchor(550, 100)),
+                ..Default::default()
+            },
+            Passage {
+                entry: Some(anchor(20, 40)),
+                exit: Some(anchor(300, 80)),
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    });
+    let buffer = Buffer::from(&[1, 2, 2][..]);
+    let positions = position(vec![(3, table)], &buffer, &[600.0; 3], None);
+    assert!(
+        positions
+            == [
+                (550.0, 0.0, 0.0),
+                (280.0, -20.0, 60.0),
+                (580.0, -20.0, 100.0),
+            ]
+    );
+
+    let table = Table::CursiveAttachment(CursiveAttachment {
+        coverage: coverage(&[2]),
+        passages: vec![Passage {
+            entry: Some(anchor(20, 40)),
+            exit: Some(anchor(300, 80)),
+            ..Default::default()
+        }],
+        ..Default::default()
+    });
+    let count = 200000;
+    let buffer = Buffer::from(&vec![2; count][..]);
+    let positions = position_given(1, vec![(3, table)], &buffer, &vec![600.0; count], None);
+    assert!(positions[0] == (300.0, 0.0, -40.0 * (count - 1) as f32));
+    assert!(positions[count - 1] == (580.0, -20.0, 0.0));
+}
+
+#[test]
+fn apply_mark_to_ligature() {
+    use opentype::glyph_definition::GlyphDefinition;
+    use opentype::glyph_positioning::{
+        Component, Ligature, Ligatures, Mark1, Mark1s, MarkToLigatureAttachment,
+    };
+    use opentype::layout::{Buffer, Glyph};
+    use std::io::Cursor;
+
+    let table = Table::MarkToLigatureAttachment(MarkToLigatureAttachment {
+        mark_coverage: coverage(&[20]),
+        ligature_coverage: coverage(&[10]),
+        marks: Mark1s {
+            records: vec![Mark1 {
+                anchor: anchor(50, 0),
+                ..Default::default()
+            }],
+            ..Default::default()
+        },
+        ligatures: Ligatures {
+            records: vec![Ligature {
+                components: vec![
+                    Component {
+                        anchors: vec![Some(anchor(100, 500))],
+                        ..Default::default()
+                    },
+                    Component {
+                        anchors: vec![Some(anchor(400, 500))],
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            }],
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+    #[rustfmt::skip]
+    let data: &[u8] = &[
+        0, 1, 0, 0, 0, 12, 0, 0, 0, 0, 0, 0, // GDEF
+        0, 2, 0, 1, 0, 20, 0, 21, 0, 3, // ClassDefFormat2
+    ];
+    let definition: GlyphDefinition = ok!(Value::read(&mut Cursor::new(data)));
+    let glyph = |glyph_id, ligature_id, component| Glyph {
+        glyph_id: glyph_id,
+        ligature_id: ligature_id,
+        component: component,
+        ..Default::default()
+    };
+    let buffer = Buffer::new(vec![
+        glyph(10, 1, 0),
+        glyph(20, 1, 1),
+        glyph(20, 1, 2),
+        glyph(20, 0, 0),
+    ]);
+    let positions = position(
+        vec![(5, table)],
+        &buffer,
+        &[1000.0, 0.0, 0.0, 0.0],
+        Some(&definition),
+    );
+    assert!(
+        positions
+            == [
+                (1000.0, 0.0, 0.0),
+                (0.0, -950.0, 500.0),
+                (0.0, -650.0, 500.0),
+                (0.0, -650.0, 500.0),
+            ]
+    );
+}
+
+#[test]
+fn apply_mark_to_mark() {
+    use opentype::glyph_positioning::{Mark1, Mark1s, Mark2, Mark2s, MarkToMarkAttachment};
+    use opentype::layout::{Buffer, Glyph};
+
+    let table = Table::MarkToMarkAttachment(MarkToMarkAttachment {
+        mark1_coverage: coverage(&[21]),
+        mark2_coverage: coverage(&[20]),
+        mark1s: Mark1s {
+            records: vec![Mark1 {
+                anchor: anchor(10, -20),
+                ..Default::default()
+            }],
+            ..Default::default()
+        },
+        mark2s: Mark2s {
+            records: vec![Mark2 {
+                anchors: vec![Some(anchor(30, 300))],
+                ..Default::default()
+            }],
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+    let offsets = |components: (u16, u16)| {
+        let glyph = |glyph_id, component| Glyph {
+            glyph_id: glyph_id,
+            ligature_id: 1,
+            component: component,
+            ..Default::default()
+        };
+        let buffer = Buffer::new(vec![
+            glyph(10, 0),
+            glyph(20, components.0),
+            glyph(21, components.1),
+        ]);
+        let positions = position(vec![(6, table.clone())], &buffer, &[1000.0, 0.0, 0.0], None);
+        (positions[2].1, positions[2].2)
+    };
+    assert!(offsets((1, 1)) == (20.0, 320.0));
+    assert!(offsets((1, 2)) == (0.0, 0.0));
+
+    let buffer = Buffer::from(&[1, 20, 21][..]);
+    let positions = position(vec![(6, table)], &buffer, &[1000.0, 0.0, 0.0], None);
+    assert!(positions[2] == (0.0, 20.0, 320.0));
+}
+
+#[test]
+fn apply_context() {
+    use opentype::glyph_positioning::{
+        ChainContextPositioning, ChainContextPositioning3, ContextPositioning, ContextPositioning1,
+        Positioning, Rule, Rules, Single, SingleAdjustment, SingleAdjustment1,
+    };
+    use opentype::layout::Buffer;
+
+    let adjustment = Table::SingleAdjustment(SingleAdjustment::Format1(SingleAdjustment1 {
+        coverage: coverage(&[2]),
+        value: Single {
+            x_placement: Some(15),
+            ..Default::default()
+        },
+        ..Default::default()
+    }));
+
+    let table = Table::ContextPositioning(ContextPositioning::Format1(ContextPositioning1 {
+        coverage: coverage(&[1]),
+        sets: vec![Rules {
+            records: vec![Rule {
+                input_glyph_ids: vec![2],
+                operations: vec![Positioning {
+                    sequence_index: 1,
+                    lookup_index: 1,
+                }],
+                ..Default::default()
+            }],
+            ..Default::default()
+        }],
+        ..Default::default()
+    }));
+    let buffer = Buffer::from(&[1, 2, 2][..]);
+    let positions = position(
+        vec![(7, table), (1, adjustment.clone())],
+        &buffer,
+        &[500.0; 3],
+        None,
+    );
+    assert!(positions == [(500.0, 0.0, 0.0), (500.0, 15.0, 0.0), (500.0, 0.0, 0.0)]);
+
+    let table = Table::ChainContextPositioning(ChainContextPositioning::Format3(
+        ChainContextPositioning3 {
+            backward_coverages: vec![coverage(&[1])],
+            input_coverages: vec![coverage(&[2])],
+            forward_coverages: vec![coverage(&[3])],
+            operations: vec![Positioning {
+                sequence_index: 0,
+                lookup_index: 1,
+            }],
+            ..Default::default()
+        },
+    ));
+    let buffer = Buffer::from(&[1, 2, 3, 2, 3][..]);
+    let positions = position(
+        vec![(8, table), (1, adjustment)],
+        &buffer,
+        &[500.0; 5],
+        None,
+    );
+    assert!(
+        positions
+            .iter()
+            .map(|position| position.1)
+            .collect::<Vec<_>>()
+            == [0.0, 15.0, 0.0, 0.0, 0.0]
+    );
+}
+
+#[test]
+fn write() {
+    use opentype::glyph_positioning::Position;
+    use opentype::layout::Buffer;
+    use opentype::writer;
+    use std::io::Cursor;
+
+    let mut tape = setup!(AdobeVFPrototype, "GDEF");
+    ok!(tape.seek(SeekFrom::Current(18)));
+    let variations: Variations = ok!(Value::read(&mut tape));
+    let table: GlyphPositioning = ok!(Value::read(&mut setup!(AdobeVFPrototype, "GPOS")));
+    let data = ok!(writer::write(&table));
+    let other: GlyphPositioning = ok!(Value::read(&mut Cursor::new(&data)));
+    assert!(ok!(writer::write(&other)) == data);
+    match &other.lookups.records[0].tables[1] {
+        &Table::PairAdjustment(PairAdjustment::Format2(ref table)) => {
+            let value = &table.sets[0].records[5].value1;
+            let adjustment = value.vary(&variations, &[-16384, 0]);
+            assert!(adjustment == Adjustment::default());
+        }
+        _ => unreachable!(),
+    }
+
+    let mut table: GlyphPositioning = ok!(Value::read(&mut setup!(SourceSerifPro, "GPOS")));
+    let (glyph1_id, glyph2_id) = match &mut table.lookups.records[0].tables[0] {
+        &mut Table::PairAdjustment(PairAdjustment::Format1(ref mut table)) => {
+            let (glyph_id, index) = ok!(table.coverage.iter().next());
+            let pair = &mut table.sets[index as usize].records[0];
+            pair.value1.x_advance = Some(-42);
+            pair.value1.y_advance = Some(7);
+            (glyph_id, pair.glyph2_id)
+        }
+        _ => unreachable!(),
+    };
+    let data = ok!(writer::write(&table));
+    let other: GlyphPositioning = ok!(Value::read(&mut Cursor::new(&data)));
+    let indices = other.lookup_indices(Tag(*b"latn"), None, &[Tag(*b"kern")]);
+    let buffer = Buffer::from(&[glyph1_id, glyph2_id][..]);
+    let mut positions = vec![Position::default(); 2];
+    ok!(other.apply(&buffer, &mut positions, &indices, None, &[]));
+    assert!((positions[0].x_advance, positions[0].y_advance) == (-42.0, 7.0));
+
+    #[rustfmt::skip]
+    let data: &[u8] = &[
+        0, 1, 0, 0, 0, 10, 0, 12, 0, 14, // GPOS
+        0, 0, // ScriptList
+        0, 0, // FeatureList
+        0, 1, 0, 4, // LookupList
+        0, 4, 0, 0, 0, 1, 0, 8, // Lookup
+        0, 1, 0, 12, 0, 20, 0, 1, 0, 28, 0, 50, // MarkBasePosFormat1
+        0, 1, 0, 2, 0, 5, 0, 6, // CoverageFormat1
+        0, 1, 0, 2, 0, 3, 0, 4, // CoverageFormat1
+        0, 2, 0, 0, 0, 10, 0, 0, 0, 16, // MarkArray
+        0, 1, 0, 100, 1, 244, // AnchorFormat1
+        0, 1, 0, 50, 255, 236, // AnchorFormat1
+        0, 2, 0, 6, 0, 0, // BaseArray
+        0, 1, 1, 44, 2, 88, // AnchorFormat1
+    ];
+    let table: GlyphPositioning = ok!(Value::read(&mut Cursor::new(data)));
+    #[rustfmt::skip]
+    let expected: &[u8] = &[
+        0, 1, 0, 0, 0, 10, 0, 10, 0, 12, // GPOS
+        0, 0, // ScriptList and FeatureList
+        0, 1, 0, 4, // LookupList
+    ];
+    let data = &[expected, &data[18..]].concat();
+    assert!(&ok!(writer::write(&table)) == data);
+}
+
+fn anchor(x: i16, y: i16) -> opentype::glyph_positioning::Anchor {
+    use opentype::glyph_positioning::{Anchor, Anchor1};
+
+    Anchor::Format1(Anchor1 {
+        format: 1,
+        x: x,
+        y: y,
+    })
+}
+
+fn coverage(glyph_ids: &[truetype::GlyphID]) -> opentype::layout::Coverage {
+    use opentype::layout::{Coverage, Coverage1};
+
+    Coverage::Format1(Coverage1 {
+        glyph_ids: glyph_ids.to_vec(),
+        ..Default::default()
+    })
+}
+
+fn position(
+    tables: Vec<(u16, Table)>,
+    buffer: &opentype::layout::Buffer,
+    advances: &[f32],
+    definition: Option<&opentype::glyph_definition::GlyphDefinition>,
+) -> Vec<(f32, f32, f32)> {
+    position_given(0, tables, buffer, advances, definition)
+}
+
+fn position_given(
+    flags: u16,
+    tables: Vec<(u16, Table)>,
+    buffer: &opentype::layout::Buffer,
+    advances: &[f32],
+    definition: Option<&opentype::glyph_definition::GlyphDefinition>,
+) -> Vec<(f32, f32, f32)> {
+    use opentype::glyph_positioning::Position;
+    use opentype::layout::lookup::{Flags, Record};
+
+    let mut table: GlyphPositioning = ok!(Value::read(&mut setup!(SourceSerifPro, "GPOS")));
+    table.lookups.records = tables
+        .into_iter()
+        .map(|(kind, table)| Record {
+            kind: kind,
+            flags: Flags(flags),
+            table_count: 1,
+            table_offsets: vec![0],
+            mark_filtering_set: None,
+            tables: vec![table],
+        })
+        .collect();
+    let mut positions = advances
+        .iter()
+        .map(|&x_advance| Position {
+            x_advance: x_advance,
+            ..Default::default()
+        })
+        .collect::<Vec<_>>();
+    ok!(table.apply(buffer, &mut positions, &[0], definition, &[]));
+    positions
+        .iter()
+        .map(|position| (position.x_advance, position.x_offset, position.y_offset))
+        .collect()
+}
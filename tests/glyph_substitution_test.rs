@@ -3,7 +3,7 @@ extern crate truetype;
 
 use opentype::glyph_substitution::{GlyphSubstitution, SingleSubstitution, Table};
 use opentype::layout::script::{Language, Script};
-use truetype::Value;
+use truetype::{Tag, Value};
 
 #[macro_use]
 mod common;
@@ -126,3 +126,263 @@ fn scripts() {
     assert!(record.language_count == 3);
     assert!(record.get(Language::Turkish).is_some());
 }
+
+#[test]
+fn closure() {
+    let table: GlyphSubstitution = ok!(Value::read(&mut setup!(SourceSerifPro, "GSUB")));
+    // f, i, a, one, slash
+    let glyph_ids = [33, 36, 28, 216, 310];
+    let closure = |features: &[Tag]| {
+        table
+            .closure(&glyph_ids, features)
+            .into_iter()
+            .collect::<Vec<_>>()
+    };
+    assert!(closure(&[]) == [28, 33, 36, 216, 310]);
+    assert!(closure(&[Tag(*b"liga")]) == [28, 33, 36, 211, 216, 310]);
+    assert!(closure(&[Tag(*b"frac")]) == [28, 33, 36, 216, 310, 360, 374, 429]);
+    assert!(
+        closure(&[Tag(*b"aalt")])
+            == [28, 33, 36, 163, 216, 227, 238, 248, 310, 328, 346, 360, 374, 389, 394, 397]
+    );
+    let mut glyph_ids = vec![33].into_iter().collect();
+    table.close(&mut glyph_ids, &[17]);
+    assert!(glyph_ids.into_iter().collect::<Vec<_>>() == [33]);
+    let mut glyph_ids = vec![33, 36].into_iter().collect();
+    table.close(&mut glyph_ids, &[17]);
+    assert!(glyph_ids.into_iter().collect::<Vec<_>>() == [33, 36, 211]);
+}
+
+#[test]
+fn extensions() {
+    use opentype::glyph_substitution::SingleSubstitution;
+    use opentype::layout::lookup::Record;
+    use opentype::layout::Coverage;
+    use std::io::Cursor;
+
+    #[rustfmt::skip]
+    let data: &[u8] = &[
+        0, 7, 0, 0, 0, 1, 0, 8, // Lookup
+        0, 1, 0, 1, 0, 0, 0, 8, // ExtensionSubstFormat1
+        0, 1, 0, 6, 0, 5, // SingleSubstFormat1
+        0, 1, 0, 1, 0, 10, // CoverageFormat1
+    ];
+    let record: Record<Table> = ok!(Value::read(&mut Cursor::new(data)));
+    assert!(record.kind == 7);
+    assert!(record.resolved_kind() == 1);
+    match record.tables[0] {
+        Table::ExtensionSubstitution(ref table) => {
+            assert!(table.kind == 1);
+            assert!(table.offset == 8);
+        }
+        _ => unreachable!(),
+    }
+    let tables = record.resolved_tables().collect::<Vec<_>>();
+    assert!(tables.len() == 1);
+    match tables[0] {
+        &Table::SingleSubstitution(SingleSubstitution::Format1(ref table)) => {
+            assert!(table.delta_glyph_id == 5);
+            match table.coverage {
+                Coverage::Format1(ref table) => assert!(table.glyph_ids == [10]),
+                _ => unreachable!(),
+            }
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn variations() {
+    use opentype::layout::feature::Condition;
+
+    let table: GlyphSubstitution = ok!(Value::read(&mut setup!(AdobeVFPrototype, "GSUB")));
+    let variations = table.variations.as_ref().unwrap();
+    assert!(variations.count == 1);
+    match ok!(variations.conditions[0].as_ref()).records[0] {
+        Condition::Format1(ref condition) => {
+            assert!(condition.axis_index == 0);
+            assert!(condition.min_value == -3277);
+            assert!(condition.max_value == 0);
+        }
+        _ => unreachable!(),
+    }
+    let substitutions = ok!(variations.substitutions[0].as_ref());
+    assert!(
+        substitutions
+            .headers
+            .iter()
+            .map(|header| header.index)
+            .collect::<Vec<_>>()
+            == [12, 13]
+    );
+    assert!(table.features.headers[12].tag == truetype::Tag(*b"rvrn"));
+    assert!(table.features.records[12].lookup_indices == [9]);
+    let features = table.features_at(&[-1000]);
+    assert!(features.records[12].lookup_indices == [10]);
+    assert!(features.records[13].lookup_indices == [10]);
+    assert!(features.records[14].lookup_indices == [2]);
+    let features = table.features_at(&[1000]);
+    assert!(features.records[12].lookup_indices == [9]);
+    let features = table.features_at(&[]);
+    assert!(features.records[12].lookup_indices == [10]);
+}
+
+#[test]
+fn lookup_indices() {
+    let mut table: GlyphSubstitution = ok!(Value::read(&mut setup!(SourceSerifPro, "GSUB")));
+    let (latn, cyrl) = (Tag(*b"latn"), Tag(*b"cyrl"));
+    let (liga, locl) = (Tag(*b"liga"), Tag(*b"locl"));
+    assert!(table.lookup_indices(latn, None, &[liga, locl]) == [17]);
+    assert!(table.lookup_indices(latn, Some(Tag(*b"TRK ")), &[liga, locl]) == [2, 17]);
+    assert!(table.lookup_indices(latn, Some(Tag(*b"dflt")), &[liga, locl]) == [17]);
+    assert!(table.lookup_indices(latn, Some(Tag(*b"DEU ")), &[liga, locl]) == [17]);
+    assert!(table.lookup_indices(cyrl, None, &[liga]) == [17]);
+    let (frac, sups) = (Tag(*b"frac"), Tag(*b"sups"));
+    assert!(table.lookup_indices(latn, None, &[sups, frac]) == [4, 6, 7, 8, 9, 10]);
+    assert!(table.lookup_indices(latn, None, &[]).is_empty());
+    table.scripts.records[1]
+        .default_language
+        .as_mut()
+        .unwrap()
+        .required_feature_index = 6;
+    assert!(table.lookup_indices(latn, None, &[liga]) == [3, 17]);
+}
+
+#[test]
+fn apply() {
+    use opentype::glyph_definition::GlyphDefinition;
+    use opentype::layout::lookup::Flags;
+    use opentype::layout::{Buffer, Glyph};
+    use std::io::Cursor;
+
+    let mut table: GlyphSubstitution = ok!(Value::read(&mut setup!(SourceSerifPro, "GSUB")));
+
+    let indices = table.lookup_indices(Tag(*b"latn"), None, &[Tag(*b"frac")]);
+    let mut buffer = Buffer::from(&[216, 310, 217, 218][..]);
+    table.apply(&mut buffer, &indices, None);
+    assert!(buffer.glyph_ids() == [374, 429, 361, 362]);
+
+    let indices = table.lookup_indices(Tag(*b"latn"), None, &[Tag(*b"liga")]);
+    let mut buffer = Buffer::from(&[33, 47, 33, 36, 1][..]);
+    table.apply(&mut buffer, &indices, None);
+    assert!(buffer.glyph_ids() == [213, 211, 1]);
+    assert!(buffer.iter().map(|glyph| glyph.cluster).collect::<Vec<_>>() == [0, 2, 4]);
+
+    #[rustfmt::skip]
+    let data: &[u8] = &[
+        0, 1, 0, 0, 0, 12, 0, 0, 0, 0, 0, 0, // GDEF
+        0, 2, 0, 1, 0, 2, 0, 2, 0, 3, // ClassDefFormat2
+    ];
+    let definition: GlyphDefinition = ok!(Value::read(&mut Cursor::new(data)));
+    let mut buffer = Buffer::from(&[33, 2, 36][..]);
+    table.apply(&mut buffer, &indices, Some(&definition));
+    assert!(buffer.glyph_ids() == [33, 2, 36]);
+    table.lookups.records[17].flags = Flags(0x0008);
+    table.apply(&mut buffer, &indices, Some(&definition));
+    assert!(
+        buffer[..]
+            == [
+                Glyph {
+                    glyph_id: 211,
+                    cluster: 0,
+                    ligature_id: 1,
+                    component: 0,
+                    alternate: 0,
+                },
+                Glyph {
+                    glyph_id: 2,
+                    cluster: 1,
+                    ligature_id: 1,
+                    component: 1,
+                    alternate: 0,
+                },
+            ]
+    );
+
+    let mut buffer = Buffer::from(&[215, 215, 215][..]);
+    buffer[1].alternate = 2;
+    buffer[2].alternate = 6;
+    table.apply(&mut buffer, &[1], None);
+    assert!(buffer.glyph_ids() == [373, 327, 215]);
+}
+
+#[test]
+fn write() {
+    use opentype::glyph_substitution::Ligature;
+    use opentype::layout::Buffer;
+    use opentype::writer;
+    use std::io::Cursor;
+
+    let mut table: GlyphSubstitution = ok!(Value::read(&mut setup!(SourceSerifPro, "GSUB")));
+    let data = ok!(writer::write(&table));
+    let other: GlyphSubstitution = ok!(Value::read(&mut Cursor::new(&data)));
+    assert!(ok!(writer::write(&other)) == data);
+    assert!(other.lookups.records.len() == table.lookups.records.len());
+    let indices = other.lookup_indices(Tag(*b"latn"), None, &[Tag(*b"frac")]);
+    assert!(indices == [4, 6, 7]);
+    let mut buffer = Buffer::from(&[216, 310, 217, 218][..]);
+    other.apply(&mut buffer, &indices, None);
+    assert!(buffer.glyph_ids() == [374, 429, 361, 362]);
+
+    match &mut table.lookups.records[17].tables[0] {
+        &mut Table::LigatureSubstitution(ref mut table) => {
+            let index = ok!(table.coverage.index(33)) as usize;
+            table.sets[index].records.push(Ligature {
+                glyph_id: 300,
+                component_ids: vec![33, 33],
+                ..Default::default()
+            });
+        }
+        _ => unreachable!(),
+    }
+    let data = ok!(writer::write(&table));
+    let other: GlyphSubstitution = ok!(Value::read(&mut Cursor::new(&data)));
+    let indices = other.lookup_indices(Tag(*b"latn"), None, &[Tag(*b"liga")]);
+    let mut buffer = Buffer::from(&[33, 33, 33, 33, 36][..]);
+    other.apply(&mut buffer, &indices, None);
+    assert!(buffer.glyph_ids() == [300, 211]);
+}
+
+#[test]
+fn write_extensions() {
+    use opentype::glyph_substitution::SingleSubstitution2;
+    use opentype::layout::lookup::{Flags, Record};
+    use opentype::layout::{Buffer, Coverage, Coverage1};
+    use opentype::writer::{self, Writer};
+    use std::io::Cursor;
+
+    let mut table: GlyphSubstitution = ok!(Value::read(&mut setup!(SourceSerifPro, "GSUB")));
+    table.lookups.records = (0..20)
+        .map(|i| Record {
+            kind: 1,
+            flags: Flags(0),
+            table_count: 0,
+            table_offsets: vec![],
+            mark_filtering_set: None,
+            tables: vec![Table::SingleSubstitution(SingleSubstitution::Format2(
+                SingleSubstitution2 {
+                    coverage: Coverage::Format1(Coverage1 {
+                        glyph_ids: (0..1000).collect(),
+                        ..Default::default()
+                    }),
+                    glyph_ids: (0..1000).map(|glyph_id| glyph_id + i).collect(),
+                    ..Default::default()
+                },
+            ))],
+        })
+        .collect();
+    let mut writer = Writer::new();
+    ok!(writer.give(&table));
+    assert!(writer.finish().is_err());
+    let data = ok!(writer::write(&table));
+    let other: GlyphSubstitution = ok!(Value::read(&mut Cursor::new(&data)));
+    assert!(other.lookups.records.len() == 20);
+    for record in &other.lookups.records {
+        assert!(record.kind == 7);
+        assert!(record.resolved_kind() == 1);
+    }
+    let mut buffer = Buffer::from(&[5, 990][..]);
+    other.apply(&mut buffer, &[19], None);
+    assert!(buffer.glyph_ids() == [24, 1009]);
+    assert!(ok!(writer::write(&other)) == data);
+}
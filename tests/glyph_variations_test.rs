@@ -0,0 +1,153 @@
+extern crate opentype;
+extern crate truetype;
+
+use opentype::{Font, FontVariations, GlyphVariations};
+use std::io::Cursor;
+use truetype::glyph_data::{Arguments, Description, Glyph, PointFlags, SimpleDescription};
+use truetype::{FontHeader, GlyphData, GlyphMapping, MaximumProfile, Tape};
+
+#[macro_use]
+mod common;
+
+#[test]
+fn ttf() {
+    let mut reader = setup!(Gingham);
+    let font = ok!(Font::read(&mut reader));
+    let font_header = ok!(ok!(font.take::<_, FontHeader>(&mut reader)));
+    let maximum_profile = ok!(ok!(font.take::<_, MaximumProfile>(&mut reader)));
+    let glyph_mapping = ok!(ok!(
+        font.take_given::<_, GlyphMapping>(&mut reader, (&font_header, &maximum_profile))
+    ));
+    let glyph_data = ok!(ok!(
+        font.take_given::<_, GlyphData>(&mut reader, &glyph_mapping)
+    ));
+    let font_variations = ok!(ok!(font.take::<_, FontVariations>(&mut reader)));
+    let table = ok!(ok!(font.take::<_, GlyphVariations>(&mut reader)));
+    assert!(table.axis_count == 2);
+    assert!(table.glyph_count == 61);
+    assert!(table.shared_tuples == [vec![16384, 16384], vec![16384, 0], vec![0, 16384]]);
+    assert!(table.records[0].is_none());
+
+    let record = ok!(table.records[2].as_ref());
+    assert!(record.tuple_count.count() == 3);
+    assert!(!record.tuple_count.has_shared_points());
+    assert!(record.headers[0].index.index() == 1);
+    assert!(record.headers[0].index.has_private_points());
+    assert!(record.headers[0].peak.is_none());
+    assert!(record.deltas[0].points.is_none());
+    assert!(record.deltas[0].x.len() == 12 + 4);
+    assert!(record.deltas[0].x[..4] == [3, 69, 78, 14]);
+
+    let glyph = ok!(glyph_data.0[2].as_ref());
+    let coordinates = font_variations.normalize_given(&[300.0, 1.0], None);
+    let deltas = ok!(table.deltas(2, Some(glyph), &coordinates));
+    assert!(deltas.iter().all(|&delta| delta == (0.0, 0.0)));
+    let coordinates = font_variations.normalize_given(&[500.0, 1.0], None);
+    let deltas = ok!(table.deltas(2, Some(glyph), &coordinates));
+    assert!(deltas[..2] == [(1.5, -0.5), (34.5, -0.5)]);
+    let coordinates = font_variations.normalize_given(&[700.0, 150.0], None);
+    let (other, phantoms) = ok!(table.apply(2, glyph, &coordinates));
+    assert!((other.min_x, other.min_y, other.max_x, other.max_y) == (9, 0, 670, 700));
+    assert!(phantoms == [(0.0, 0.0), (229.0, 0.0), (0.0, 0.0), (0.0, 0.0)]);
+    match other.description {
+        Description::Simple(ref description) => {
+            assert!(description.x[..3] == [274, 131, 265]);
+            assert!(!description.flags[0].is_x_short());
+        }
+        _ => unreachable!(),
+    }
+
+    let glyph = ok!(glyph_data.0[57].as_ref());
+    let (other, phantoms) = ok!(table.apply(57, glyph, &[16384, 0]));
+    assert!(phantoms[1] == (55.0, 0.0));
+    match other.description {
+        Description::Composite(ref description) => {
+            let component = &description.components[1];
+            assert!(matches!(component.arguments, Arguments::Offsets(166, 0)));
+            assert!(component.flags.are_arguments_words());
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn synthetic() {
+    #[rustfmt::skip]
+    let data: &[u8] = &[
+        0, 1, 0, 0, // majorVersion, minorVersion
+        0, 1, 0, 0, // axisCount, sharedTupleCount
+        0, 0, 0, 24, // sharedTuplesOffset
+        0, 1, 0, 0, // glyphCount, flags
+        0, 0, 0, 24, // glyphVariationDataArrayOffset
+        0, 0, 0, 19, // glyphVariationDataOffsets
+        0x80, 2, 0, 20, // tupleVariationCount, dataOffset
+        0, 11, 0xE0, 0, 0x20, 0, 0, 0, 0x40, 0, // tupleVariationHeaders[0]
+        0, 5, 0x80, 0, 0x40, 0, // tupleVariationHeaders[1]
+        0, // shared point numbers
+        2, 0x01, 0, 2, 0x01, 10, 30, 0x80, 0x40, 0, 20, // serialized data[0]
+        0x84, 0x00, 8, 0x81, 0x87, // serialized data[1]
+        0, // padding
+    ];
+    let table: GlyphVariations = ok!(Cursor::new(data).take());
+    let record = ok!(table.records[0].as_ref());
+    assert!(record.tuple_count.has_shared_points());
+    let header = &record.headers[0];
+    assert!(header.index.has_embedded_peak());
+    assert!(header.index.has_intermediate_region());
+    assert!(header.peak == Some(vec![8192]));
+    assert!(header.start == Some(vec![0]));
+    assert!(header.end == Some(vec![16384]));
+    assert!(record.deltas[0].points == Some(vec![0, 2]));
+    assert!(record.deltas[0].x == [10, 30]);
+    assert!(record.deltas[0].y == [0, 20]);
+    assert!(record.deltas[1].points.is_none());
+    assert!(record.deltas[1].x == [0, 0, 0, 0, 0, 8, 0, 0]);
+    assert!(record.deltas[1].y == [0; 8]);
+
+    let glyph = Glyph {
+        contour_count: 1,
+        max_x: 100,
+        max_y: 100,
+        description: Description::Simple(SimpleDescription {
+            end_points: vec![3],
+            flags: vec![PointFlags(1); 4],
+            x: vec![0, 50, 50, -100],
+            y: vec![0, 0, 100, 0],
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let deltas = ok!(table.deltas(0, Some(&glyph), &[0]));
+    assert!(deltas == [(0.0, 0.0); 8]);
+    let deltas = ok!(table.deltas(0, Some(&glyph), &[12288]));
+    assert!(
+        deltas
+            == [
+                (5.0, 0.0),
+                (10.0, 0.0),
+                (15.0, 10.0),
+                (5.0, 10.0),
+                (0.0, 0.0),
+                (6.0, 0.0),
+                (0.0, 0.0),
+                (0.0, 0.0),
+            ]
+    );
+    let (other, phantoms) = ok!(table.apply(0, &glyph, &[8192]));
+    assert!((other.min_x, other.min_y, other.max_x, other.max_y) == (10, 0, 130, 120));
+    assert!(phantoms == [(0.0, 0.0), (4.0, 0.0), (0.0, 0.0), (0.0, 0.0)]);
+    match other.description {
+        Description::Simple(ref description) => {
+            assert!(description.x == [10, 60, 60, -120]);
+            assert!(description.y == [0, 0, 120, 0]);
+        }
+        _ => unreachable!(),
+    }
+
+    let mut other = glyph.clone();
+    if let Description::Simple(ref mut description) = other.description {
+        description.x[0] = 32660;
+    }
+    assert!(table.apply(0, &other, &[0]).is_ok());
+    assert!(table.apply(0, &other, &[8192]).is_err());
+}
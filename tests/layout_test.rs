@@ -0,0 +1,206 @@
+extern crate opentype;
+extern crate truetype;
+
+use opentype::glyph_definition::GlyphDefinition;
+use opentype::glyph_positioning::{GlyphPositioning, PairAdjustment, Table};
+use opentype::layout::lookup::{Flags, Record};
+use opentype::layout::{Buffer, Class, Coverage, Matcher};
+use std::io::Cursor;
+use truetype::Value;
+
+#[macro_use]
+mod common;
+
+#[test]
+fn class() {
+    #[rustfmt::skip]
+    let data: &[u8] = &[
+        0, 1, 0, 3, 0, 3, 0, 1, 0, 0, 0, 2, // ClassDefFormat1
+    ];
+    let class: Class = ok!(Value::read(&mut Cursor::new(data)));
+    assert!(
+        (0..7)
+            .map(|glyph_id| class.get(glyph_id))
+            .collect::<Vec<_>>()
+            == [0, 0, 0, 1, 0, 2, 0]
+    );
+    assert!(class.glyphs_in(0, 7).collect::<Vec<_>>() == [0, 1, 2, 4, 6]);
+    assert!(class.glyphs_in(2, 7).collect::<Vec<_>>() == [5]);
+    assert!(class.iter().collect::<Vec<_>>() == [(3, 1), (4, 0), (5, 2)]);
+
+    #[rustfmt::skip]
+    let data: &[u8] = &[
+        0, 2, 0, 2, // ClassDefFormat2
+        0, 2, 0, 3, 0, 1, // ClassRangeRecord
+        0, 6, 0, 6, 0, 2, // ClassRangeRecord
+    ];
+    let class: Class = ok!(Value::read(&mut Cursor::new(data)));
+    assert!(
+        (0..8)
+            .map(|glyph_id| class.get(glyph_id))
+            .collect::<Vec<_>>()
+            == [0, 0, 1, 1, 0, 0, 2, 0]
+    );
+    assert!(class.glyphs_in(1, 8).collect::<Vec<_>>() == [2, 3]);
+    assert!(class.iter().collect::<Vec<_>>() == [(2, 1), (3, 1), (6, 2)]);
+
+    let GlyphPositioning { lookups, .. } = ok!(Value::read(&mut setup!(SourceSerifPro, "GPOS")));
+    match &lookups.records[0].tables[1] {
+        &Table::PairAdjustment(PairAdjustment::Format2(ref table)) => {
+            for (glyph_id, class) in table.class1.iter() {
+                assert!(class < table.class1_count);
+                assert!(table.class1.get(glyph_id) == class);
+            }
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn coverage() {
+    #[rustfmt::skip]
+    let data: &[u8] = &[
+        0, 1, 0, 3, 0, 2, 0, 5, 0, 9, // CoverageFormat1
+    ];
+    let coverage: Coverage = ok!(Value::read(&mut Cursor::new(data)));
+    assert!(coverage.len() == 3);
+    assert!(coverage.index(5) == Some(1));
+    assert!(coverage.index(4).is_none());
+    assert!(coverage.contains(9));
+    assert!(coverage.iter().collect::<Vec<_>>() == [(2, 0), (5, 1), (9, 2)]);
+
+    #[rustfmt::skip]
+    let data: &[u8] = &[
+        0, 2, 0, 2, // CoverageFormat2
+        0, 4, 0, 6, 0, 0, // RangeRecord
+        0, 10, 0, 11, 0, 3, // RangeRecord
+    ];
+    let coverage: Coverage = ok!(Value::read(&mut Cursor::new(data)));
+    assert!(coverage.len() == 5);
+    assert!(coverage.index(5) == Some(1));
+    assert!(coverage.index(11) == Some(4));
+    assert!(coverage.index(7).is_none());
+    assert!(!coverage.contains(3));
+    assert!(coverage.iter().collect::<Vec<_>>() == [(4, 0), (5, 1), (6, 2), (10, 3), (11, 4)]);
+
+    #[rustfmt::skip]
+    let data: &[u8] = &[
+        0, 2, 0, 1, // CoverageFormat2
+        0, 4, 0, 6, 255, 254, // RangeRecord
+    ];
+    let coverage: Coverage = ok!(Value::read(&mut Cursor::new(data)));
+    assert!(coverage.index(5) == Some(0xFFFF));
+    assert!(coverage.index(6).is_none());
+    assert!(coverage.iter().collect::<Vec<_>>() == [(4, 0xFFFE), (5, 0xFFFF)]);
+
+    let GlyphPositioning { lookups, .. } = ok!(Value::read(&mut setup!(SourceSerifPro, "GPOS")));
+    match &lookups.records[0].tables[0] {
+        &Table::PairAdjustment(PairAdjustment::Format1(ref table)) => {
+            assert!(table.coverage.len() == 65);
+            for (glyph_id, index) in table.coverage.iter() {
+                assert!(table.coverage.index(glyph_id) == Some(index));
+            }
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn feature_variations() {
+    use opentype::layout::feature::{Condition, Variations};
+    use opentype::writer;
+
+    #[rustfmt::skip]
+    let data: &[u8] = &[
+        0, 1, 0, 0, 0, 0, 0, 2, // FeatureVariations
+        0, 0, 0, 24, 0, 0, 0, 0, // FeatureVariationRecord
+        0, 0, 0, 0, 0, 0, 0, 38, // FeatureVariationRecord
+        0, 1, 0, 0, 0, 6, // ConditionSet
+        0, 1, 0, 0, 0x20, 0, 0x40, 0, // ConditionTableFormat1
+        0, 1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 12, // FeatureTableSubstitution
+        0, 0, 0, 1, 0, 5, // Feature
+    ];
+    let variations: Variations = ok!(Value::read(&mut Cursor::new(data)));
+    assert!(variations.conditions[0].is_some());
+    assert!(variations.conditions[1].is_none());
+    assert!(variations.substitutions[0].is_none());
+    assert!(variations.substitutions[1].is_some());
+    assert!(variations.find(&[16384]).is_none());
+    let substitutions = ok!(variations.find(&[0]));
+    assert!(substitutions.records[0].lookup_indices == [5]);
+    let substitutions = ok!(variations.find(&[]));
+    assert!(substitutions.records[0].lookup_indices == [5]);
+    assert!(ok!(writer::write(&variations)) == data);
+
+    #[rustfmt::skip]
+    let data: &[u8] = &[
+        0, 1, 0, 0, 0, 0, 0, 2, // FeatureVariations
+        0, 0, 0, 24, 0, 0, 0, 34, // FeatureVariationRecord
+        0, 0, 0, 0, 0, 0, 0, 0, // FeatureVariationRecord
+        0, 1, 0, 0, 0, 6, // ConditionSet
+        0, 2, 0, 0, // ConditionTableFormat2
+        0, 1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 12, // FeatureTableSubstitution
+        0, 0, 0, 1, 0, 5, // Feature
+    ];
+    let variations: Variations = ok!(Value::read(&mut Cursor::new(data)));
+    assert!(matches!(
+        ok!(variations.conditions[0].as_ref()).records[0],
+        Condition::Unknown(2)
+    ));
+    assert!(variations.find(&[0]).is_none());
+    assert!(writer::write(&variations).is_err());
+}
+
+#[test]
+fn matcher() {
+    #[rustfmt::skip]
+    let data: &[u8] = &[
+        0, 1, 0, 2, 0, 14, 0, 0, 0, 0, 0, 36, 0, 52, // GDEF
+        0, 2, 0, 3, // ClassDefFormat2
+        0, 1, 0, 1, 0, 1, // ClassRangeRecord
+        0, 2, 0, 2, 0, 2, // ClassRangeRecord
+        0, 3, 0, 5, 0, 3, // ClassRangeRecord
+        0, 2, 0, 2, // ClassDefFormat2
+        0, 3, 0, 4, 0, 1, // ClassRangeRecord
+        0, 5, 0, 5, 0, 2, // ClassRangeRecord
+        0, 1, 0, 1, 0, 0, 0, 8, // MarkGlyphSetsTable
+        0, 1, 0, 1, 0, 4, // CoverageFormat1
+    ];
+    let definition: GlyphDefinition = ok!(Value::read(&mut Cursor::new(data)));
+    let buffer = Buffer::from(&[1, 3, 2, 4, 5, 6][..]);
+    let visible = |flags, mark_filtering_set| {
+        let record = Record::<()> {
+            kind: 1,
+            flags: Flags(flags),
+            table_count: 0,
+            table_offsets: vec![],
+            mark_filtering_set: mark_filtering_set,
+            tables: vec![],
+        };
+        let matcher = Matcher::new(&record, Some(&definition));
+        matcher.following(&buffer, 0).collect::<Vec<_>>()
+    };
+    assert!(Flags(0x0200).mark_attachment_type() == 2);
+    assert!(visible(0x0000, None) == [1, 2, 3, 4, 5]);
+    assert!(visible(0x0002, None) == [1, 2, 3, 4, 5]);
+    assert!(visible(0x0004, None) == [1, 3, 4, 5]);
+    assert!(visible(0x0008, None) == [2, 5]);
+    assert!(visible(0x0100, None) == [1, 2, 3, 5]);
+    assert!(visible(0x0200, None) == [2, 4, 5]);
+    assert!(visible(0x0010, Some(0)) == [2, 3, 5]);
+
+    let record = Record::<()> {
+        kind: 1,
+        flags: Flags(0x0008),
+        table_count: 0,
+        table_offsets: vec![],
+        mark_filtering_set: None,
+        tables: vec![],
+    };
+    let matcher = Matcher::new(&record, Some(&definition));
+    assert!(matcher.preceding(&buffer, 5).collect::<Vec<_>>() == [2, 0]);
+    assert!(matcher.next(&buffer, 2) == Some(5));
+    assert!(matcher.previous(&buffer, 2) == Some(0));
+    let matcher = Matcher::new(&record, None);
+    assert!(matcher.next(&buffer, 0) == Some(1));
+}
@@ -0,0 +1,323 @@
+extern crate opentype;
+extern crate postscript;
+extern crate truetype;
+
+use opentype::subset;
+use opentype::Font;
+use std::io::{Cursor, Read, Seek};
+use truetype::char_mapping::Encoding;
+use truetype::{CharMapping, GlyphID, HorizontalHeader, HorizontalMetrics, MaximumProfile};
+
+#[macro_use]
+mod common;
+
+#[test]
+fn cff() {
+    use opentype::layout::Buffer;
+    use opentype::GlyphSubstitution;
+    use postscript::compact1::FontSet;
+    use truetype::Tag;
+
+    let mut reader = setup!(SourceSerifPro);
+    let font = ok!(Font::read(&mut reader));
+    let data = ok!(subset::subset(&font, &mut reader, &[0x61, 0x66, 0x69], &[]));
+    let mut other_reader = Cursor::new(data);
+    let other = ok!(Font::read(&mut other_reader));
+    assert!(other.offset_table.records.len() == 12);
+
+    let mapping = map(&other, &mut other_reader);
+    assert!(mapping == [(0x61, 1), (0x66, 2), (0x69, 3)]);
+    let original_mapping = map(&font, &mut reader);
+    let original = |code_point| {
+        original_mapping
+            .iter()
+            .find(|&&(other, _)| other == code_point)
+            .unwrap()
+            .1
+    };
+
+    let profile = ok!(ok!(other.take::<_, MaximumProfile>(&mut other_reader)));
+    assert!(profile.glyph_count() == 9);
+    let set = ok!(ok!(font.take::<_, FontSet>(&mut reader)));
+    let other_set = ok!(ok!(other.take::<_, FontSet>(&mut other_reader)));
+    assert!(other_set.char_strings[0].len() == 9);
+    assert!(other_set.global_subroutines.len() == set.global_subroutines.len());
+    for &(code_point, glyph_id) in &mapping {
+        let original_glyph_id = original(code_point) as usize;
+        assert!(
+            other_set.char_strings[0][glyph_id as usize] == set.char_strings[0][original_glyph_id]
+        );
+    }
+    assert!(
+        metrics(&other, &mut other_reader)[1..4]
+            == mapping
+                .iter()
+                .map(|&(code_point, _)| metrics(&font, &mut reader)[original(code_point) as usize])
+                .collect::<Vec<_>>()[..]
+    );
+
+    let table = ok!(ok!(other.take::<_, GlyphSubstitution>(&mut other_reader)));
+    let indices = table.lookup_indices(Tag(*b"latn"), None, &[Tag(*b"liga")]);
+    let mut buffer = Buffer::from(&[2, 3, 1][..]);
+    table.apply(&mut buffer, &indices, None);
+    let glyph_ids = buffer.glyph_ids();
+    assert!(glyph_ids.len() == 2);
+    assert!(glyph_ids[0] > 3 && glyph_ids[0] < 9);
+    assert!(glyph_ids[1] == 1);
+}
+
+#[test]
+fn ttf() {
+    use opentype::GlyphDefinition;
+    use truetype::glyph_data::Description;
+    use truetype::{FontHeader, GlyphData, GlyphMapping};
+
+    let mut reader = setup!(OpenSans);
+    let font = ok!(Font::read(&mut reader));
+    let data = ok!(subset::subset(&font, &mut reader, &[0x41, 0xE9], &[]));
+    let mut other_reader = Cursor::new(data);
+    let other = ok!(Font::read(&mut other_reader));
+    let _ = ok!(ok!(other.take::<_, GlyphDefinition>(&mut other_reader)));
+
+    let mapping = map(&other, &mut other_reader);
+    assert!(mapping == [(0x41, 1), (0xE9, 4)]);
+
+    let font_header = ok!(ok!(other.take::<_, FontHeader>(&mut other_reader)));
+    let profile = ok!(ok!(other.take::<_, MaximumProfile>(&mut other_reader)));
+    assert!(profile.glyph_count() == 5);
+    let glyph_mapping = ok!(ok!(
+        other.take_given::<_, GlyphMapping>(&mut other_reader, (&font_header, &profile))
+    ));
+    let glyph_data = ok!(ok!(
+        other.take_given::<_, GlyphData>(&mut other_reader, &glyph_mapping)
+    ));
+    match glyph_data.0[4].as_ref().unwrap().description {
+        Description::Composite(ref description) => {
+            let glyph_ids = description
+                .components
+                .iter()
+                .map(|component| component.glyph_index)
+                .collect::<Vec<_>>();
+            assert!(glyph_ids == [2, 3]);
+        }
+        _ => unreachable!(),
+    }
+
+    let metrics = metrics(&other, &mut other_reader);
+    assert!(
+        metrics
+            == [
+                (1229, 193),
+                (1137, -117),
+                (1010, 98),
+                (1135, 532),
+                (1010, 98)
+            ]
+    );
+}
+
+#[test]
+fn glyph_ids() {
+    let mut reader = setup!(OpenSans);
+    let font = ok!(Font::read(&mut reader));
+    let data = ok!(subset::subset(&font, &mut reader, &[], &[36]));
+    let mut other_reader = Cursor::new(data);
+    let other = ok!(Font::read(&mut other_reader));
+    assert!(map(&other, &mut other_reader) == [(0x41, 1)]);
+    let profile = ok!(ok!(other.take::<_, MaximumProfile>(&mut other_reader)));
+    assert!(profile.glyph_count() == 2);
+}
+
+#[test]
+fn variable() {
+    let mut reader = setup!(AdobeVFPrototype);
+    let font = ok!(Font::read(&mut reader));
+    assert!(subset::subset(&font, &mut reader, &[0x41], &[]).is_err());
+
+    let mut reader = setup!(Gingham);
+    let font = ok!(Font::read(&mut reader));
+    let data = ok!(subset::subset(&font, &mut reader, &[0x41], &[]));
+    let other = ok!(Font::read(&mut Cursor::new(data)));
+    assert!(other
+        .offset_table
+        .records
+        .iter()
+        .all(|record| !matches!(&*record.tag, b"fvar" | b"gvar" | b"HVAR")));
+}
+
+#[test]
+fn variable_layout() {
+    use opentype::glyph_positioning::{PairAdjustment, Table};
+    use opentype::layout::Correction;
+    use opentype::{Builder, GlyphDefinition, GlyphPositioning, GlyphSubstitution};
+    use truetype::Tag;
+
+    let values = |table: &GlyphPositioning| {
+        let mut values = vec![];
+        for table in table
+            .lookups
+            .records
+            .iter()
+            .flat_map(|record| &record.tables)
+        {
+            match *table {
+                Table::PairAdjustment(PairAdjustment::Format1(ref table)) => {
+                    for record in table.sets.iter().flat_map(|set| &set.records) {
+                        values.extend([record.value1.clone(), record.value2.clone()]);
+                    }
+                }
+                Table::PairAdjustment(PairAdjustment::Format2(ref table)) => {
+                    for record in table.sets.iter().flat_map(|set| &set.records) {
+                        values.extend([record.value1.clone(), record.value2.clone()]);
+                    }
+                }
+                _ => {}
+            }
+        }
+        let varied = values
+            .iter()
+            .flat_map(|value| {
+                [
+                    &value.x_placement_correction,
+                    &value.y_placement_correction,
+                    &value.x_advance_correction,
+                    &value.y_advance_correction,
+                ]
+            })
+            .filter(|correction| matches!(correction, Some(Correction::Variation(_))))
+            .count();
+        (values.len(), varied)
+    };
+
+    let mut reader = setup!(AdobeVFPrototype);
+    let font = ok!(Font::read(&mut reader));
+    let tables = [b"GDEF", b"GPOS", b"GSUB"]
+        .iter()
+        .map(|&tag| (Tag(*tag), ok!(ok!(font.take_bytes(&mut reader, Tag(*tag))))))
+        .collect::<Vec<_>>();
+    let mut reader = setup!(Gingham);
+    let font = ok!(Font::read(&mut reader));
+    let mut builder = Builder::new(font.offset_table.header.version);
+    for record in &font.offset_table.records {
+        builder.add(
+            record.tag,
+            ok!(ok!(font.take_bytes(&mut reader, record.tag))),
+        );
+    }
+    for (tag, data) in tables {
+        builder.add(tag, data);
+    }
+    let mut reader = Cursor::new(ok!(builder.build()));
+    let font = ok!(Font::read(&mut reader));
+    let table = ok!(ok!(font.take::<_, GlyphPositioning>(&mut reader)));
+    assert!(values(&table).1 > 0);
+
+    let glyph_ids = (0..61).collect::<Vec<_>>();
+    let data = ok!(subset::subset(&font, &mut reader, &[], &glyph_ids));
+    let mut reader = Cursor::new(data);
+    let font = ok!(Font::read(&mut reader));
+    assert!(font
+        .offset_table
+        .records
+        .iter()
+        .all(|record| &*record.tag != b"STAT"));
+    let table = ok!(ok!(font.take::<_, GlyphDefinition>(&mut reader)));
+    assert!(table.variations.is_none());
+    let table = ok!(ok!(font.take::<_, GlyphSubstitution>(&mut reader)));
+    assert!(table.variations.is_none());
+    let table = ok!(ok!(font.take::<_, GlyphPositioning>(&mut reader)));
+    assert!(table.variations.is_none());
+    let (count, varied) = values(&table);
+    assert!(count > 0);
+    assert!(varied == 0);
+}
+
+#[test]
+fn large_char_mapping() {
+    use opentype::Builder;
+
+    let mut reader = setup!(OpenSans);
+    let font = ok!(Font::read(&mut reader));
+    for &count in &[10000, 0xC000] {
+        let characters = (0..count)
+            .map(|i| (0x1000 + i, 2 - (i % 2) as GlyphID))
+            .collect::<Vec<_>>();
+        let mut builder = Builder::new(font.offset_table.header.version);
+        for record in &font.offset_table.records {
+            let data = match &*record.tag {
+                b"cmap" => char_mapping(&characters),
+                _ => ok!(ok!(font.take_bytes(&mut reader, record.tag))),
+            };
+            builder.add(record.tag, data);
+        }
+        let mut other_reader = Cursor::new(ok!(builder.build()));
+        let other = ok!(Font::read(&mut other_reader));
+        let code_points = characters
+            .iter()
+            .map(|&(code_point, _)| code_point)
+            .collect::<Vec<_>>();
+        let data = ok!(subset::subset(&other, &mut other_reader, &code_points, &[]));
+        let mut other_reader = Cursor::new(data);
+        let other = ok!(Font::read(&mut other_reader));
+        let table = ok!(ok!(other.take::<_, CharMapping>(&mut other_reader)));
+        assert!(table.encodings.len() == 1);
+        match table.encodings[0] {
+            Encoding::Format4(_) if count == 10000 => {
+                assert!(map(&other, &mut other_reader) == characters);
+            }
+            Encoding::Format12(ref encoding) if count == 0xC000 => {
+                let mut mapping = encoding.mapping().into_iter().collect::<Vec<_>>();
+                mapping.sort();
+                assert!(mapping == characters);
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+fn map<T: Read + Seek>(font: &Font, tape: &mut T) -> Vec<(u32, GlyphID)> {
+    let mapping = ok!(ok!(font.take::<_, CharMapping>(tape)));
+    let mut result = vec![];
+    for encoding in &mapping.encodings {
+        if let Encoding::Format4(ref encoding) = *encoding {
+            for (code_point, glyph_id) in encoding.mapping() {
+                if glyph_id != 0 {
+                    result.push((code_point as u32, glyph_id));
+                }
+            }
+        }
+    }
+    result.sort();
+    result.dedup();
+    result
+}
+
+fn char_mapping(characters: &[(u32, GlyphID)]) -> Vec<u8> {
+    let mut data = vec![];
+    for value in &[0u16, 1, 3, 10] {
+        data.extend_from_slice(&value.to_be_bytes());
+    }
+    data.extend_from_slice(&12u32.to_be_bytes());
+    data.extend_from_slice(&12u16.to_be_bytes());
+    data.extend_from_slice(&0u16.to_be_bytes());
+    data.extend_from_slice(&((16 + 12 * characters.len()) as u32).to_be_bytes());
+    data.extend_from_slice(&0u32.to_be_bytes());
+    data.extend_from_slice(&(characters.len() as u32).to_be_bytes());
+    for &(code_point, glyph_id) in characters {
+        data.extend_from_slice(&code_point.to_be_bytes());
+        data.extend_from_slice(&code_point.to_be_bytes());
+        data.extend_from_slice(&(glyph_id as u32).to_be_bytes());
+    }
+    data
+}
+
+fn metrics<T: Read + Seek>(font: &Font, tape: &mut T) -> Vec<(u16, i16)> {
+    let profile = ok!(ok!(font.take::<_, MaximumProfile>(tape)));
+    let header = ok!(ok!(font.take::<_, HorizontalHeader>(tape)));
+    let metrics = ok!(ok!(
+        font.take_given::<_, HorizontalMetrics>(tape, (&header, &profile))
+    ));
+    (0..profile.glyph_count())
+        .map(|glyph_id| metrics.get(glyph_id))
+        .collect()
+}
@@ -0,0 +1,32 @@
+extern crate opentype;
+extern crate truetype;
+
+use opentype::variation::item::Variations;
+use std::io::{Seek, SeekFrom};
+use truetype::Value;
+
+#[macro_use]
+mod common;
+
+#[test]
+fn item() {
+    let mut tape = setup!(AdobeVFPrototype, "GDEF");
+    ok!(tape.seek(SeekFrom::Current(18)));
+    let table: Variations = ok!(Value::read(&mut tape));
+    assert!(table.regions.axis_count == 2);
+    assert!(table.regions.count == 5);
+    let axis = &table.regions.records[0].axes[0];
+    assert!((axis.start, axis.peak, axis.end) == (-16384, -10355, 0));
+    assert!(table.records.len() == 1);
+    let record = &table.records[0];
+    assert!(record.item_count == 3613);
+    assert!(record.deltas.len() == 3613);
+    assert!(record.deltas[0] == [-20, -20, 0, 0, 0]);
+    assert!(record.deltas[3] == [-40, 0, 0, 0, 0]);
+    assert!(ok!(table.delta(0, 0, &[0, 0])) == 0.0);
+    assert!(ok!(table.delta(0, 0, &[-10355, 0])) == -20.0);
+    assert!(ok!(table.delta(0, 0, &[-16384, 0])) == -20.0);
+    assert!((ok!(table.delta(0, 0, &[-8192])) + 20.0 * 8192.0 / 10355.0).abs() < 1e-3);
+    assert!(table.delta(1, 0, &[]).is_none());
+    assert!(table.delta(0, 3613, &[]).is_none());
+}